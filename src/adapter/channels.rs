@@ -0,0 +1,347 @@
+use crate::adapter::websocket::{RewindSession, SessionStore, session_events};
+use crate::clock::SharedClock;
+use crate::interface::{
+    BroadcastSink, DeliveryReport, DeliveryState, SessionContext, TranscriptRecord,
+    WebSocketMessage,
+};
+use crate::playback::{PlaybackStep, deliver};
+use crate::script::{ReplyReceiver, ReplySender};
+use crate::stats::utterance_secs;
+use crate::transcript::{format_time, parse_time_to_time};
+use futures_util::{Sink, SinkExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+/// Messages buffered for a slow stream before it starts missing them
+const CHANNEL_CAPACITY: usize = 1024;
+
+fn default_wait_secs() -> u64 {
+    10
+}
+
+/// Channel separation for a WebSocket session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChannelOptions {
+    /// Speakers in channel order
+    pub speakers: Vec<String>,
+    /// Seconds playback waits, from the first stream, for a stream per speaker
+    #[serde(default = "default_wait_secs")]
+    pub wait_secs: u64,
+}
+
+impl ChannelOptions {
+    pub fn new(speakers: Vec<String>, wait_secs: Option<u64>) -> Self {
+        Self {
+            speakers,
+            wait_secs: wait_secs.unwrap_or_else(default_wait_secs),
+        }
+    }
+
+    /// Channel of a speaker
+    pub fn channel(&self, speaker: &str) -> Option<usize> {
+        self.speakers.iter().position(|s| s == speaker)
+    }
+
+    /// The speaker a stream asked for, as spelled in the transcript
+    pub fn find_speaker(&self, speaker: &str) -> Option<&str> {
+        self.speakers
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case(speaker))
+            .map(String::as_str)
+    }
+}
+
+/// Speakers in order of first appearance, which is their channel order
+pub fn channel_order(records: &[TranscriptRecord]) -> Vec<String> {
+    let mut speakers: Vec<String> = Vec::new();
+    for record in records {
+        if !speakers.contains(&record.speaker) {
+            speakers.push(record.speaker.clone());
+        }
+    }
+    speakers
+}
+
+/// A record as sent on a channel-separated session, with its channel and estimated end
+///
+/// Utterances overlap when one starts before the previous one's `end`; each is still
+/// sent at its own start time, so both parties are heard talking at once.
+pub fn channel_message(
+    session: &SessionContext,
+    channel: Option<usize>,
+    record: TranscriptRecord,
+) -> WebSocketMessage {
+    let start = parse_time_to_time(&record.time).max(0) as u64;
+    let end = start + utterance_secs(&record.sentence).ceil() as u64;
+    WebSocketMessage {
+        channel,
+        end: Some(format_time(end)),
+        ..WebSocketMessage::new(session, record)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ChannelEvent {
+    Message { speaker: String, text: String },
+    Finished(DeliveryReport),
+}
+
+/// One playback of a channel-separated session, shared by all of its streams
+///
+/// Playback starts once a stream for the whole session, or one for every speaker, is
+/// connected, or `wait_secs` after the first stream. It stops when every stream is gone.
+#[derive(Debug)]
+pub struct ChannelHub {
+    events: broadcast::Sender<ChannelEvent>,
+    replies: ReplySender,
+    /// Connected streams by speaker, `None` for the whole session
+    streams: Mutex<HashMap<Option<String>, usize>>,
+    ready: Notify,
+    cancel: CancellationToken,
+}
+
+impl ChannelHub {
+    fn connect(&self, speaker: &Option<String>, options: &ChannelOptions) {
+        let mut streams = self.streams.lock().unwrap();
+        *streams.entry(speaker.clone()).or_default() += 1;
+        let every_speaker = options
+            .speakers
+            .iter()
+            .all(|s| streams.contains_key(&Some(s.clone())));
+        if speaker.is_none() || every_speaker {
+            self.ready.notify_one();
+        }
+    }
+
+    fn disconnect(&self, speaker: &Option<String>) {
+        let mut streams = self.streams.lock().unwrap();
+        if let Some(count) = streams.get_mut(speaker) {
+            *count -= 1;
+            if *count == 0 {
+                streams.remove(speaker);
+            }
+        }
+        if streams.is_empty() {
+            self.cancel.cancel();
+        }
+    }
+}
+
+struct ChannelSink {
+    hub: Arc<ChannelHub>,
+    options: ChannelOptions,
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for ChannelSink {
+    fn describe(&self) -> String {
+        "websocket channels".to_string()
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let channel = self.options.channel(&step.record.speaker);
+        let message = channel_message(session, channel, step.record.clone());
+        let text = serde_json::to_string(&message)?;
+        // No stream listening right now is not an error
+        let _ = self.hub.events.send(ChannelEvent::Message {
+            speaker: step.record.speaker.clone(),
+            text,
+        });
+        Ok(())
+    }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn close(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Waits for the streams, plays the session to all of them, then removes it
+async fn run_hub(
+    session_id: String,
+    session: RewindSession,
+    options: ChannelOptions,
+    hub: Arc<ChannelHub>,
+    replies: ReplyReceiver,
+    sessions: SessionStore,
+    clock: SharedClock,
+) {
+    tokio::select! {
+        _ = hub.ready.notified() => {}
+        _ = clock.sleep(Duration::from_secs(options.wait_secs)) => {
+            tracing::info!("Starting session {} without a stream for every speaker", session_id);
+        }
+        _ = hub.cancel.cancelled() => {}
+    }
+
+    let context = session.session.clone();
+    let events = session_events(&session_id, session, replies, clock);
+    let sink = Arc::new(ChannelSink {
+        hub: hub.clone(),
+        options,
+    });
+    let report = deliver(sink, context, events, hub.cancel.clone())
+        .report()
+        .await;
+
+    sessions.lock().await.remove(&session_id);
+    tracing::info!(
+        "Channel session {} {} and cleaned up",
+        session_id,
+        report.state.as_str()
+    );
+    let _ = hub.events.send(ChannelEvent::Finished(report));
+}
+
+/// Streams a channel-separated session to a client, whole or for one speaker
+///
+/// The first stream sets up the session's shared playback; later ones join it live.
+/// Replies from every stream reach the session's script or interactive turns. Streams
+/// get `SESSION_NOT_FOUND` for unknown sessions, `CHANNELS_NOT_ENABLED` for sessions
+/// without channel separation and `SPEAKER_NOT_FOUND` for unknown speakers.
+pub async fn join_channel_session<S>(
+    session_id: &str,
+    speaker: Option<&str>,
+    ws_sender: S,
+    mut replies: ReplyReceiver,
+    sessions: SessionStore,
+    clock: SharedClock,
+    cancel: CancellationToken,
+) -> anyhow::Result<DeliveryReport>
+where
+    S: Sink<String> + Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    let mut ws_sender = Box::pin(ws_sender);
+    let joined = {
+        let mut sessions_guard = sessions.lock().await;
+        match sessions_guard.get_mut(session_id) {
+            None => Err(("SESSION_NOT_FOUND", "Session not found")),
+            Some(session) => match session.channels.clone() {
+                None => Err(("CHANNELS_NOT_ENABLED", "Session has no channels")),
+                Some(options) => match speaker.map(|s| options.find_speaker(s)) {
+                    Some(None) => Err(("SPEAKER_NOT_FOUND", "Speaker not found")),
+                    Some(Some(found)) => Ok((session, options.clone(), Some(found.to_string()))),
+                    None => Ok((session, options, None)),
+                },
+            },
+        }
+        .map(|(session, options, speaker)| {
+            let hub = match &session.hub {
+                Some(hub) => hub.clone(),
+                None => {
+                    let (reply_sender, reply_receiver) = mpsc::unbounded_channel();
+                    let hub = Arc::new(ChannelHub {
+                        events: broadcast::channel(CHANNEL_CAPACITY).0,
+                        replies: reply_sender,
+                        streams: Mutex::new(HashMap::new()),
+                        ready: Notify::new(),
+                        cancel: CancellationToken::new(),
+                    });
+                    tokio::spawn(run_hub(
+                        session_id.to_string(),
+                        session.clone(),
+                        options.clone(),
+                        hub.clone(),
+                        reply_receiver,
+                        sessions.clone(),
+                        clock,
+                    ));
+                    session.hub = Some(hub.clone());
+                    hub
+                }
+            };
+            let events = hub.events.subscribe();
+            hub.connect(&speaker, &options);
+            (hub, events, speaker)
+        })
+    };
+
+    let (hub, mut events, speaker) = match joined {
+        Ok(joined) => joined,
+        Err((message, error)) => {
+            ws_sender
+                .send(message.to_string())
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to send {} message: {}", message, e))?;
+            return Ok(DeliveryReport {
+                state: DeliveryState::Failed,
+                delivered: 0,
+                error: Some(error.to_string()),
+            });
+        }
+    };
+
+    let reply_hub = hub.clone();
+    tokio::spawn(async move {
+        while let Some(reply) = replies.recv().await {
+            let _ = reply_hub.replies.send(reply);
+        }
+    });
+
+    let mut delivered = 0;
+    let streamed = async {
+        loop {
+            let event = tokio::select! {
+                _ = cancel.cancelled() => {
+                    return Ok(DeliveryReport {
+                        state: DeliveryState::Cancelled,
+                        delivered,
+                        error: None,
+                    });
+                }
+                event = events.recv() => event,
+            };
+            match event {
+                Ok(ChannelEvent::Message {
+                    speaker: from,
+                    text,
+                }) => {
+                    if speaker.as_ref().is_none_or(|speaker| *speaker == from) {
+                        ws_sender
+                            .send(text)
+                            .await
+                            .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))?;
+                        delivered += 1;
+                    }
+                }
+                Ok(ChannelEvent::Finished(report)) => {
+                    let sent = if report.is_completed() {
+                        ws_sender.send("SESSION_COMPLETE".to_string()).await
+                    } else {
+                        ws_sender.close().await
+                    };
+                    sent.map_err(|e| anyhow::anyhow!("Failed to end stream: {}", e))?;
+                    return Ok(DeliveryReport {
+                        delivered,
+                        ..report
+                    });
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        "Stream of session {} missed {} messages",
+                        session_id,
+                        missed
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    return Ok(DeliveryReport {
+                        state: DeliveryState::Cancelled,
+                        delivered,
+                        error: None,
+                    });
+                }
+            }
+        }
+    };
+    let report = streamed.await;
+    hub.disconnect(&speaker);
+    report
+}
@@ -0,0 +1,510 @@
+use crate::batch::BatchOptions;
+use crate::chaos::{ChaosDisconnect, ChaosOptions};
+use crate::interface::{
+    BroadcastHandle, BroadcastMessage, BroadcastSink, Broadcaster, DeliveryReport, DeliveryState,
+    ProgressEvent, SessionContext,
+};
+use crate::playback::{EventStream, PlaybackStep, deliver};
+use crate::playlist::PlaylistOptions;
+use crate::redact::RedactionOptions;
+use crate::report::WebhookExpectations;
+use crate::session::SessionParams;
+use crate::speakers::SpeakerOverrides;
+use futures_util::StreamExt;
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+/// Directory file log sinks are written to
+pub const BROADCAST_LOG_DIR: &str = "broadcast_logs";
+
+/// Finished broadcasts kept for `GET /broadcasts/{broadcast_id}`; the oldest are dropped first
+pub const MAX_FINISHED_BROADCASTS: usize = 100;
+
+/// Stream to WebSocket subscribers connected to the broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Object)]
+pub struct WebSocketSinkConfig {
+    /// Optional label shown in the broadcast status
+    pub label: Option<String>,
+}
+
+/// POST every message to a webhook
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct WebhookSinkConfig {
+    /// Webhook URL; defaults to the configured n8n webhook
+    pub url: Option<String>,
+    /// Named target from the webhook targets file, with its payload templates; overrides `url`
+    pub target: Option<String>,
+    /// Use the test n8n webhook when no URL is given
+    #[oai(default)]
+    #[serde(default)]
+    pub use_test: bool,
+    /// Group records into array payloads instead of one request per record
+    #[oai(default)]
+    #[serde(default)]
+    pub batch: BatchOptions,
+    /// Expectations evaluated into the sink's response report
+    #[oai(default)]
+    #[serde(default)]
+    pub expectations: WebhookExpectations,
+}
+
+/// Append every message as a JSON line to a file in `broadcast_logs/`
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct FileSinkConfig {
+    /// Log filename
+    pub filename: String,
+}
+
+/// One destination of a composite broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Union)]
+#[oai(discriminator_name = "type")]
+#[serde(tag = "type")]
+pub enum SinkConfig {
+    #[oai(mapping = "websocket")]
+    #[serde(rename = "websocket")]
+    WebSocket(WebSocketSinkConfig),
+    #[oai(mapping = "webhook")]
+    #[serde(rename = "webhook")]
+    Webhook(WebhookSinkConfig),
+    #[oai(mapping = "file")]
+    #[serde(rename = "file")]
+    File(FileSinkConfig),
+}
+
+fn default_filename() -> String {
+    "intake_call_test.csv".to_string()
+}
+
+/// A composite broadcast: one transcript and session played into several sinks
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct CompositeBroadcastRequest {
+    /// Transcript filename
+    #[oai(default = "default_filename")]
+    #[serde(default = "default_filename")]
+    pub filename: String,
+    /// Job description enrichment session ID (if applicable)
+    pub job_description_enrichment_session: Option<i64>,
+    /// Candidate profile enrichment session ID (if applicable)
+    pub candidate_profile_enrichment_session: Option<i64>,
+    /// Kind of session given by `session_id`, for kinds without their own field
+    pub session_kind: Option<String>,
+    /// Session ID (numeric or text) of the `session_kind` session
+    pub session_id: Option<String>,
+    /// Destinations that receive the same timeline
+    pub sinks: Vec<SinkConfig>,
+    /// Network fault injection applied to the shared timeline
+    #[oai(default)]
+    #[serde(default)]
+    pub chaos: ChaosOptions,
+    /// Seconds to wait before playback starts, giving WebSocket subscribers time to connect
+    #[oai(default)]
+    #[serde(default)]
+    pub start_delay_secs: u64,
+    /// Several transcripts (or slices of them) played as one session; replaces `filename`
+    #[oai(default)]
+    #[serde(default)]
+    pub playlist: Option<PlaylistOptions>,
+    /// Speaker renames, roles and anonymization
+    #[oai(default)]
+    #[serde(default)]
+    pub speakers: SpeakerOverrides,
+    /// Redact or pseudonymize PII before broadcasting; unset broadcasts the transcript as is
+    #[oai(default)]
+    #[serde(default)]
+    pub redaction: Option<RedactionOptions>,
+}
+
+impl CompositeBroadcastRequest {
+    /// Transcript, or playlist of transcripts, for logs and status
+    pub fn describe_transcript(&self) -> String {
+        match &self.playlist {
+            Some(playlist) => playlist.describe(),
+            None => self.filename.clone(),
+        }
+    }
+
+    /// Every transcript filename the broadcast plays
+    pub fn transcript_filenames(&self) -> Vec<&str> {
+        match &self.playlist {
+            Some(playlist) => playlist
+                .items
+                .iter()
+                .map(|item| item.filename.as_str())
+                .collect(),
+            None => vec![self.filename.as_str()],
+        }
+    }
+
+    pub fn session_params(&self) -> SessionParams {
+        SessionParams {
+            job_description_enrichment_session: self
+                .job_description_enrichment_session
+                .map(|id| id.to_string()),
+            candidate_profile_enrichment_session: self
+                .candidate_profile_enrichment_session
+                .map(|id| id.to_string()),
+            session_kind: self.session_kind.clone(),
+            session_id: self.session_id.clone(),
+        }
+    }
+}
+
+/// Publishes messages to every WebSocket subscriber of a broadcast
+pub struct SubscriberSink {
+    pub label: String,
+    pub subscribers: broadcast::Sender<String>,
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for SubscriberSink {
+    fn describe(&self) -> String {
+        self.label.clone()
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let message = BroadcastMessage::new(session, step.record.clone());
+        // Having no subscriber connected is not an error
+        let _ = self.subscribers.send(serde_json::to_string(&message)?);
+        Ok(())
+    }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        let _ = self.subscribers.send("SESSION_COMPLETE".to_string());
+        Ok(())
+    }
+}
+
+pub struct FileSink {
+    pub path: PathBuf,
+    file: Mutex<tokio::fs::File>,
+}
+
+impl FileSink {
+    /// Creates (or appends to) a log file in `broadcast_logs/`
+    pub async fn open(filename: &str) -> anyhow::Result<Self> {
+        if filename.is_empty()
+            || filename.contains('/')
+            || filename.contains('\\')
+            || filename.starts_with('.')
+        {
+            return Err(anyhow::anyhow!("Invalid log filename: {}", filename));
+        }
+
+        tokio::fs::create_dir_all(BROADCAST_LOG_DIR).await?;
+        let path = PathBuf::from(BROADCAST_LOG_DIR).join(filename);
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    async fn write_line(&self, line: String) -> anyhow::Result<()> {
+        let mut file = self.file.lock().await;
+        file.write_all(line.as_bytes()).await?;
+        file.write_all(b"\n").await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for FileSink {
+    fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let message = BroadcastMessage::new(session, step.record.clone());
+        self.write_line(serde_json::to_string(&message)?).await
+    }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        self.write_line("SESSION_COMPLETE".to_string()).await
+    }
+}
+
+/// Delivery state of one sink
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct SinkStatus {
+    /// Sink description
+    pub sink: String,
+    /// pending, running, completed, failed, interrupted or cancelled
+    pub state: String,
+    /// Number of messages delivered
+    pub delivered: usize,
+    /// Error that stopped this sink, if any
+    pub error: Option<String>,
+    /// Response report of a webhook sink, at `GET /reports/{report_id}`
+    pub report_id: Option<String>,
+}
+
+/// State of a composite broadcast and each of its sinks
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct BroadcastStatus {
+    /// Broadcast ID
+    pub broadcast_id: String,
+    /// Transcript filename being broadcast
+    pub filename: String,
+    /// pending, running, completed, failed, interrupted or cancelled
+    pub state: String,
+    /// Per-sink delivery state
+    pub sinks: Vec<SinkStatus>,
+    /// Finish time (RFC 3339), once every sink has stopped
+    pub finished_at: Option<String>,
+}
+
+pub struct BroadcastEntry {
+    pub status: BroadcastStatus,
+    /// Channel feeding WebSocket subscribers, while the broadcast is live
+    pub subscribers: Option<broadcast::Sender<String>>,
+    /// Stops the broadcast, e.g. from `POST /broadcasts/{broadcast_id}/cancel`
+    pub cancel: CancellationToken,
+}
+
+pub type BroadcastStore = Arc<Mutex<HashMap<String, BroadcastEntry>>>;
+
+/// Drops the oldest finished broadcasts beyond `MAX_FINISHED_BROADCASTS`; live ones are kept
+fn evict_finished(broadcasts: &mut HashMap<String, BroadcastEntry>) {
+    let mut finished: Vec<(String, String)> = broadcasts
+        .values()
+        .filter_map(|entry| {
+            Some((
+                entry.status.finished_at.clone()?,
+                entry.status.broadcast_id.clone(),
+            ))
+        })
+        .collect();
+    if finished.len() <= MAX_FINISHED_BROADCASTS {
+        return;
+    }
+    finished.sort();
+    for (_, broadcast_id) in &finished[..finished.len() - MAX_FINISHED_BROADCASTS] {
+        broadcasts.remove(broadcast_id);
+    }
+}
+
+/// Registers a new broadcast, evicting old finished ones
+pub async fn insert_broadcast(store: &BroadcastStore, entry: BroadcastEntry) {
+    let mut broadcasts = store.lock().await;
+    broadcasts.insert(entry.status.broadcast_id.clone(), entry);
+    evict_finished(&mut broadcasts);
+}
+
+/// Shared handle used by a composite broadcaster to publish its status
+#[derive(Clone)]
+pub struct BroadcastStatusHandle {
+    pub store: BroadcastStore,
+    pub broadcast_id: String,
+}
+
+impl BroadcastStatusHandle {
+    async fn update<F: FnOnce(&mut BroadcastEntry)>(&self, f: F) {
+        if let Some(entry) = self.store.lock().await.get_mut(&self.broadcast_id) {
+            f(entry);
+        }
+    }
+
+    /// Records the final state, closes the subscriber channel and evicts old broadcasts
+    async fn finish(&self, state: DeliveryState) {
+        let mut broadcasts = self.store.lock().await;
+        if let Some(entry) = broadcasts.get_mut(&self.broadcast_id) {
+            entry.status.state = state.as_str().to_string();
+            entry.status.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            entry.subscribers = None;
+        }
+        evict_finished(&mut broadcasts);
+    }
+
+    async fn update_sink<F: FnOnce(&mut SinkStatus)>(&self, index: usize, f: F) {
+        self.update(|entry| {
+            if let Some(sink) = entry.status.sinks.get_mut(index) {
+                f(sink);
+            }
+        })
+        .await;
+    }
+}
+
+/// Drives several sinks from one event stream
+///
+/// Each sink is delivered by its own task, so a slow or failing sink neither
+/// delays nor stops the others.
+pub struct CompositeBroadcaster {
+    pub sinks: Vec<Arc<dyn BroadcastSink>>,
+    /// Status receiving per-sink progress, if any
+    pub status: Option<BroadcastStatusHandle>,
+}
+
+/// Delivers one sink and mirrors its progress into the broadcast status
+async fn run_sink(
+    index: usize,
+    sink: Arc<dyn BroadcastSink>,
+    session: SessionContext,
+    events: EventStream,
+    cancel: CancellationToken,
+    status: Option<BroadcastStatusHandle>,
+) -> DeliveryReport {
+    if let Some(status) = &status {
+        status
+            .update_sink(index, |sink| sink.state = "running".to_string())
+            .await;
+    }
+
+    let mut handle = deliver(sink.clone(), session, events, cancel);
+    while let Some(event) = handle.progress.recv().await {
+        match event {
+            ProgressEvent::Delivered { .. } => {
+                if let Some(status) = &status {
+                    status.update_sink(index, |sink| sink.delivered += 1).await;
+                }
+            }
+            ProgressEvent::Failed { error, .. } => {
+                tracing::error!("Sink {} failed: {}", sink.describe(), error);
+            }
+            _ => {}
+        }
+    }
+
+    let report = handle.report().await;
+    if let Some(status) = &status {
+        let report = report.clone();
+        status
+            .update_sink(index, |sink| {
+                sink.state = report.state.as_str().to_string();
+                sink.delivered = report.delivered;
+                sink.error = report.error;
+            })
+            .await;
+    }
+    report
+}
+
+/// Turns a channel of forwarded events back into an event stream
+fn channel_stream(
+    receiver: mpsc::UnboundedReceiver<Result<PlaybackStep, ChaosDisconnect>>,
+) -> EventStream {
+    futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|event| (event, receiver))
+    })
+    .boxed()
+}
+
+impl Broadcaster for CompositeBroadcaster {
+    /// Fans the event stream out to every sink; the report fails if any sink did not complete
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        mut events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        let (progress, receiver) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            if let Some(status) = &self.status {
+                status
+                    .update(|entry| entry.status.state = "running".to_string())
+                    .await;
+            }
+
+            let mut channels = Vec::new();
+            let mut tasks = Vec::new();
+            for (index, sink) in self.sinks.iter().enumerate() {
+                let (tx, rx) = mpsc::unbounded_channel();
+                channels.push(tx);
+                tasks.push(tokio::spawn(run_sink(
+                    index,
+                    sink.clone(),
+                    session.clone(),
+                    channel_stream(rx),
+                    cancel.child_token(),
+                    self.status.clone(),
+                )));
+            }
+
+            let mut report = DeliveryReport::default();
+            loop {
+                let next = tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => {
+                        report.state = DeliveryState::Cancelled;
+                        break;
+                    }
+                    next = events.next() => next,
+                };
+                let Some(event) = next else {
+                    break;
+                };
+
+                match &event {
+                    Ok(step) => {
+                        report.delivered += 1;
+                        let _ = progress.send(ProgressEvent::Delivered {
+                            index: step.index,
+                            time: step.time,
+                        });
+                    }
+                    Err(reason) => {
+                        tracing::info!("Composite broadcast interrupted: {}", reason);
+                        report.state = DeliveryState::Interrupted;
+                        report.error = Some(reason.to_string());
+                    }
+                }
+                for channel in &channels {
+                    // A closed channel means that sink already failed
+                    let _ = channel.send(event.clone());
+                }
+                if report.state == DeliveryState::Interrupted {
+                    break;
+                }
+            }
+            drop(channels);
+
+            let mut failed = 0;
+            for task in tasks {
+                match task.await {
+                    Ok(sink_report) if sink_report.is_completed() => {}
+                    _ => failed += 1,
+                }
+            }
+
+            if report.state == DeliveryState::Running {
+                if failed > 0 {
+                    report.state = DeliveryState::Failed;
+                    report.error = Some(format!("{} sink(s) did not complete", failed));
+                } else {
+                    report.state = DeliveryState::Completed;
+                }
+            }
+            let _ = progress.send(match report.state {
+                DeliveryState::Completed => ProgressEvent::Completed,
+                DeliveryState::Cancelled => ProgressEvent::Cancelled,
+                DeliveryState::Interrupted => ProgressEvent::Interrupted {
+                    reason: report.error.clone().unwrap_or_default(),
+                },
+                _ => ProgressEvent::Failed {
+                    index: None,
+                    error: report.error.clone().unwrap_or_default(),
+                },
+            });
+
+            if let Some(status) = &self.status {
+                status.finish(report.state).await;
+            }
+            report
+        });
+
+        BroadcastHandle::new(receiver, task)
+    }
+}
@@ -1,5 +1,19 @@
+pub mod channels;
+pub mod composite;
+pub mod stdout;
 pub mod webhook;
 pub mod websocket;
+pub mod websocket_client;
 
-pub use webhook::WebhookBroadcaster;
-pub use websocket::{RewindSession, SessionStore, WebSocketBroadcaster};
+pub use channels::{ChannelHub, ChannelOptions, channel_order, join_channel_session};
+pub use composite::{
+    BroadcastStatusHandle, BroadcastStore, CompositeBroadcastRequest, CompositeBroadcaster,
+    FileSink, SinkConfig, SubscriberSink,
+};
+pub use stdout::StdoutBroadcaster;
+pub use webhook::{WebhookBroadcaster, WebhookDelivery};
+pub use websocket::{
+    AudioMessage, RewindSession, SessionStore, WebSocketBroadcaster, broadcast_session_messages,
+    broadcast_session_with_replies, stream_audio_session,
+};
+pub use websocket_client::WebSocketClientBroadcaster;
@@ -0,0 +1,41 @@
+use crate::interface::{
+    BroadcastHandle, BroadcastSink, Broadcaster, SessionContext, WebSocketMessage,
+};
+use crate::playback::{EventStream, PlaybackStep, deliver};
+use std::io::Write;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Writes each record as a JSON line on stdout, paced like a live session
+pub struct StdoutBroadcaster;
+
+impl Broadcaster for StdoutBroadcaster {
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        deliver(self, session, events, cancel)
+    }
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for StdoutBroadcaster {
+    fn describe(&self) -> String {
+        "stdout".to_string()
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let ws_message = WebSocketMessage::new(session, step.record.clone());
+        let mut stdout = std::io::stdout();
+        writeln!(stdout, "{}", serde_json::to_string(&ws_message)?)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        writeln!(std::io::stdout(), "SESSION_COMPLETE")?;
+        Ok(())
+    }
+}
@@ -1,155 +1,389 @@
-use crate::interface::{BroadcastMessage, Broadcaster, TranscriptRecord};
+use crate::batch::{BatchOptions, Batcher};
+use crate::dead_letter::DeadLetterHandle;
+use crate::interface::{
+    BroadcastHandle, BroadcastSink, Broadcaster, DeliveryReport, SessionContext, TranscriptRecord,
+};
+use crate::playback::{EventStream, PlaybackStep, deliver};
+use crate::report::{DeliveryResult, MAX_CAPTURED_BODY, ReportHandle};
+use crate::script::ReplySender;
+use crate::template::WebhookTemplates;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 pub struct WebhookBroadcaster {
     pub webhook_url: String,
+    pub client: reqwest::Client,
+    /// Payload and completion templates of the target
+    pub templates: WebhookTemplates,
+    /// Report receiving every captured response, if any
+    pub report: Option<ReportHandle>,
+    /// Store receiving every message that could not be delivered, if any; with one, a
+    /// failed message no longer stops the broadcast
+    pub dead_letters: Option<DeadLetterHandle>,
+    /// Receives each response body, e.g. to choose a script's branches
+    pub replies: Option<ReplySender>,
+    batch: BatchOptions,
+}
+
+impl WebhookBroadcaster {
+    /// Posts one default-shaped payload per record, without a report
+    pub fn new(webhook_url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            client,
+            templates: WebhookTemplates::default(),
+            report: None,
+            dead_letters: None,
+            replies: None,
+            batch: BatchOptions::default(),
+        }
+    }
+
+    pub fn with_templates(mut self, templates: WebhookTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    pub fn with_report(mut self, report: ReportHandle) -> Self {
+        self.report = Some(report);
+        self
+    }
+
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterHandle) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    pub fn with_replies(mut self, replies: ReplySender) -> Self {
+        self.replies = Some(replies);
+        self
+    }
+
+    /// Groups records into array payloads instead of one request per record
+    pub fn with_batch(mut self, batch: BatchOptions) -> Self {
+        self.batch = batch;
+        self
+    }
 }
 
-#[async_trait::async_trait]
 impl Broadcaster for WebhookBroadcaster {
-    async fn broadcast(
-        &self,
-        session_id: i32,
-        records: Vec<TranscriptRecord>,
-    ) -> anyhow::Result<()> {
-        broadcast_to_webhook(self.webhook_url.clone(), session_id, records).await
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        deliver(
+            Arc::new(WebhookDelivery::new(self)),
+            session,
+            events,
+            cancel,
+        )
     }
 }
 
-fn parse_time_to_time(time_str: &str) -> i32 {
-    let parts: Vec<&str> = time_str.split(':').collect();
+/// One broadcast of a `WebhookBroadcaster`, with its own batch buffer and dead-letter count
+///
+/// `broadcast` starts one per call; composite broadcasts use one as a sink.
+pub struct WebhookDelivery {
+    broadcaster: Arc<WebhookBroadcaster>,
+    batcher: Mutex<Batcher>,
+    /// Messages of this broadcast filed as dead letters
+    undeliverable: AtomicUsize,
+}
 
-    match parts.len() {
-        3 => {
-            // HH:MM:SS format
-            let hours = parts[0].parse::<i32>().unwrap_or(0);
-            let minutes = parts[1].parse::<i32>().unwrap_or(0);
-            let time = parts[2].parse::<i32>().unwrap_or(0);
-            hours * 3600 + minutes * 60 + time
-        }
-        2 => {
-            // MM:SS format
-            let minutes = parts[0].parse::<i32>().unwrap_or(0);
-            let time = parts[1].parse::<i32>().unwrap_or(0);
-            minutes * 60 + time
+impl WebhookDelivery {
+    pub fn new(broadcaster: Arc<WebhookBroadcaster>) -> Self {
+        let batcher = Mutex::new(Batcher::new(broadcaster.batch.clone()));
+        Self {
+            broadcaster,
+            batcher,
+            undeliverable: AtomicUsize::new(0),
         }
-        1 => {
-            // Just time
-            parts[0].parse::<i32>().unwrap_or(0)
+    }
+
+    /// Files a failed message in the dead-letter store, if one is configured
+    async fn dead_letter(&self, payload: serde_json::Value, response: &DeliveryResult) {
+        if let Some(dead_letters) = &self.broadcaster.dead_letters {
+            let report_id = self
+                .broadcaster
+                .report
+                .as_ref()
+                .map(|r| r.report_id.clone());
+            dead_letters
+                .push(&self.broadcaster.webhook_url, payload, response, report_id)
+                .await;
+            self.undeliverable.fetch_add(1, Ordering::Relaxed);
         }
-        _ => 0,
     }
-}
 
-async fn broadcast_to_webhook(
-    webhook_url: String,
-    session_id: i32,
-    records: Vec<TranscriptRecord>,
-) -> anyhow::Result<()> {
-    let client = reqwest::Client::new();
-    let mut last_time = 0;
-
-    println!("Starting webhook broadcast to: {}", webhook_url);
-
-    for record in &records {
-        // Parse the time field from HH:MM:SS format to total seconds
-        let current_time = parse_time_to_time(&record.time);
-
-        // Calculate how long we should wait before sending this message
-        let wait_duration = if current_time > last_time {
-            current_time - last_time
-        } else {
-            0
+    /// Posts one record, or an array of records when batching, and records the response
+    async fn post_records(
+        &self,
+        session: &SessionContext,
+        steps: Vec<PlaybackStep>,
+    ) -> anyhow::Result<()> {
+        let (Some(first), Some(last)) = (steps.first(), steps.last()) else {
+            return Ok(());
         };
 
-        // Wait for the calculated duration
-        if wait_duration > 0 {
-            tokio::time::sleep(tokio::time::Duration::from_secs(wait_duration as u64)).await;
+        if !self.broadcaster.batch.is_enabled() {
+            return self
+                .post_payload(
+                    self.broadcaster.templates.payload(session, first),
+                    first,
+                    Some(first.record.clone()),
+                    Vec::new(),
+                    format!("{} - {}", first.record.speaker, first.record.sentence),
+                )
+                .await;
         }
 
-        // Create broadcast message with session parameters and body
-        // Note: Currently treating all webhook sessions as job_description_enrichment_session
-        // This could be enhanced to distinguish based on session context
-        let broadcast_message = BroadcastMessage {
-            job_description_enrichment_session: Some(session_id),
-            candidate_profile_enrichment_session: None,
-            body: record.clone(),
-        };
+        // Render each record with the target's template and send them as one array
+        let payload = serde_json::Value::Array(
+            steps
+                .iter()
+                .map(|step| self.broadcaster.templates.payload(session, step))
+                .collect(),
+        );
+        let summary = format!(
+            "batch of {} record(s) ({}s - {}s)",
+            steps.len(),
+            first.time,
+            last.time
+        );
+        self.post_payload(
+            payload,
+            last,
+            None,
+            steps.iter().map(|step| step.index).collect(),
+            summary,
+        )
+        .await
+    }
 
+    async fn post_payload(
+        &self,
+        payload: serde_json::Value,
+        step: &PlaybackStep,
+        record: Option<TranscriptRecord>,
+        batch: Vec<usize>,
+        summary: String,
+    ) -> anyhow::Result<()> {
         // Send POST request to webhook
-        let response = client
-            .post(&webhook_url)
-            .json(&broadcast_message)
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                if resp.status().is_success() {
+        let mut response = post_and_capture(
+            &self.broadcaster.client,
+            &self.broadcaster.webhook_url,
+            &payload,
+        )
+        .await;
+        response.record_index = Some(batch.first().copied().unwrap_or(step.index));
+        response.record = record;
+        response.batch = batch;
+        let status = response.status.map(reqwest::StatusCode::from_u16);
+        let error = response.error.clone();
+        // An error status the report expects is an answer, not a failed delivery
+        let expected = match (&self.broadcaster.report, response.status) {
+            (Some(report), Some(code)) => report.expects_status(code).await,
+            _ => false,
+        };
+        let failed = !expected
+            && !matches!(&status, Some(Ok(s)) if !s.is_client_error() && !s.is_server_error());
+        if failed {
+            self.dead_letter(payload, &response).await;
+        }
+        if let (Some(replies), Some(body)) = (&self.broadcaster.replies, &response.body) {
+            // The script may already have ended
+            let _ = replies.send(body.clone());
+        }
+        if let Some(report) = &self.broadcaster.report {
+            report.push(response).await;
+        }
+
+        let failure = match (status, error) {
+            (Some(Ok(status)), _) => {
+                if status.is_success() {
+                    println!("✓ Sent to webhook at {}s: {}", step.time, summary);
+                    None
+                } else if expected {
                     println!(
-                        "✓ Sent to webhook at {}s: {} - {}",
-                        current_time, record.speaker, record.sentence
+                        "✓ Webhook returned expected status {} at {}s: {}",
+                        status, step.time, summary
                     );
+                    None
                 } else {
-                    let status = resp.status();
-                    eprintln!(
-                        "✗ Webhook returned status {}: {} - {}",
-                        status, record.speaker, record.sentence
-                    );
-
-                    // Stop session on connection failure errors (4xx and 5xx)
-                    if status.is_client_error() || status.is_server_error() {
-                        eprintln!(
-                            "✗ Stopping webhook broadcast due to error status: {}",
-                            status
-                        );
-                        return Err(anyhow::anyhow!(
-                            "Webhook connection failed with status: {}",
-                            status
-                        ));
-                    }
+                    eprintln!("✗ Webhook returned status {}: {}", status, summary);
+                    // Connection failure errors (4xx and 5xx)
+                    (status.is_client_error() || status.is_server_error())
+                        .then(|| format!("Webhook connection failed with status: {}", status))
                 }
             }
-            Err(e) => {
-                eprintln!(
-                    "✗ Failed to send to webhook: {} - {} - {}",
-                    e, record.speaker, record.sentence
-                );
-                // Stop session on connection errors
-                eprintln!("✗ Stopping webhook broadcast due to connection error");
-                return Err(anyhow::anyhow!("Webhook connection failed: {}", e));
+            (_, e) => {
+                let e = e.unwrap_or_else(|| "invalid status code".to_string());
+                eprintln!("✗ Failed to send to webhook: {} - {}", e, summary);
+                Some(format!("Webhook connection failed: {}", e))
             }
-        }
+        };
+        let Some(failure) = failure else {
+            return Ok(());
+        };
 
-        last_time = current_time;
+        // A dead-lettered message can be redelivered later, so the rest still goes out
+        if self.broadcaster.dead_letters.is_some() {
+            eprintln!("✗ Saved as a dead letter, continuing the broadcast");
+            return Ok(());
+        }
+        eprintln!("✗ Stopping webhook broadcast: {}", failure);
+        Err(anyhow::anyhow!(failure))
     }
+}
 
-    // Send completion message
-    let completion_message = serde_json::json!({
-        "status": "complete",
-        "message": "Broadcast completed"
-    });
+/// Sends a JSON payload and captures status, latency, headers and body
+pub(crate) async fn post_and_capture<T: serde::Serialize>(
+    client: &reqwest::Client,
+    webhook_url: &str,
+    payload: &T,
+) -> DeliveryResult {
+    let started = tokio::time::Instant::now();
+    let mut delivery = DeliveryResult::default();
 
-    match client
-        .post(&webhook_url)
-        .json(&completion_message)
-        .send()
-        .await
-    {
+    match client.post(webhook_url).json(payload).send().await {
         Ok(resp) => {
-            if resp.status().is_success() {
+            delivery.status = Some(resp.status().as_u16());
+            delivery.headers = resp
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect();
+            match resp.bytes().await {
+                Ok(bytes) => {
+                    let end = bytes.len().min(MAX_CAPTURED_BODY);
+                    delivery.body = Some(String::from_utf8_lossy(&bytes[..end]).into_owned());
+                }
+                Err(e) => delivery.error = Some(format!("Failed to read response body: {}", e)),
+            }
+        }
+        Err(e) => delivery.error = Some(e.to_string()),
+    }
+
+    delivery.latency_ms = started.elapsed().as_millis() as u64;
+    delivery
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for WebhookDelivery {
+    fn describe(&self) -> String {
+        format!("webhook {}", self.broadcaster.webhook_url)
+    }
+
+    async fn open(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        println!(
+            "Starting webhook broadcast to: {}",
+            self.broadcaster.webhook_url
+        );
+        Ok(())
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let ready = self.batcher.lock().await.push(step.clone());
+        match ready {
+            Some(steps) => self.post_records(session, steps).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn complete(&self, session: &SessionContext) -> anyhow::Result<()> {
+        // Send whatever is still batched before completing
+        let remaining = self.batcher.lock().await.flush();
+        if let Some(steps) = remaining {
+            self.post_records(session, steps).await?;
+        }
+
+        // Send completion message
+        let completion_message = self.broadcaster.templates.completion(session);
+
+        let response = post_and_capture(
+            &self.broadcaster.client,
+            &self.broadcaster.webhook_url,
+            &completion_message,
+        )
+        .await;
+        match (response.status, &response.error) {
+            (Some(status), None) if (200..300).contains(&status) => {
                 println!("✓ Sent completion message to webhook");
-            } else {
+            }
+            (Some(status), _) => {
+                eprintln!("✗ Failed to send completion message, status: {}", status);
+                self.dead_letter(completion_message, &response).await;
+            }
+            (None, e) => {
                 eprintln!(
-                    "✗ Failed to send completion message, status: {}",
-                    resp.status()
+                    "✗ Failed to send completion message: {}",
+                    e.as_deref().unwrap_or("unknown error")
                 );
+                self.dead_letter(completion_message, &response).await;
             }
         }
-        Err(e) => {
-            eprintln!("✗ Failed to send completion message: {}", e);
+        if let Some(report) = &self.broadcaster.report {
+            report.push(response).await;
         }
+
+        // Every message was attempted; the broadcast still fails if any went undelivered
+        let undeliverable = self.undeliverable.load(Ordering::Relaxed);
+        if undeliverable > 0 {
+            return Err(anyhow::anyhow!(
+                "{} webhook message(s) could not be delivered and were saved as dead letters",
+                undeliverable
+            ));
+        }
+
+        println!("Webhook broadcast completed");
+        Ok(())
     }
 
-    println!("Webhook broadcast completed");
-    Ok(())
+    async fn close(&self, session: &SessionContext) -> anyhow::Result<()> {
+        // Batched records were already counted as delivered, so they are not dropped silently
+        let remaining = self.batcher.lock().await.flush();
+        if let (Some(steps), Some(_)) = (remaining, &self.broadcaster.dead_letters) {
+            let payload = serde_json::Value::Array(
+                steps
+                    .iter()
+                    .map(|step| self.broadcaster.templates.payload(session, step))
+                    .collect(),
+            );
+            let response = DeliveryResult {
+                error: Some("Broadcast stopped before the batch was sent".to_string()),
+                record_index: steps.first().map(|step| step.index),
+                batch: steps.iter().map(|step| step.index).collect(),
+                ..Default::default()
+            };
+            eprintln!(
+                "✗ Saved {} unsent batched record(s) as a dead letter",
+                steps.len()
+            );
+            self.dead_letter(payload, &response).await;
+        }
+        Ok(())
+    }
+
+    async fn finish(&self, report: &DeliveryReport) {
+        if !report.is_completed() {
+            eprintln!(
+                "✗ Webhook broadcast stopped: {}",
+                report.error.as_deref().unwrap_or("cancelled")
+            );
+        }
+        if let Some(handle) = &self.broadcaster.report {
+            handle
+                .finish(report.clone().into_result().err().map(|e| e.to_string()))
+                .await;
+        }
+    }
 }
@@ -1,43 +1,335 @@
-use crate::interface::{Broadcaster, TranscriptRecord};
+use crate::adapter::channels::{ChannelHub, ChannelOptions, join_channel_session};
+use crate::audio::{AudioEvent, AudioPlayback, AudioTrack};
+use crate::chaos::ChaosOptions;
+use crate::clock::SharedClock;
+use crate::interface::{
+    BroadcastHandle, BroadcastSink, Broadcaster, DeliveryReport, DeliveryState, SessionContext,
+    TranscriptRecord, WebSocketMessage,
+};
+use crate::playback::{EventStream, Playback, PlaybackStep, deliver};
+use crate::script::{ReplyReceiver, Script, ScriptPlayback};
+use crate::transcript::format_time;
+use crate::turns::{TurnTaking, TurnTakingOptions};
+use futures_util::{Sink, SinkExt};
 use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Clone)]
 pub struct RewindSession {
-    pub job_description_enrichment_session: Option<i32>,
-    pub candidate_profile_enrichment_session: Option<i32>,
+    pub session: SessionContext,
     pub filename: String,
     pub records: Vec<TranscriptRecord>,
     pub current_index: usize,
+    pub chaos: ChaosOptions,
+    /// Branching script played instead of `records`, driven by the client's replies
+    pub script: Option<Script>,
+    /// Interactive mode: records that wait for the client's reply before playback goes on
+    pub interactive: Option<TurnTakingOptions>,
+    /// Channel separation: one shared playback, streamed whole or per speaker
+    pub channels: Option<ChannelOptions>,
+    /// Shared playback of a channel-separated session, once a stream has connected
+    pub hub: Option<Arc<ChannelHub>>,
+    /// Audio streamed as binary frames alongside the records
+    pub audio: Option<AudioTrack>,
 }
 
 pub type SessionStore = Arc<Mutex<HashMap<String, RewindSession>>>;
 
+/// A WebSocket message of an audio session
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioMessage {
+    /// A JSON record or status message
+    Text(String),
+    /// Encoded audio samples
+    Binary(Vec<u8>),
+}
+
+type TextSink = Pin<Box<dyn Sink<String, Error = anyhow::Error> + Send>>;
+
+/// Streams events to a single connected WebSocket client
+///
+/// Messages are serialized JSON text, followed by `SESSION_COMPLETE`; the
+/// connection is closed when playback is interrupted or cancelled.
 pub struct WebSocketBroadcaster {
-    pub job_description_enrichment_session: Option<i32>,
-    pub candidate_profile_enrichment_session: Option<i32>,
-    pub sessions: SessionStore,
+    sender: Mutex<TextSink>,
+}
+
+impl WebSocketBroadcaster {
+    pub fn new<S>(sender: S) -> Self
+    where
+        S: Sink<String> + Send + 'static,
+        S::Error: std::fmt::Display,
+    {
+        let sender = sender.sink_map_err(|e| anyhow::anyhow!("{}", e));
+        Self {
+            sender: Mutex::new(Box::pin(sender)),
+        }
+    }
 }
 
-#[async_trait::async_trait]
 impl Broadcaster for WebSocketBroadcaster {
-    async fn broadcast(
-        &self,
-        session_id: i32,
-        records: Vec<TranscriptRecord>,
-    ) -> anyhow::Result<()> {
-        let session = RewindSession {
-            job_description_enrichment_session: self.job_description_enrichment_session,
-            candidate_profile_enrichment_session: self.candidate_profile_enrichment_session,
-            filename: "".to_string(),
-            records,
-            current_index: 0,
-        };
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        deliver(self, session, events, cancel)
+    }
+}
 
-        let mut sessions = self.sessions.lock().await;
-        sessions.insert(session_id.to_string(), session);
+#[async_trait::async_trait]
+impl BroadcastSink for WebSocketBroadcaster {
+    fn describe(&self) -> String {
+        "websocket client".to_string()
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let record = &step.record;
+        let ws_message = WebSocketMessage::new(session, record.clone());
+        let message = serde_json::to_string(&ws_message)?;
+        self.sender
+            .lock()
+            .await
+            .send(message)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))?;
 
+        tracing::debug!(
+            "Sent message at {}s: {} - {}",
+            step.time,
+            record.speaker,
+            record.sentence
+        );
         Ok(())
     }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        self.sender
+            .lock()
+            .await
+            .send("SESSION_COMPLETE".to_string())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send completion message: {}", e))
+    }
+
+    async fn close(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        self.sender
+            .lock()
+            .await
+            .close()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to close connection: {}", e))
+    }
+}
+
+/// Plays a stored session into a connected client, then removes the session
+///
+/// Unknown sessions get `SESSION_NOT_FOUND`. Cancelling `cancel`, e.g. when the
+/// client disconnects, stops playback early.
+pub async fn broadcast_session_messages<S>(
+    session_id: &str,
+    ws_sender: S,
+    sessions: SessionStore,
+    clock: SharedClock,
+    cancel: CancellationToken,
+) -> anyhow::Result<DeliveryReport>
+where
+    S: Sink<String> + Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    let (_, replies) = mpsc::unbounded_channel();
+    broadcast_session_with_replies(session_id, ws_sender, replies, sessions, clock, cancel).await
+}
+
+/// Like `broadcast_session_messages`, with the messages the client sends back
+///
+/// Replies drive the session's script or interactive turns, and are ignored otherwise.
+/// Channel-separated sessions are joined as a stream of every channel.
+pub async fn broadcast_session_with_replies<S>(
+    session_id: &str,
+    ws_sender: S,
+    replies: ReplyReceiver,
+    sessions: SessionStore,
+    clock: SharedClock,
+    cancel: CancellationToken,
+) -> anyhow::Result<DeliveryReport>
+where
+    S: Sink<String> + Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    let session = {
+        let sessions_guard = sessions.lock().await;
+        sessions_guard.get(session_id).cloned()
+    };
+
+    let Some(session) = session else {
+        let mut ws_sender = Box::pin(ws_sender);
+        ws_sender
+            .send("SESSION_NOT_FOUND".to_string())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send not found message: {}", e))?;
+        return Ok(DeliveryReport {
+            state: DeliveryState::Failed,
+            delivered: 0,
+            error: Some("Session not found".to_string()),
+        });
+    };
+
+    if session.channels.is_some() {
+        return join_channel_session(
+            session_id, None, ws_sender, replies, sessions, clock, cancel,
+        )
+        .await;
+    }
+
+    let context = session.session.clone();
+    let events = session_events(session_id, session, replies, clock);
+
+    let broadcaster = Arc::new(WebSocketBroadcaster::new(ws_sender));
+    let report = broadcaster
+        .broadcast(context, events, cancel)
+        .report()
+        .await;
+
+    // Clean up session once broadcasting has stopped
+    let mut sessions_guard = sessions.lock().await;
+    sessions_guard.remove(session_id);
+    tracing::info!(
+        "Session {} {} and cleaned up",
+        session_id,
+        report.state.as_str()
+    );
+
+    Ok(report)
+}
+
+/// Plays a stored session with audio into a connected client, then removes the session
+///
+/// Audio goes out as binary frames paced in real time, each record as JSON text just
+/// before the frame its sound starts in, with `end` set to when its sound stops.
+/// Unknown sessions get `SESSION_NOT_FOUND`, sessions without audio `AUDIO_NOT_ENABLED`.
+pub async fn stream_audio_session<S>(
+    session_id: &str,
+    ws_sender: S,
+    sessions: SessionStore,
+    clock: SharedClock,
+    cancel: CancellationToken,
+) -> anyhow::Result<DeliveryReport>
+where
+    S: Sink<AudioMessage> + Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    let mut ws_sender = Box::pin(ws_sender);
+    let session = {
+        let sessions_guard = sessions.lock().await;
+        sessions_guard.get(session_id).cloned()
+    };
+    let found = match session {
+        None => Err(("SESSION_NOT_FOUND", "Session not found")),
+        Some(session) => match session.audio.clone() {
+            None => Err(("AUDIO_NOT_ENABLED", "Session has no audio")),
+            Some(track) => Ok((session, track)),
+        },
+    };
+    let (session, track) = match found {
+        Ok(found) => found,
+        Err((message, error)) => {
+            ws_sender
+                .send(AudioMessage::Text(message.to_string()))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to send {} message: {}", message, e))?;
+            return Ok(DeliveryReport {
+                state: DeliveryState::Failed,
+                delivered: 0,
+                error: Some(error.to_string()),
+            });
+        }
+    };
+
+    let mut report = DeliveryReport::default();
+    let ends: Vec<String> = (0..session.records.len())
+        .map(|index| format_time(track.end_secs(index)))
+        .collect();
+    let mut playback = AudioPlayback::new(session.records, track, clock);
+    loop {
+        let event = tokio::select! {
+            _ = cancel.cancelled() => {
+                report.state = DeliveryState::Cancelled;
+                break;
+            }
+            event = playback.next() => event,
+        };
+        let sent = match event {
+            Some(AudioEvent::Record(step)) => {
+                let message = WebSocketMessage {
+                    end: ends.get(step.index).cloned(),
+                    ..WebSocketMessage::new(&session.session, step.record)
+                };
+                let sent = ws_sender
+                    .send(AudioMessage::Text(serde_json::to_string(&message)?))
+                    .await;
+                if sent.is_ok() {
+                    report.delivered += 1;
+                }
+                sent
+            }
+            Some(AudioEvent::Frame(bytes)) => ws_sender.send(AudioMessage::Binary(bytes)).await,
+            None => {
+                report.state = DeliveryState::Completed;
+                ws_sender
+                    .send(AudioMessage::Text("SESSION_COMPLETE".to_string()))
+                    .await
+            }
+        };
+        if let Err(e) = sent {
+            report.state = DeliveryState::Failed;
+            report.error = Some(format!("Failed to send message: {}", e));
+            break;
+        }
+        if report.state == DeliveryState::Completed {
+            break;
+        }
+    }
+
+    let mut sessions_guard = sessions.lock().await;
+    sessions_guard.remove(session_id);
+    tracing::info!(
+        "Audio session {} {} and cleaned up",
+        session_id,
+        report.state.as_str()
+    );
+    Ok(report)
+}
+
+/// The events a stored session plays: its script, or its records paced in real time
+pub(crate) fn session_events(
+    session_id: &str,
+    session: RewindSession,
+    replies: ReplyReceiver,
+    clock: SharedClock,
+) -> EventStream {
+    match session.script {
+        Some(script) => {
+            tracing::info!(
+                "Running script {} for session {}",
+                session.filename,
+                session_id
+            );
+            ScriptPlayback::new(script, replies, clock).into_stream()
+        }
+        None => {
+            let playback = Playback::new(session.records, session.chaos, clock.clone());
+            if let Some(seed) = playback.chaos_seed() {
+                tracing::info!("Chaos enabled for session {} (seed {})", session_id, seed);
+            }
+            match session.interactive {
+                Some(options) => TurnTaking::new(playback, options, replies, clock).into_stream(),
+                None => playback.into_stream(),
+            }
+        }
+    }
 }
@@ -0,0 +1,88 @@
+use crate::interface::{
+    BroadcastHandle, BroadcastSink, Broadcaster, SessionContext, WebSocketMessage,
+};
+use crate::playback::{EventStream, PlaybackStep, deliver};
+use futures_util::SinkExt;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+type ClientSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Connects to a remote WebSocket server and streams the transcript to it
+pub struct WebSocketClientBroadcaster {
+    pub url: String,
+    socket: Mutex<Option<ClientSocket>>,
+}
+
+impl WebSocketClientBroadcaster {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            socket: Mutex::new(None),
+        }
+    }
+
+    async fn send_text(&self, text: String) -> anyhow::Result<()> {
+        let mut socket = self.socket.lock().await;
+        let socket = socket
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Not connected to {}", self.url))?;
+        socket
+            .send(Message::Text(text))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))
+    }
+}
+
+impl Broadcaster for WebSocketClientBroadcaster {
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        deliver(self, session, events, cancel)
+    }
+}
+
+#[async_trait::async_trait]
+impl BroadcastSink for WebSocketClientBroadcaster {
+    fn describe(&self) -> String {
+        format!("websocket {}", self.url)
+    }
+
+    async fn open(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        let (socket, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", self.url, e))?;
+        tracing::info!("Connected to {}", self.url);
+        *self.socket.lock().await = Some(socket);
+        Ok(())
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()> {
+        let ws_message = WebSocketMessage::new(session, step.record.clone());
+        self.send_text(serde_json::to_string(&ws_message)?).await?;
+        tracing::debug!("Sent message at {}s to {}", step.time, self.url);
+        Ok(())
+    }
+
+    async fn complete(&self, session: &SessionContext) -> anyhow::Result<()> {
+        self.send_text("SESSION_COMPLETE".to_string())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send completion message: {}", e))?;
+        self.close(session).await
+    }
+
+    async fn close(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        if let Some(mut socket) = self.socket.lock().await.take() {
+            tracing::info!("Closing {}", self.url);
+            socket.close(None).await?;
+        }
+        Ok(())
+    }
+}
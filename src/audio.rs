@@ -0,0 +1,354 @@
+use crate::clock::SharedClock;
+use crate::interface::TranscriptRecord;
+use crate::playback::PlaybackStep;
+use crate::stats::utterance_secs;
+use crate::transcript::parse_time_to_time;
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest audio frame accepted, in milliseconds
+pub const MAX_FRAME_MS: u32 = 1_000;
+
+/// Sample format of audio frames
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AudioEncoding {
+    /// Signed 16-bit little-endian PCM
+    #[default]
+    Pcm,
+    /// 8-bit G.711 μ-law
+    Mulaw,
+}
+
+/// What each record sounds like
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSource {
+    /// Silent frames for the utterance's estimated duration
+    #[default]
+    Silence,
+    /// A sine tone for the utterance's estimated duration
+    Tone,
+    /// The WAV file in the record's `audio` column, or silence for records without one
+    Wav,
+}
+
+/// Audio streamed alongside a session's text events, mono at one sample rate
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct AudioOptions {
+    pub encoding: AudioEncoding,
+    pub source: AudioSource,
+    /// Samples per second
+    pub sample_rate: u32,
+    /// Length of each frame, in milliseconds
+    pub frame_ms: u32,
+    /// Frequency of the `tone` source, in hertz
+    pub tone_hz: f64,
+}
+
+impl AudioOptions {
+    /// Builds options from query parameters; audio is on when an encoding is given
+    ///
+    /// The sample rate defaults to 16 kHz for PCM and 8 kHz for μ-law.
+    pub fn from_params(
+        encoding: Option<AudioEncoding>,
+        source: Option<AudioSource>,
+        sample_rate: Option<u32>,
+        frame_ms: Option<u32>,
+        tone_hz: Option<f64>,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(encoding) = encoding else {
+            return Ok(None);
+        };
+        let options = Self {
+            encoding,
+            source: source.unwrap_or_default(),
+            sample_rate: sample_rate.unwrap_or(match encoding {
+                AudioEncoding::Pcm => 16_000,
+                AudioEncoding::Mulaw => 8_000,
+            }),
+            frame_ms: frame_ms.unwrap_or(20),
+            tone_hz: tone_hz.unwrap_or(440.0),
+        };
+        options.validate()?;
+        Ok(Some(options))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(1_000..=192_000).contains(&self.sample_rate) {
+            return Err(anyhow::anyhow!(
+                "Sample rate must be between 1000 and 192000, got {}",
+                self.sample_rate
+            ));
+        }
+        if self.frame_ms == 0 || self.frame_ms > MAX_FRAME_MS {
+            return Err(anyhow::anyhow!(
+                "Frame size must be between 1 and {} ms, got {}",
+                MAX_FRAME_MS,
+                self.frame_ms
+            ));
+        }
+        if !(self.tone_hz > 0.0 && self.tone_hz < self.sample_rate as f64 / 2.0) {
+            return Err(anyhow::anyhow!(
+                "Tone must be between 0 and half the sample rate, got {} Hz",
+                self.tone_hz
+            ));
+        }
+        Ok(())
+    }
+
+    /// Samples in one frame
+    pub fn frame_samples(&self) -> usize {
+        (self.sample_rate as u64 * self.frame_ms as u64 / 1_000).max(1) as usize
+    }
+}
+
+/// Encodes one sample as G.711 μ-law
+pub fn mulaw(sample: i16) -> u8 {
+    const BIAS: i32 = 0x84;
+    const CLIP: i32 = 32_635;
+    let mut value = sample as i32;
+    let sign = if value < 0 {
+        value = -value;
+        0x80
+    } else {
+        0
+    };
+    value = value.min(CLIP) + BIAS;
+    let mut exponent = 7;
+    let mut mask = 0x4000;
+    while exponent > 0 && value & mask == 0 {
+        exponent -= 1;
+        mask >>= 1;
+    }
+    let mantissa = (value >> (exponent + 3)) & 0x0F;
+    !(sign | (exponent << 4) | mantissa) as u8
+}
+
+/// Reads a WAV file as mono 16-bit samples at `sample_rate`
+///
+/// Channels are mixed down and other rates resampled linearly.
+pub fn load_wav(path: &Path, sample_rate: u32) -> anyhow::Result<Vec<i16>> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let samples: Vec<f64> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f64;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f64 / scale))
+                .collect::<Result<_, _>>()?
+        }
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .map(|s| s.map(f64::from))
+            .collect::<Result<_, _>>()?,
+    };
+    let channels = spec.channels.max(1) as usize;
+    let mono: Vec<f64> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f64>() / frame.len() as f64)
+        .collect();
+
+    let ratio = spec.sample_rate as f64 / sample_rate as f64;
+    let length = (mono.len() as f64 / ratio).floor() as usize;
+    Ok((0..length)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = mono.get(index + 1).copied().unwrap_or(mono[index]);
+            let value = mono[index] + (next - mono[index]) * position.fract();
+            (value.clamp(-1.0, 1.0) * i16::MAX as f64) as i16
+        })
+        .collect())
+}
+
+/// A record's sound on the audio timeline, in samples
+#[derive(Debug, Clone)]
+struct Segment {
+    start: usize,
+    end: usize,
+    clip: Option<Arc<Vec<i16>>>,
+}
+
+/// The audio of a transcript, laid out on one continuous timeline
+///
+/// Each record sounds from its start time for its estimated duration, or its WAV file's
+/// length, and is cut short when the next record starts. Gaps are silent.
+#[derive(Debug, Clone)]
+pub struct AudioTrack {
+    pub options: AudioOptions,
+    segments: Vec<Segment>,
+}
+
+impl AudioTrack {
+    /// Lays out the audio of `records`, reading WAV files relative to `dir`
+    pub fn load(
+        records: &[TranscriptRecord],
+        options: AudioOptions,
+        dir: &Path,
+    ) -> anyhow::Result<Self> {
+        options.validate()?;
+        let rate = options.sample_rate as f64;
+        let starts: Vec<usize> = records
+            .iter()
+            .map(|record| (parse_time_to_time(&record.time).max(0) as f64 * rate) as usize)
+            .collect();
+        let mut segments = Vec::with_capacity(records.len());
+        for (index, record) in records.iter().enumerate() {
+            let clip = match (&record.audio, options.source) {
+                (Some(file), AudioSource::Wav) => {
+                    let file = Path::new(file);
+                    if file.is_absolute()
+                        || file
+                            .components()
+                            .any(|c| matches!(c, std::path::Component::ParentDir))
+                    {
+                        return Err(anyhow::anyhow!(
+                            "Audio file must be inside the transcript directory: {}",
+                            file.display()
+                        ));
+                    }
+                    Some(Arc::new(load_wav(&dir.join(file), options.sample_rate)?))
+                }
+                _ => None,
+            };
+            let start = starts[index];
+            let length = match &clip {
+                Some(clip) => clip.len(),
+                None => (utterance_secs(&record.sentence) * rate) as usize,
+            };
+            let next = starts.get(index + 1).copied().unwrap_or(usize::MAX);
+            segments.push(Segment {
+                start,
+                end: (start + length).min(next.max(start)),
+                clip,
+            });
+        }
+        Ok(Self { options, segments })
+    }
+
+    /// Samples from the first to the end of the last sound
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(|s| s.end).max().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whole seconds until a record's sound has stopped
+    pub fn end_secs(&self, index: usize) -> u64 {
+        self.segments.get(index).map_or(0, |segment| {
+            (segment.end as f64 / self.options.sample_rate as f64).ceil() as u64
+        })
+    }
+
+    fn sample(&self, segment: &Segment, n: usize) -> i16 {
+        let offset = n - segment.start;
+        match (&segment.clip, self.options.source) {
+            (Some(clip), _) => clip.get(offset).copied().unwrap_or(0),
+            (None, AudioSource::Tone) => {
+                let phase = std::f64::consts::TAU * self.options.tone_hz * offset as f64
+                    / self.options.sample_rate as f64;
+                (phase.sin() * 0.3 * i16::MAX as f64) as i16
+            }
+            (None, _) => 0,
+        }
+    }
+
+    /// Encoded bytes of `count` samples from sample `from`
+    pub fn frame(&self, from: usize, count: usize) -> Vec<u8> {
+        let to = from + count;
+        let sounding: Vec<&Segment> = self
+            .segments
+            .iter()
+            .filter(|s| s.start < to && s.end > from)
+            .collect();
+        let mut bytes = Vec::with_capacity(count * 2);
+        for n in from..to {
+            let sample = sounding
+                .iter()
+                .find(|s| s.start <= n && n < s.end)
+                .map_or(0, |segment| self.sample(segment, n));
+            match self.options.encoding {
+                AudioEncoding::Pcm => bytes.extend_from_slice(&sample.to_le_bytes()),
+                AudioEncoding::Mulaw => bytes.push(mulaw(sample)),
+            }
+        }
+        bytes
+    }
+}
+
+/// An event of an audio session: a record as it starts, or a frame of audio
+#[derive(Debug, Clone)]
+pub enum AudioEvent {
+    Record(PlaybackStep),
+    Frame(Vec<u8>),
+}
+
+/// Paces a transcript's records and audio frames in real time
+///
+/// Frames cover the track back to back, each sent when its audio is due. A record is
+/// sent just before the frame its sound starts in, so text and audio stay aligned.
+pub struct AudioPlayback {
+    records: Vec<TranscriptRecord>,
+    track: AudioTrack,
+    clock: SharedClock,
+    started: Duration,
+    /// Next record and first sample of the next frame
+    record: usize,
+    sample: usize,
+}
+
+impl AudioPlayback {
+    pub fn new(records: Vec<TranscriptRecord>, track: AudioTrack, clock: SharedClock) -> Self {
+        let started = clock.elapsed();
+        Self {
+            records,
+            track,
+            clock,
+            started,
+            record: 0,
+            sample: 0,
+        }
+    }
+
+    /// Waits until the next record or frame is due and returns it, or `None` once done
+    pub async fn next(&mut self) -> Option<AudioEvent> {
+        let frame = self.track.options.frame_samples();
+        if self.record >= self.records.len() && self.sample >= self.track.len() {
+            return None;
+        }
+
+        // A frame is due once the audio before it has played
+        let due =
+            Duration::from_secs_f64(self.sample as f64 / self.track.options.sample_rate as f64);
+        let elapsed = self.clock.elapsed().saturating_sub(self.started);
+        if due > elapsed {
+            self.clock.sleep(due - elapsed).await;
+        }
+
+        if let Some(segment) = self.track.segments.get(self.record)
+            && segment.start < self.sample + frame
+        {
+            let step = PlaybackStep {
+                index: self.record,
+                time: parse_time_to_time(&self.records[self.record].time),
+                record: self.records[self.record].clone(),
+            };
+            self.record += 1;
+            return Some(AudioEvent::Record(step));
+        }
+
+        let bytes = self.track.frame(self.sample, frame);
+        self.sample += frame;
+        Some(AudioEvent::Frame(bytes))
+    }
+}
@@ -0,0 +1,101 @@
+use crate::playback::PlaybackStep;
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// How webhook deliveries are grouped into requests
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[oai(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    /// One request per record
+    #[default]
+    None,
+    /// Records whose playback offsets fall in the same `window_secs` window
+    Window,
+    /// Every `size` records
+    Records,
+    /// Consecutive records from the same speaker
+    SpeakerTurn,
+    /// The whole transcript in a single request once playback ends
+    Full,
+}
+
+/// Batching settings for a webhook broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct BatchOptions {
+    /// Grouping mode
+    pub mode: BatchMode,
+    /// Window length for `window` mode, in seconds
+    pub window_secs: u64,
+    /// Records per request for `records` mode
+    pub size: usize,
+}
+
+impl BatchOptions {
+    pub fn is_enabled(&self) -> bool {
+        self.mode != BatchMode::None
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self.mode {
+            BatchMode::Window if self.window_secs == 0 => Err(anyhow::anyhow!(
+                "window batching requires window_secs greater than 0"
+            )),
+            BatchMode::Records if self.size == 0 => Err(anyhow::anyhow!(
+                "records batching requires size greater than 0"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Buffers playback steps and hands them back as batches
+///
+/// A window or speaker turn is closed by the first record that falls outside it,
+/// so its batch is sent when that record arrives; whatever is left is returned
+/// by `flush` when playback completes.
+#[derive(Debug, Default)]
+pub struct Batcher {
+    options: BatchOptions,
+    buffer: Vec<PlaybackStep>,
+}
+
+impl Batcher {
+    pub fn new(options: BatchOptions) -> Self {
+        Self {
+            options,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn is_batching(&self) -> bool {
+        self.options.is_enabled()
+    }
+
+    /// Adds a step and returns the batch that became ready, if any
+    pub fn push(&mut self, step: PlaybackStep) -> Option<Vec<PlaybackStep>> {
+        let closes_batch = match (self.options.mode, self.buffer.last()) {
+            (BatchMode::Window, Some(last)) => {
+                let window = self.options.window_secs as i64;
+                (step.time as i64).div_euclid(window) != (last.time as i64).div_euclid(window)
+            }
+            (BatchMode::SpeakerTurn, Some(last)) => step.record.speaker != last.record.speaker,
+            _ => false,
+        };
+        let ready = if closes_batch { self.flush() } else { None };
+
+        self.buffer.push(step);
+        match self.options.mode {
+            BatchMode::None => self.flush(),
+            BatchMode::Records if self.buffer.len() >= self.options.size => self.flush(),
+            _ => ready,
+        }
+    }
+
+    /// Takes whatever is buffered
+    pub fn flush(&mut self) -> Option<Vec<PlaybackStep>> {
+        (!self.buffer.is_empty()).then(|| std::mem::take(&mut self.buffer))
+    }
+}
@@ -2,21 +2,109 @@ use futures_util::{SinkExt, StreamExt};
 use poem::EndpointExt;
 use poem::{Result, Route, Server, middleware::Tracing, web::websocket::{WebSocket, WebSocketStream}, handler, web::Path};
 use poem_openapi::{ApiResponse, Object, OpenApi, OpenApiService, payload::Json};
-use restream::adapter::{SessionStore, WebSocketBroadcaster, WebhookBroadcaster};
-use restream::consts::{WEBHOOK_URL_PROD, WEBHOOK_URL_TEST};
-use restream::interface::{Broadcaster, TranscriptFile, TranscriptRecord, WebSocketMessage};
+use clap::{Args, Parser, Subcommand};
+use restream::adapter::composite::{
+    BroadcastEntry, BroadcastStatus, CompositeBroadcastRequest, SinkStatus, insert_broadcast,
+};
+use restream::adapter::{
+    AudioMessage, BroadcastStatusHandle, BroadcastStore, CompositeBroadcaster, FileSink, RewindSession,
+    SessionStore, SinkConfig, StdoutBroadcaster, SubscriberSink, WebSocketClientBroadcaster,
+    WebhookBroadcaster, WebhookDelivery, broadcast_session_with_replies, stream_audio_session,
+};
+use restream::adapter::channels::{ChannelOptions, channel_order, join_channel_session};
+use restream::audio::{AudioEncoding, AudioOptions, AudioSource, AudioTrack};
+use restream::batch::{BatchMode, BatchOptions};
+use restream::chaos::ChaosOptions;
+use restream::dead_letter::{DeadLetter, DeadLetterHandle, DeadLetterStore, Destination};
+use restream::clock::TokioClock;
+use restream::consts::{DEAD_LETTERS_FILE, SCHEDULES_FILE, SCRIPTS_DIR, STRICT_TRANSCRIPTS_ENV, TEMPLATES_DIR, WEBHOOK_TARGETS_FILE, WEBHOOK_URL_PROD, WEBHOOK_URL_TEST};
+use restream::generate::{GeneratedTranscript, GeneratorTemplate, generate_transcripts, save_transcripts};
+use restream::report::{JsonExpectation, ReportHandle, ReportStore, WebhookExpectations, WebhookReport, insert_report};
+use restream::recorder::{CapturedEvent, RecordingSession, RecordingStore, validate_filename};
+use restream::interface::{BroadcastSink, Broadcaster, TranscriptFile, TranscriptRecord};
+use restream::schedule::{Schedule, ScheduleRequest, ScheduleStore, spawn_scheduler};
+use restream::redact::{RedactionMode, RedactionOptions};
+use restream::speakers::SpeakerOverrides;
+use restream::transform::TranscriptTransforms;
+use restream::stats::TranscriptStats;
+use restream::session::{SessionContext, SessionKind, SessionKinds, SessionParams};
+use restream::template::{WebhookTargets, WebhookTemplates};
+use restream::playback::Playback;
+use restream::playlist::{Playlist, PlaylistItem, PlaylistOptions};
+use restream::lint::{LintOptions, LintReport, Severity, lint_contents, lint_dir, lint_file};
+use restream::queue::{JobPlayback, JobStatus, QueueLimits, WebhookJob, WebhookQueue};
+use restream::script::{Script, ScriptPlayback};
+use restream::turns::TurnTakingOptions;
+use restream::range::TranscriptRange;
+use restream::transcript::{
+    load_all_transcripts, load_transcript_from_file, write_transcript_to_file,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path as StdPath;
+use std::path::{Path as StdPath, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::filter::Directive;
 use uuid::Uuid;
 
+fn default_bucket_secs() -> u64 {
+    60
+}
+
+/// WebSocket URL of each speaker's stream of a channel-separated session
+fn speaker_websocket_urls(session_id: &str, speakers: &[String]) -> std::collections::BTreeMap<String, String> {
+    speakers
+        .iter()
+        .map(|speaker| {
+            let mut url = url::Url::parse("ws://0.0.0.0:8080").expect("base URL");
+            url.path_segments_mut()
+                .expect("base URL has a path")
+                .extend(["ws", session_id, "speakers", speaker]);
+            (speaker.clone(), url.to_string())
+        })
+        .collect()
+}
+
+/// Loads a script from the scripts directory by bare filename
+fn load_script(name: &str) -> anyhow::Result<Script> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(anyhow::anyhow!("Invalid script name: {}", name));
+    }
+    Script::load(&StdPath::new(SCRIPTS_DIR).join(name))
+}
+
+/// Rejects transcript options on a script request, since a script has no transcript to apply them to
+fn check_script_options(
+    range: &TranscriptRange,
+    speakers: &SpeakerOverrides,
+    redaction: Option<&RedactionOptions>,
+    chaos: &ChaosOptions,
+    batch: Option<&BatchOptions>,
+) -> anyhow::Result<()> {
+    let unsupported: Vec<&str> = [
+        (!range.is_full(), "a range"),
+        (!speakers.is_empty(), "speaker overrides"),
+        (redaction.is_some(), "redaction"),
+        (!chaos.is_disabled(), "chaos"),
+        (batch.is_some_and(BatchOptions::is_enabled), "batching"),
+    ]
+    .into_iter()
+    .filter_map(|(set, option)| set.then_some(option))
+    .collect();
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!("Scripts cannot be combined with {}", unsupported.join(", ")))
+}
+
+fn default_generate_count() -> usize {
+    1
+}
+
 fn default_filename() -> String {
     "intake_call_test.csv".to_string()
 }
@@ -32,45 +120,620 @@ enum TranscriptResponse {
     Ok(Json<Vec<TranscriptFile>>),
 }
 
+#[derive(ApiResponse)]
+enum InvalidTranscriptsResponse {
+    /// Lint reports of the catalog transcripts that have errors
+    #[oai(status = 200)]
+    Ok(Json<Vec<LintReport>>),
+}
+
 #[derive(Serialize, Deserialize, Debug, Object)]
+struct ValidateTranscriptRequest {
+    /// Transcript in the catalog to validate
+    filename: Option<String>,
+    /// Transcript text to validate instead of a catalog file
+    content: Option<String>,
+    /// Format of `content`: csv (default) or json
+    format: Option<String>,
+    /// Known speakers and gap threshold
+    #[oai(default)]
+    #[serde(default)]
+    options: LintOptions,
+}
+
+#[derive(ApiResponse)]
+enum ValidateTranscriptResponse {
+    /// Lint report; the transcript is valid when `errors` is 0
+    #[oai(status = 200)]
+    Ok(Json<LintReport>),
+    /// Neither or both of `filename` and `content`, or an unknown format
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Transcript not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Object)]
+struct GenerateTranscriptsRequest {
+    /// Template file in templates/, e.g. intake_call.json
+    template_name: Option<String>,
+    /// Template given inline instead of by name
+    template: Option<GeneratorTemplate>,
+    /// Transcripts to generate
+    #[oai(default = "default_generate_count")]
+    #[serde(default = "default_generate_count")]
+    count: usize,
+    /// Seed of the first transcript; overrides the template's seed
+    seed: Option<u64>,
+    /// Save the transcripts to the catalog as `<save_as>_001.csv`, `<save_as>_002.csv`, ...
+    save_as: Option<String>,
+}
+
+#[derive(Serialize, Debug, Object)]
+struct GenerateTranscriptsResponseBody {
+    /// Seed of the first transcript; each following transcript adds one
+    seed: u64,
+    /// Catalog filenames, when saved
+    filenames: Vec<String>,
+    transcripts: Vec<GeneratedTranscript>,
+}
+
+#[derive(ApiResponse)]
+enum GenerateTranscriptsResponse {
+    /// Generated transcripts
+    #[oai(status = 200)]
+    Ok(Json<GenerateTranscriptsResponseBody>),
+    /// Invalid template, count or filename, or a saved transcript that already exists
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Template not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum TranscriptStatsResponse {
+    /// Transcript statistics
+    #[oai(status = 200)]
+    Ok(Json<Box<TranscriptStats>>),
+    /// Invalid filename or transcript that fails to load
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Transcript not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(Serialize, Debug, Object)]
 struct WebsocketInfo {
     /// WebSocket URL for rewind connection
     websocket_url: String,
+    /// One stream per speaker, by speaker, on channel-separated sessions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    speaker_websocket_urls: Option<std::collections::BTreeMap<String, String>>,
+    /// Format of the binary audio frames streamed alongside the records
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    audio: Option<AudioOptions>,
     /// Job description enrichment session ID (if applicable)
     job_description_enrichment_session: Option<String>,
     /// Candidate profile enrichment session ID (if applicable)
     candidate_profile_enrichment_session: Option<String>,
+    /// Session kind, for kinds enabled through `RESTREAM_SESSION_KINDS`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    session_kind: Option<String>,
+    /// Session ID, for kinds enabled through `RESTREAM_SESSION_KINDS`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    session_id: Option<String>,
     /// Port number for WebSocket connection
     port: u16,
 }
 
+impl WebsocketInfo {
+    /// Empty websocket information returned when a rewind cannot be set up
+    fn unavailable() -> Self {
+        Self {
+            websocket_url: "".to_string(),
+            speaker_websocket_urls: None,
+            audio: None,
+            job_description_enrichment_session: None,
+            candidate_profile_enrichment_session: None,
+            session_kind: None,
+            session_id: None,
+            port: 0,
+        }
+    }
+
+    /// Sets the session ID field for the session's kind
+    fn with_session(mut self, session: &SessionContext) -> Self {
+        let id = Some(session.id.to_string());
+        match session.kind.name() {
+            SessionKind::JOB_DESCRIPTION => self.job_description_enrichment_session = id,
+            SessionKind::CANDIDATE_PROFILE => self.candidate_profile_enrichment_session = id,
+            kind => {
+                self.session_kind = Some(kind.to_string());
+                self.session_id = id;
+            }
+        }
+        self
+    }
+}
+
 #[derive(ApiResponse)]
 enum RewindResponse {
     /// Rewind initiated successfully with websocket information
     #[oai(status = 200)]
     Ok(Json<WebsocketInfo>),
+    /// Invalid audio options, or options that cannot be combined
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+}
+
+impl From<WebsocketInfo> for RewindResponse {
+    fn from(info: WebsocketInfo) -> Self {
+        RewindResponse::Ok(Json(info))
+    }
 }
 
 #[derive(ApiResponse)]
 enum WebhookBroadcastResponse {
-    /// Webhook broadcast initiated successfully
+    /// Webhook broadcast queued successfully
     #[oai(status = 200)]
     Ok(Json<serde_json::Value>),
     /// Error occurred during broadcast
     #[oai(status = 400)]
     BadRequest(Json<serde_json::Value>),
+    /// Too many webhook broadcasts are already waiting
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Object)]
+struct RedeliverRequest {
+    /// Dead letters to redeliver; every dead letter when empty
+    #[oai(default)]
+    ids: Vec<String>,
+    /// Named target to redeliver to instead of the original webhook
+    target: Option<String>,
+    /// Webhook URL to redeliver to instead of the original webhook
+    url: Option<String>,
+}
+
+#[derive(ApiResponse)]
+enum DeadLetterListResponse {
+    /// Every undeliverable webhook message, oldest first
+    #[oai(status = 200)]
+    Ok(Json<Vec<DeadLetter>>),
+}
+
+#[derive(ApiResponse)]
+enum RedeliverResponse {
+    /// Redelivery attempted; per-message results
+    #[oai(status = 200)]
+    Ok(Json<serde_json::Value>),
+    /// Invalid destination
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Dead letter not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum ScheduleListResponse {
+    /// Every schedule, oldest first
+    #[oai(status = 200)]
+    Ok(Json<Vec<Schedule>>),
+}
+
+#[derive(ApiResponse)]
+enum ScheduleResponse {
+    /// The schedule
+    #[oai(status = 200)]
+    Ok(Json<Box<Schedule>>),
+    /// Invalid schedule or action
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Schedule not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum ScheduleDeleteResponse {
+    /// Schedule deleted
+    #[oai(status = 200)]
+    Ok(Json<serde_json::Value>),
+    /// Schedule not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum WebhookJobListResponse {
+    /// Every webhook job, oldest first
+    #[oai(status = 200)]
+    Ok(Json<Vec<JobStatus>>),
+}
+
+#[derive(ApiResponse)]
+enum WebhookJobResponse {
+    /// Webhook job status
+    #[oai(status = 200)]
+    Ok(Json<JobStatus>),
+    /// Job not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Object)]
+struct RecordingInfo {
+    /// Recording ID
+    recording_id: String,
+    /// Transcript filename the capture will be saved as
+    filename: String,
+    /// WebSocket URL accepting live events
+    websocket_url: String,
+    /// HTTP URL accepting live events via POST
+    webhook_url: String,
+}
+
+#[derive(ApiResponse)]
+enum RecordingResponse {
+    /// Recording started with ingest information
+    #[oai(status = 200)]
+    Ok(Json<RecordingInfo>),
+    /// Invalid recording request
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum RecordingActionResponse {
+    /// Action applied to the recording
+    #[oai(status = 200)]
+    Ok(Json<serde_json::Value>),
+    /// Invalid event or recording could not be saved
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    /// Recording not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum WebhookReportResponse {
+    /// Webhook broadcast report
+    #[oai(status = 200)]
+    Ok(Json<Box<WebhookReport>>),
+    /// Report not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum CompositeBroadcastResponse {
+    /// Composite broadcast started
+    #[oai(status = 200)]
+    Ok(Json<serde_json::Value>),
+    /// Invalid broadcast request
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+enum BroadcastStatusResponse {
+    /// Composite broadcast status
+    #[oai(status = 200)]
+    Ok(Json<BroadcastStatus>),
+    /// Broadcast not found
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+/// A composite broadcast that was started
+struct StartedBroadcast {
+    broadcast_id: String,
+    /// Description of every sink, in request order
+    sinks: Vec<String>,
+    has_subscribers: bool,
+}
+
+/// Starts composite broadcasts, for `POST /broadcasts` and for schedules
+#[derive(Clone)]
+struct BroadcastLauncher {
+    session_kinds: SessionKinds,
+    webhook_targets: Arc<WebhookTargets>,
+    broadcasts: BroadcastStore,
+    reports: ReportStore,
+    dead_letters: Arc<DeadLetterStore>,
+    /// Webhook sinks wait for slots on the same queue as `/webhook-broadcast` jobs
+    webhook_queue: WebhookQueue,
+}
+
+/// What a composite broadcast plays
+enum BroadcastSource {
+    Transcript(Vec<TranscriptRecord>),
+    Playlist(Playlist),
+}
+
+impl BroadcastLauncher {
+    /// Checks a request without starting it and returns its session
+    fn validate(&self, request: &CompositeBroadcastRequest) -> Result<SessionContext, String> {
+        let session = request
+            .session_params()
+            .resolve(&self.session_kinds)
+            .map_err(|e| e.to_string())?;
+        if request.sinks.is_empty() {
+            return Err("At least one sink is required".to_string());
+        }
+        if let Err(e) = request.chaos.validate() {
+            return Err(format!("Invalid chaos options: {}", e));
+        }
+        for sink in &request.sinks {
+            if let SinkConfig::Webhook(config) = sink {
+                if let Some(name) = &config.target
+                    && let Err(e) = self.webhook_targets.get(name)
+                {
+                    return Err(format!("Invalid webhook sink: {}", e));
+                }
+                if let Err(e) = config.batch.validate() {
+                    return Err(format!("Invalid batch options: {}", e));
+                }
+            }
+        }
+        Ok(session)
+    }
+
+    async fn start(&self, request: CompositeBroadcastRequest) -> Result<StartedBroadcast, String> {
+        let session = self.validate(&request)?;
+
+        let broadcast_id = Uuid::new_v4().to_string();
+        let filename = request.describe_transcript();
+        let client = self.webhook_queue.client();
+        let mut subscribers = None;
+        let mut sinks: Vec<Arc<dyn BroadcastSink>> = Vec::new();
+        // Response report of each sink, registered once the broadcast starts
+        let mut reports: Vec<Option<WebhookReport>> = Vec::new();
+        // Queue target of each webhook sink: its named target or URL
+        let mut webhook_targets = Vec::new();
+        for sink in &request.sinks {
+            let mut report = None;
+            let sink: Arc<dyn BroadcastSink> = match sink {
+                SinkConfig::WebSocket(config) => {
+                    let sender = subscribers
+                        .get_or_insert_with(|| tokio::sync::broadcast::channel(1024).0)
+                        .clone();
+                    Arc::new(SubscriberSink {
+                        label: config
+                            .label
+                            .clone()
+                            .unwrap_or_else(|| "websocket subscribers".to_string()),
+                        subscribers: sender,
+                    })
+                }
+                SinkConfig::Webhook(config) => {
+                    let (url, templates, target) = match &config.target {
+                        Some(name) => match self.webhook_targets.get(name) {
+                            Ok(target) => (target.url.clone(), target.templates.clone(), Some(target.name.clone())),
+                            Err(e) => return Err(format!("Invalid webhook sink: {}", e)),
+                        },
+                        None => (
+                            config.url.clone().unwrap_or_else(|| {
+                                if config.use_test { WEBHOOK_URL_TEST } else { WEBHOOK_URL_PROD }.to_string()
+                            }),
+                            WebhookTemplates::default(),
+                            None,
+                        ),
+                    };
+                    webhook_targets.push(target.clone().unwrap_or_else(|| url.clone()));
+                    let report_id = Uuid::new_v4().to_string();
+                    report = Some(WebhookReport::new(
+                        report_id.clone(),
+                        filename.clone(),
+                        url.clone(),
+                        config.expectations.clone(),
+                    ));
+                    let broadcaster = WebhookBroadcaster::new(url, client.clone())
+                        .with_templates(templates)
+                        .with_batch(config.batch.clone())
+                        .with_report(ReportHandle {
+                            store: self.reports.clone(),
+                            report_id,
+                        })
+                        .with_dead_letters(DeadLetterHandle {
+                            store: self.dead_letters.clone(),
+                            target,
+                        });
+                    Arc::new(WebhookDelivery::new(Arc::new(broadcaster)))
+                }
+                SinkConfig::File(config) => match FileSink::open(&config.filename).await {
+                    Ok(sink) => Arc::new(sink),
+                    Err(e) => return Err(format!("Invalid file sink: {}", e)),
+                },
+            };
+            sinks.push(sink);
+            reports.push(report);
+        }
+
+        let mut transforms = TranscriptTransforms::new(request.speakers.clone(), request.redaction.clone());
+        let source = match &request.playlist {
+            Some(options) => match Playlist::load_with(options, StdPath::new("transcript"), &mut transforms).await {
+                Ok(playlist) => BroadcastSource::Playlist(playlist),
+                Err(e) => {
+                    error!("Error loading playlist {}: {}", filename, e);
+                    return Err(format!("Failed to load playlist: {}", e));
+                }
+            },
+            None => {
+                let transcript_path = format!("transcript/{}", filename);
+                match transforms.load(StdPath::new(&transcript_path)).await {
+                    Ok(records) => BroadcastSource::Transcript(records),
+                    Err(e) => {
+                        error!("Error loading transcript {}: {}", filename, e);
+                        return Err(format!("Failed to load transcript: {}", e));
+                    }
+                }
+            }
+        };
+
+        let status = BroadcastStatus {
+            broadcast_id: broadcast_id.clone(),
+            filename: filename.clone(),
+            state: "pending".to_string(),
+            sinks: sinks
+                .iter()
+                .zip(&reports)
+                .map(|(sink, report)| SinkStatus {
+                    sink: sink.describe(),
+                    state: "pending".to_string(),
+                    delivered: 0,
+                    error: None,
+                    report_id: report.as_ref().map(|report| report.report_id.clone()),
+                })
+                .collect(),
+            finished_at: None,
+        };
+        let sink_names: Vec<String> = status.sinks.iter().map(|sink| sink.sink.clone()).collect();
+        let has_subscribers = subscribers.is_some();
+        for report in reports.into_iter().flatten() {
+            insert_report(&self.reports, report).await;
+        }
+        let cancel = CancellationToken::new();
+        insert_broadcast(
+            &self.broadcasts,
+            BroadcastEntry {
+                status,
+                subscribers,
+                cancel: cancel.clone(),
+            },
+        )
+        .await;
+
+        let broadcaster = Arc::new(CompositeBroadcaster {
+            sinks,
+            status: Some(BroadcastStatusHandle {
+                store: self.broadcasts.clone(),
+                broadcast_id: broadcast_id.clone(),
+            }),
+        });
+
+        info!(
+            "Starting composite broadcast {} of {} to {} sink(s) with session_id: {}",
+            broadcast_id,
+            filename,
+            sink_names.len(),
+            session.id
+        );
+        let start_delay = request.start_delay_secs;
+        let chaos = request.chaos;
+        let webhook_queue = self.webhook_queue.clone();
+        tokio::spawn(async move {
+            // A broadcast cancelled while waiting stops at once, without taking queue slots
+            let _slots = tokio::select! {
+                _ = cancel.cancelled() => None,
+                slots = async {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(start_delay)).await;
+                    webhook_queue.acquire_slots(&webhook_targets).await
+                } => Some(slots),
+            };
+            let events = match source {
+                BroadcastSource::Transcript(records) => {
+                    let playback = Playback::new(records, chaos, TokioClock::shared());
+                    if let Some(seed) = playback.chaos_seed() {
+                        info!("Chaos enabled for composite broadcast (seed {})", seed);
+                    }
+                    playback.into_stream()
+                }
+                BroadcastSource::Playlist(playlist) => {
+                    if let Some(seed) = playlist.shuffle_seed() {
+                        info!("Shuffling composite broadcast playlist (seed {})", seed);
+                    }
+                    playlist.into_stream(chaos, TokioClock::shared())
+                }
+            };
+            let report = broadcaster.broadcast(session, events, cancel).report().await;
+            if let Err(e) = report.into_result() {
+                error!("Composite broadcast failed: {}", e);
+            }
+        });
+
+        Ok(StartedBroadcast {
+            broadcast_id,
+            sinks: sink_names,
+            has_subscribers,
+        })
+    }
+}
+
+/// Cuts a transcript to the requested range, returning the records and the index of the first
+fn slice(
+    range: &TranscriptRange,
+    records: Vec<TranscriptRecord>,
+) -> anyhow::Result<(Vec<TranscriptRecord>, usize)> {
+    if range.is_full() {
+        return Ok((records, 0));
+    }
+    let total = records.len();
+    let slice = range.apply(records)?;
+    info!(
+        "Playing records {}..{} of {} ({} context)",
+        slice.offset + slice.context,
+        slice.offset + slice.records.len(),
+        total,
+        slice.context
+    );
+    Ok((slice.records, slice.offset))
 }
 
 struct Api {
+    session_kinds: SessionKinds,
+    webhook_targets: Arc<WebhookTargets>,
     sessions: SessionStore,
+    recordings: RecordingStore,
+    reports: ReportStore,
+    broadcasts: BroadcastStore,
+    webhook_queue: WebhookQueue,
+    dead_letters: Arc<DeadLetterStore>,
+    launcher: BroadcastLauncher,
+    schedules: Arc<ScheduleStore>,
+}
+
+fn schedule_not_found(schedule_id: String) -> ScheduleResponse {
+    ScheduleResponse::NotFound(Json(serde_json::json!({
+        "status": "error",
+        "message": "Schedule not found",
+        "schedule_id": schedule_id
+    })))
+}
+
+impl Api {
+    async fn set_schedule_paused(&self, schedule_id: String, paused: bool) -> ScheduleResponse {
+        match self.schedules.set_paused(&schedule_id, paused, chrono::Utc::now()).await {
+            Some(Ok(schedule)) => {
+                info!("Schedule {} is now {:?}", schedule_id, schedule.state);
+                ScheduleResponse::Ok(Json(Box::new(schedule)))
+            }
+            Some(Err(e)) => ScheduleResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "schedule_id": schedule_id
+            }))),
+            None => schedule_not_found(schedule_id),
+        }
+    }
 }
 
 #[OpenApi]
 impl Api {
-    /// List all transcripts
+    /// List all transcripts; ones that fail to parse are listed by `/transcripts/invalid`
     #[oai(path = "/transcripts", method = "get")]
     async fn list_transcripts(&self) -> TranscriptResponse {
-        match load_all_transcripts().await {
+        match load_all_transcripts(StdPath::new("transcript/")).await {
             Ok(transcripts) => TranscriptResponse::Ok(Json(transcripts)),
             Err(e) => {
                 error!("Error loading transcripts: {}", e);
@@ -79,194 +742,1111 @@ impl Api {
         }
     }
 
+    /// List catalog transcripts with errors, as checked at startup
+    #[oai(path = "/transcripts/invalid", method = "get")]
+    async fn list_invalid_transcripts(&self) -> InvalidTranscriptsResponse {
+        match lint_dir(StdPath::new("transcript"), &LintOptions::default()) {
+            Ok(reports) => InvalidTranscriptsResponse::Ok(Json(
+                reports.into_iter().filter(|report| !report.is_valid()).collect(),
+            )),
+            Err(e) => {
+                error!("Error validating transcripts: {}", e);
+                InvalidTranscriptsResponse::Ok(Json(vec![]))
+            }
+        }
+    }
+
+    /// Check a catalog transcript, or uploaded transcript text, for problems
+    #[oai(path = "/transcripts/validate", method = "post")]
+    async fn validate_transcript(
+        &self,
+        request: Json<ValidateTranscriptRequest>,
+    ) -> ValidateTranscriptResponse {
+        let request = request.0;
+        let bad_request = |message: String| {
+            ValidateTranscriptResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })))
+        };
+
+        match (request.filename, request.content) {
+            (Some(filename), None) => {
+                if filename.is_empty()
+                    || filename.contains(['/', '\\'])
+                    || filename.starts_with('.')
+                {
+                    return bad_request(format!("Invalid filename: {}", filename));
+                }
+                let path = StdPath::new("transcript").join(&filename);
+                if !path.exists() {
+                    return ValidateTranscriptResponse::NotFound(Json(serde_json::json!({
+                        "status": "error",
+                        "message": format!("Transcript not found: {}", filename)
+                    })));
+                }
+                match lint_file(&path, &request.options) {
+                    Ok(report) => ValidateTranscriptResponse::Ok(Json(report)),
+                    Err(e) => bad_request(format!("Failed to read transcript: {}", e)),
+                }
+            }
+            (None, Some(content)) => {
+                let json = match request.format.as_deref() {
+                    None | Some("csv") => false,
+                    Some("json") => true,
+                    Some(other) => return bad_request(format!("Unknown format: {}", other)),
+                };
+                ValidateTranscriptResponse::Ok(Json(lint_contents(
+                    None,
+                    &content,
+                    json,
+                    &request.options,
+                )))
+            }
+            _ => bad_request("Give either filename or content".to_string()),
+        }
+    }
+
+    /// Generate synthetic transcripts from a template, optionally saving them to the catalog
+    #[oai(path = "/transcripts/generate", method = "post")]
+    async fn generate_transcripts(
+        &self,
+        request: Json<GenerateTranscriptsRequest>,
+    ) -> GenerateTranscriptsResponse {
+        let request = request.0;
+        let bad_request = |message: String| {
+            GenerateTranscriptsResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })))
+        };
+
+        let template = match (request.template_name, request.template) {
+            (Some(name), None) => {
+                if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+                    return bad_request(format!("Invalid template name: {}", name));
+                }
+                let path = StdPath::new(TEMPLATES_DIR).join(&name);
+                if !path.exists() {
+                    return GenerateTranscriptsResponse::NotFound(Json(serde_json::json!({
+                        "status": "error",
+                        "message": format!("Template not found: {}", name)
+                    })));
+                }
+                match GeneratorTemplate::load(&path) {
+                    Ok(template) => template,
+                    Err(e) => return bad_request(e.to_string()),
+                }
+            }
+            (None, Some(template)) => template,
+            _ => return bad_request("Give either template_name or template".to_string()),
+        };
+        if let Some(prefix) = &request.save_as
+            && (prefix.is_empty() || prefix.contains(['/', '\\', '.']))
+        {
+            return bad_request(format!("Invalid save_as: {}", prefix));
+        }
+
+        let (seed, transcripts) = match generate_transcripts(&template, request.count, request.seed) {
+            Ok(generated) => generated,
+            Err(e) => return bad_request(format!("Invalid template: {}", e)),
+        };
+        info!("Generated {} transcript(s) with seed {}", transcripts.len(), seed);
+
+        let mut filenames = Vec::new();
+        if let Some(prefix) = &request.save_as {
+            match save_transcripts(StdPath::new("transcript"), prefix, &transcripts, false) {
+                Ok(paths) => {
+                    filenames = paths
+                        .iter()
+                        .filter_map(|path| path.file_name().and_then(|s| s.to_str()))
+                        .map(str::to_string)
+                        .collect();
+                    info!("Saved {} generated transcript(s) as {}_*.csv", filenames.len(), prefix);
+                }
+                Err(e) => return bad_request(format!("Failed to save transcripts: {}", e)),
+            }
+        }
+
+        GenerateTranscriptsResponse::Ok(Json(GenerateTranscriptsResponseBody {
+            seed,
+            filenames,
+            transcripts,
+        }))
+    }
+
+    /// Conversation statistics for a transcript: talk time, turns, pace, silences and a timeline
+    #[oai(path = "/transcripts/:filename/stats", method = "get")]
+    async fn transcript_stats(
+        &self,
+        filename: poem_openapi::param::Path<String>,
+        /// Timeline bucket length, in seconds
+        #[oai(name = "bucket_secs", default = "default_bucket_secs")]
+        bucket_secs: poem_openapi::param::Query<u64>,
+    ) -> TranscriptStatsResponse {
+        let filename = filename.0;
+        let error = |message: String| {
+            Json(serde_json::json!({
+                "status": "error",
+                "message": message,
+                "filename": filename
+            }))
+        };
+
+        if filename.contains(['/', '\\']) || filename.starts_with('.') {
+            return TranscriptStatsResponse::BadRequest(error(format!("Invalid filename: {}", filename)));
+        }
+        let path = StdPath::new("transcript").join(&filename);
+        if !path.exists() {
+            return TranscriptStatsResponse::NotFound(error(format!("Transcript not found: {}", filename)));
+        }
+        match load_transcript_from_file(&path).await {
+            Ok(records) => TranscriptStatsResponse::Ok(Json(Box::new(TranscriptStats::compute(
+                &records,
+                bucket_secs.0,
+            )))),
+            Err(e) => TranscriptStatsResponse::BadRequest(error(format!("Failed to load transcript: {}", e))),
+        }
+    }
+
     /// Rewind a transcript by filename (defaults to intake_call.csv)
+    #[allow(clippy::too_many_arguments)]
     #[oai(path = "/websocket-broadcast", method = "get")]
     async fn handle_websocket_broadcast(
         &self,
         #[oai(name = "filename", default = "default_filename")]
         filename: poem_openapi::param::Query<String>,
+        /// Branching script in scripts/ to run instead of a transcript; replies choose its branches
+        #[oai(name = "script")] script: poem_openapi::param::Query<Option<String>>,
         #[oai(name = "job_description_enrichment_session")]
-        job_description_enrichment_session: poem_openapi::param::Query<Option<i32>>,
+        job_description_enrichment_session: poem_openapi::param::Query<Option<String>>,
         #[oai(name = "candidate_profile_enrichment_session")]
-        candidate_profile_enrichment_session: poem_openapi::param::Query<Option<i32>>,
+        candidate_profile_enrichment_session: poem_openapi::param::Query<Option<String>>,
+        /// Kind of session given by `session_id`, for kinds without their own parameter
+        #[oai(name = "session_kind")] session_kind: poem_openapi::param::Query<Option<String>>,
+        /// Session ID (numeric or text) of the `session_kind` session
+        #[oai(name = "session_id")] session_id: poem_openapi::param::Query<Option<String>>,
+        /// Speakers: rename a speaker, as `name=new name` (repeatable)
+        #[oai(name = "rename_speaker")] rename_speaker: poem_openapi::param::Query<Vec<String>>,
+        /// Speakers: set a speaker's role, as `name=role` (repeatable)
+        #[oai(name = "speaker_role")] speaker_role: poem_openapi::param::Query<Vec<String>>,
+        /// Speakers: replace names not renamed with `Speaker 1`, `Speaker 2`, ...
+        #[oai(name = "anonymize_speakers")] anonymize_speakers: poem_openapi::param::Query<Option<bool>>,
+        /// Redaction: redact PII (emails, phones, salaries, speaker names) before broadcasting
+        #[oai(name = "redact")] redact: poem_openapi::param::Query<Option<bool>>,
+        /// Redaction: `redact` for tokens such as `[NAME_1]`, `pseudonymize` for stand-in values
+        #[oai(name = "redact_mode")] redact_mode: poem_openapi::param::Query<Option<RedactionMode>>,
+        /// Redaction: further personal name to redact (repeatable)
+        #[oai(name = "redact_name")] redact_name: poem_openapi::param::Query<Vec<String>>,
+        /// Redaction: company name to redact (repeatable)
+        #[oai(name = "redact_company")] redact_company: poem_openapi::param::Query<Vec<String>>,
+        /// Range: first record to play, as HH:MM:SS, MM:SS or a record index
+        #[oai(name = "start")] start: poem_openapi::param::Query<Option<String>>,
+        /// Range: last record to play (inclusive), as HH:MM:SS, MM:SS or a record index
+        #[oai(name = "end")] end: poem_openapi::param::Query<Option<String>>,
+        /// Range: number of records before `start` sent instantly as context
+        #[oai(name = "context")] context: poem_openapi::param::Query<Option<usize>>,
+        /// Interactive: speaker name or role whose records wait for the client's reply (repeatable)
+        #[oai(name = "interactive_speaker")] interactive_speaker: poem_openapi::param::Query<Vec<String>>,
+        /// Interactive: seconds to wait for a reply before carrying on (default 30)
+        #[oai(name = "interactive_timeout_secs")]
+        interactive_timeout_secs: poem_openapi::param::Query<Option<u64>>,
+        /// Interactive: send each reply back as a record
+        #[oai(name = "echo_replies", default)] echo_replies: poem_openapi::param::Query<bool>,
+        /// Interactive: speaker of echoed replies (default `Agent`)
+        #[oai(name = "reply_speaker")] reply_speaker: poem_openapi::param::Query<Option<String>>,
+        /// Channels: tag messages with a per-speaker `channel` and allow a stream per speaker
+        #[oai(name = "channels", default)] channels: poem_openapi::param::Query<bool>,
+        /// Channels: seconds to wait, from the first stream, for a stream per speaker (default 10)
+        #[oai(name = "channel_wait_secs")] channel_wait_secs: poem_openapi::param::Query<Option<u64>>,
+        /// Audio: stream binary audio frames alongside the records, as `pcm` or `mulaw`
+        #[oai(name = "audio")] audio: poem_openapi::param::Query<Option<AudioEncoding>>,
+        /// Audio: `silence` or `tone` for each utterance, or `wav` for each record's audio file
+        #[oai(name = "audio_source")] audio_source: poem_openapi::param::Query<Option<AudioSource>>,
+        /// Audio: samples per second (default 16000 for pcm, 8000 for mulaw)
+        #[oai(name = "audio_sample_rate")] audio_sample_rate: poem_openapi::param::Query<Option<u32>>,
+        /// Audio: frame length in milliseconds (default 20)
+        #[oai(name = "audio_frame_ms")] audio_frame_ms: poem_openapi::param::Query<Option<u32>>,
+        /// Audio: frequency of the `tone` source in hertz (default 440)
+        #[oai(name = "audio_tone_hz")] audio_tone_hz: poem_openapi::param::Query<Option<f64>>,
+        /// Chaos: seed for reproducible fault injection
+        #[oai(name = "chaos_seed")] chaos_seed: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: probability (0.0 - 1.0) of dropping a message
+        #[oai(name = "chaos_drop_rate")] chaos_drop_rate: poem_openapi::param::Query<Option<f64>>,
+        /// Chaos: probability (0.0 - 1.0) of duplicating a message
+        #[oai(name = "chaos_duplicate_rate")]
+        chaos_duplicate_rate: poem_openapi::param::Query<Option<f64>>,
+        /// Chaos: shuffle messages within windows of this many records
+        #[oai(name = "chaos_reorder_window")]
+        chaos_reorder_window: poem_openapi::param::Query<Option<usize>>,
+        /// Chaos: fixed latency added before each message, in milliseconds
+        #[oai(name = "chaos_latency_ms")] chaos_latency_ms: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: maximum random jitter added to each message, in milliseconds
+        #[oai(name = "chaos_jitter_ms")] chaos_jitter_ms: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: force a disconnect before this record index is sent
+        #[oai(name = "chaos_disconnect_at_record")]
+        chaos_disconnect_at_record: poem_openapi::param::Query<Option<usize>>,
+        /// Chaos: force a disconnect after this many seconds
+        #[oai(name = "chaos_disconnect_after_secs")]
+        chaos_disconnect_after_secs: poem_openapi::param::Query<Option<u64>>,
     ) -> RewindResponse {
-        let filename = filename.0;
-        let job_desc_session = job_description_enrichment_session.0;
-        let candidate_session = candidate_profile_enrichment_session.0;
-        
-        // Validate that exactly one session type is provided
-        let session_id = match (job_desc_session, candidate_session) {
-            (Some(id), None) => id,
-            (None, Some(id)) => id,
-            (Some(_), Some(_)) => {
-                error!("Both session types provided - only one allowed");
-                let websocket_info = WebsocketInfo {
-                    websocket_url: "".to_string(),
-                    job_description_enrichment_session: None,
-                    candidate_profile_enrichment_session: None,
-                    port: 0,
-                };
-                return RewindResponse::Ok(Json(websocket_info));
+        let filename = script.0.clone().unwrap_or(filename.0);
+        let session_params = SessionParams {
+            job_description_enrichment_session: job_description_enrichment_session.0,
+            candidate_profile_enrichment_session: candidate_profile_enrichment_session.0,
+            session_kind: session_kind.0,
+            session_id: session_id.0,
+        };
+        let chaos = ChaosOptions {
+            seed: chaos_seed.0,
+            drop_rate: chaos_drop_rate.0.unwrap_or_default(),
+            duplicate_rate: chaos_duplicate_rate.0.unwrap_or_default(),
+            reorder_window: chaos_reorder_window.0.unwrap_or_default(),
+            latency_ms: chaos_latency_ms.0.unwrap_or_default(),
+            jitter_ms: chaos_jitter_ms.0.unwrap_or_default(),
+            disconnect_at_record: chaos_disconnect_at_record.0,
+            disconnect_after_secs: chaos_disconnect_after_secs.0,
+        };
+
+        if let Err(e) = chaos.validate() {
+            return RewindResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid chaos options: {}", e),
+                "filename": filename
+            })));
+        }
+
+        let range = match TranscriptRange::parse(
+            start.0.as_deref(),
+            end.0.as_deref(),
+            context.0.unwrap_or_default(),
+        ) {
+            Ok(range) => range,
+            Err(e) => {
+                return RewindResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid range: {}", e),
+                    "filename": filename
+                })));
             }
-            (None, None) => {
-                error!("No session type provided - exactly one required");
-                let websocket_info = WebsocketInfo {
-                    websocket_url: "".to_string(),
-                    job_description_enrichment_session: None,
-                    candidate_profile_enrichment_session: None,
-                    port: 0,
-                };
-                return RewindResponse::Ok(Json(websocket_info));
+        };
+        let speakers = match SpeakerOverrides::parse(
+            &rename_speaker.0,
+            &speaker_role.0,
+            anonymize_speakers.0.unwrap_or_default(),
+        ) {
+            Ok(speakers) => speakers,
+            Err(e) => {
+                return RewindResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid speaker overrides: {}", e),
+                    "filename": filename
+                })));
             }
         };
-        
-        info!("Rewinding transcript: {} with session_id: {}", filename, session_id);
+        let redaction = RedactionOptions::from_params(
+            redact.0,
+            redact_mode.0,
+            redact_name.0,
+            redact_company.0,
+        );
+        if script.0.is_some()
+            && let Err(e) =
+                check_script_options(&range, &speakers, redaction.as_ref(), &chaos, None)
+        {
+            return RewindResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "filename": filename
+            })));
+        }
+
+        let audio = match AudioOptions::from_params(
+            audio.0,
+            audio_source.0,
+            audio_sample_rate.0,
+            audio_frame_ms.0,
+            audio_tone_hz.0,
+        ) {
+            Ok(audio) => audio,
+            Err(e) => {
+                return RewindResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid audio options: {}", e),
+                    "filename": filename
+                })));
+            }
+        };
+        if audio.is_some()
+            && (script.0.is_some() || !interactive_speaker.0.is_empty() || channels.0 || !chaos.is_disabled())
+        {
+            return RewindResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": "Audio cannot be combined with scripts, interactive mode, channels or chaos",
+                "filename": filename
+            })));
+        }
+
+        let session = match session_params.resolve(&self.session_kinds) {
+            Ok(session) => session,
+            Err(e) => {
+                return RewindResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": e.to_string(),
+                    "filename": filename
+                })));
+            }
+        };
+
+        info!("Rewinding transcript: {} with session_id: {}", filename, session.id);
+        let interactive = TurnTakingOptions::from_params(
+            interactive_speaker.0,
+            interactive_timeout_secs.0,
+            echo_replies.0,
+            reply_speaker.0,
+        );
 
         // Load transcript from file
         let transcript_path = format!("transcript/{}", filename);
         let path = StdPath::new(&transcript_path);
 
-        match load_transcript_from_file(path).await {
-            Ok(records) => {
-                // Create WebSocket broadcaster
+        let mut transforms = TranscriptTransforms::new(speakers, redaction);
+        let loaded = match &script.0 {
+            Some(name) => load_script(name).map(|script| (Vec::new(), 0, Some(script))),
+            None => transforms
+                .load(path)
+                .await
+                .and_then(|records| slice(&range, records))
+                .map(|(records, current_index)| (records, current_index, None)),
+        };
+        let loaded = loaded.and_then(|(records, current_index, script)| {
+            let track = audio
+                .clone()
+                .map(|options| AudioTrack::load(&records, options, StdPath::new("transcript")))
+                .transpose()?;
+            Ok((records, current_index, script, track))
+        });
+        match loaded {
+            Ok((records, current_index, script, track)) => {
+                // Store the session until a client connects to its websocket URL
                 let session_uuid = Uuid::new_v4().to_string();
-                let broadcaster = WebSocketBroadcaster {
-                    job_description_enrichment_session: job_desc_session,
-                    candidate_profile_enrichment_session: candidate_session,
-                    sessions: self.sessions.clone(),
-                };
+                let kind = session.kind.clone();
+                let channels = channels.0.then(|| {
+                    let speakers = match &script {
+                        Some(script) => script.speakers(),
+                        None => channel_order(&records),
+                    };
+                    ChannelOptions::new(speakers, channel_wait_secs.0)
+                });
+                let speaker_websocket_urls = channels
+                    .as_ref()
+                    .map(|channels| speaker_websocket_urls(&session_uuid, &channels.speakers));
+                self.sessions.lock().await.insert(
+                    session_uuid.clone(),
+                    RewindSession {
+                        session,
+                        filename: filename.clone(),
+                        records,
+                        current_index,
+                        chaos,
+                        script,
+                        interactive,
+                        channels,
+                        hub: None,
+                        audio: track,
+                    },
+                );
 
-                // Use the broadcaster to setup the session
-                match broadcaster.broadcast(session_id, records).await {
-                    Ok(_) => {
-                        // Update the session with the filename
-                        let mut sessions = self.sessions.lock().await;
-                        if let Some(session) = sessions.get_mut(&session_uuid) {
-                            session.filename = filename.clone();
-                        }
+                // Return websocket information, keyed by the requested session kind
+                let websocket_info = WebsocketInfo {
+                    websocket_url: format!("ws://0.0.0.0:8080/ws/{}", session_uuid),
+                    speaker_websocket_urls,
+                    audio,
+                    port: 8080,
+                    ..WebsocketInfo::unavailable()
+                }
+                .with_session(&SessionContext::new(kind, session_uuid));
+
+                websocket_info.into()
+            }
+            Err(e) => {
+                error!("Error loading transcript {}: {}", filename, e);
+                // Return error as websocket info for now (could be improved)
+                WebsocketInfo::unavailable().into()
+            }
+        }
+    }
+
+    /// Broadcast a transcript via webhook using POST requests
+    #[allow(clippy::too_many_arguments)]
+    #[oai(path = "/webhook-broadcast", method = "get")]
+    async fn handle_webhook_broadcast(
+        &self,
+        #[oai(name = "use_test", default = "default_test")] use_test: poem_openapi::param::Query<
+            bool,
+        >,
+        /// Named target from the webhook targets file, with its payload templates; overrides use_test
+        #[oai(name = "target")] target: poem_openapi::param::Query<Option<String>>,
+        #[oai(name = "filename", default = "default_filename")]
+        filename: poem_openapi::param::Query<String>,
+        /// Branching script in scripts/ to run instead of a transcript; replies choose its branches
+        #[oai(name = "script")] script: poem_openapi::param::Query<Option<String>>,
+        #[oai(name = "job_description_enrichment_session")]
+        job_description_enrichment_session: poem_openapi::param::Query<Option<String>>,
+        #[oai(name = "candidate_profile_enrichment_session")]
+        candidate_profile_enrichment_session: poem_openapi::param::Query<Option<String>>,
+        /// Kind of session given by `session_id`, for kinds without their own parameter
+        #[oai(name = "session_kind")] session_kind: poem_openapi::param::Query<Option<String>>,
+        /// Session ID (numeric or text) of the `session_kind` session
+        #[oai(name = "session_id")] session_id: poem_openapi::param::Query<Option<String>>,
+        /// Speakers: rename a speaker, as `name=new name` (repeatable)
+        #[oai(name = "rename_speaker")] rename_speaker: poem_openapi::param::Query<Vec<String>>,
+        /// Speakers: set a speaker's role, as `name=role` (repeatable)
+        #[oai(name = "speaker_role")] speaker_role: poem_openapi::param::Query<Vec<String>>,
+        /// Speakers: replace names not renamed with `Speaker 1`, `Speaker 2`, ...
+        #[oai(name = "anonymize_speakers")] anonymize_speakers: poem_openapi::param::Query<Option<bool>>,
+        /// Redaction: redact PII (emails, phones, salaries, speaker names) before broadcasting
+        #[oai(name = "redact")] redact: poem_openapi::param::Query<Option<bool>>,
+        /// Redaction: `redact` for tokens such as `[NAME_1]`, `pseudonymize` for stand-in values
+        #[oai(name = "redact_mode")] redact_mode: poem_openapi::param::Query<Option<RedactionMode>>,
+        /// Redaction: further personal name to redact (repeatable)
+        #[oai(name = "redact_name")] redact_name: poem_openapi::param::Query<Vec<String>>,
+        /// Redaction: company name to redact (repeatable)
+        #[oai(name = "redact_company")] redact_company: poem_openapi::param::Query<Vec<String>>,
+        /// Range: first record to play, as HH:MM:SS, MM:SS or a record index
+        #[oai(name = "start")] start: poem_openapi::param::Query<Option<String>>,
+        /// Range: last record to play (inclusive), as HH:MM:SS, MM:SS or a record index
+        #[oai(name = "end")] end: poem_openapi::param::Query<Option<String>>,
+        /// Range: number of records before `start` sent instantly as context
+        #[oai(name = "context")] context: poem_openapi::param::Query<Option<usize>>,
+        /// Chaos: seed for reproducible fault injection
+        #[oai(name = "chaos_seed")] chaos_seed: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: probability (0.0 - 1.0) of dropping a message
+        #[oai(name = "chaos_drop_rate")] chaos_drop_rate: poem_openapi::param::Query<Option<f64>>,
+        /// Chaos: probability (0.0 - 1.0) of duplicating a message
+        #[oai(name = "chaos_duplicate_rate")]
+        chaos_duplicate_rate: poem_openapi::param::Query<Option<f64>>,
+        /// Chaos: shuffle messages within windows of this many records
+        #[oai(name = "chaos_reorder_window")]
+        chaos_reorder_window: poem_openapi::param::Query<Option<usize>>,
+        /// Chaos: fixed latency added before each message, in milliseconds
+        #[oai(name = "chaos_latency_ms")] chaos_latency_ms: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: maximum random jitter added to each message, in milliseconds
+        #[oai(name = "chaos_jitter_ms")] chaos_jitter_ms: poem_openapi::param::Query<Option<u64>>,
+        /// Chaos: force a disconnect before this record index is sent
+        #[oai(name = "chaos_disconnect_at_record")]
+        chaos_disconnect_at_record: poem_openapi::param::Query<Option<usize>>,
+        /// Chaos: force a disconnect after this many seconds
+        #[oai(name = "chaos_disconnect_after_secs")]
+        chaos_disconnect_after_secs: poem_openapi::param::Query<Option<u64>>,
+        /// Expect: accepted response status codes (repeatable)
+        #[oai(name = "expect_status")] expect_status: poem_openapi::param::Query<Vec<u16>>,
+        /// Expect: JSON path in the response body, as `path` or `path=value` (repeatable)
+        #[oai(name = "expect_json")] expect_json: poem_openapi::param::Query<Vec<String>>,
+        /// Expect: maximum response latency, in milliseconds
+        #[oai(name = "expect_max_latency_ms")]
+        expect_max_latency_ms: poem_openapi::param::Query<Option<u64>>,
+        /// Batch: group records into array payloads (window, records, speaker_turn or full)
+        #[oai(name = "batch")] batch: poem_openapi::param::Query<Option<BatchMode>>,
+        /// Batch: window length for `window` batching, in seconds
+        #[oai(name = "batch_window_secs")] batch_window_secs: poem_openapi::param::Query<Option<u64>>,
+        /// Batch: records per request for `records` batching
+        #[oai(name = "batch_size")] batch_size: poem_openapi::param::Query<Option<usize>>,
+    ) -> WebhookBroadcastResponse {
+        let use_test = use_test.0;
+        let filename = script.0.clone().unwrap_or(filename.0);
+        let session_params = SessionParams {
+            job_description_enrichment_session: job_description_enrichment_session.0,
+            candidate_profile_enrichment_session: candidate_profile_enrichment_session.0,
+            session_kind: session_kind.0,
+            session_id: session_id.0,
+        };
+        let chaos = ChaosOptions {
+            seed: chaos_seed.0,
+            drop_rate: chaos_drop_rate.0.unwrap_or_default(),
+            duplicate_rate: chaos_duplicate_rate.0.unwrap_or_default(),
+            reorder_window: chaos_reorder_window.0.unwrap_or_default(),
+            latency_ms: chaos_latency_ms.0.unwrap_or_default(),
+            jitter_ms: chaos_jitter_ms.0.unwrap_or_default(),
+            disconnect_at_record: chaos_disconnect_at_record.0,
+            disconnect_after_secs: chaos_disconnect_after_secs.0,
+        };
+
+        if let Err(e) = chaos.validate() {
+            return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid chaos options: {}", e),
+                "filename": filename
+            })));
+        }
+
+        let range = match TranscriptRange::parse(
+            start.0.as_deref(),
+            end.0.as_deref(),
+            context.0.unwrap_or_default(),
+        ) {
+            Ok(range) => range,
+            Err(e) => {
+                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid range: {}", e),
+                    "filename": filename
+                })));
+            }
+        };
+        let speakers = match SpeakerOverrides::parse(
+            &rename_speaker.0,
+            &speaker_role.0,
+            anonymize_speakers.0.unwrap_or_default(),
+        ) {
+            Ok(speakers) => speakers,
+            Err(e) => {
+                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid speaker overrides: {}", e),
+                    "filename": filename
+                })));
+            }
+        };
+        let redaction = RedactionOptions::from_params(
+            redact.0,
+            redact_mode.0,
+            redact_name.0,
+            redact_company.0,
+        );
+        let batch = BatchOptions {
+            mode: batch.0.unwrap_or_default(),
+            window_secs: batch_window_secs.0.unwrap_or_default(),
+            size: batch_size.0.unwrap_or_default(),
+        };
+        if let Err(e) = batch.validate() {
+            return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid batch options: {}", e),
+                "filename": filename
+            })));
+        }
+        if script.0.is_some()
+            && let Err(e) =
+                check_script_options(&range, &speakers, redaction.as_ref(), &chaos, Some(&batch))
+        {
+            return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "filename": filename
+            })));
+        }
+
+        let expectations = match expect_json
+            .0
+            .iter()
+            .map(|raw| JsonExpectation::parse(raw))
+            .collect::<anyhow::Result<Vec<_>>>()
+        {
+            Ok(json) => WebhookExpectations {
+                status_codes: expect_status.0,
+                json,
+                max_latency_ms: expect_max_latency_ms.0,
+            },
+            Err(e) => {
+                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid expectation: {}", e),
+                    "filename": filename
+                })));
+            }
+        };
+        
+        let session = match session_params.resolve(&self.session_kinds) {
+            Ok(session) => session,
+            Err(e) => {
+                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": e.to_string(),
+                    "filename": filename
+                })));
+            }
+        };
 
-                        // Return websocket information
-                        let websocket_info = WebsocketInfo {
-                            websocket_url: format!("ws://0.0.0.0:8080/ws/{}", session_uuid),
-                            job_description_enrichment_session: if job_desc_session.is_some() { Some(session_uuid.clone()) } else { None },
-                            candidate_profile_enrichment_session: if candidate_session.is_some() { Some(session_uuid.clone()) } else { None },
-                            port: 8080,
-                        };
+        // Determine the webhook URL and payload templates to use
+        let (webhook_url, templates, environment) = match &target.0 {
+            Some(name) => match self.webhook_targets.get(name) {
+                Ok(target) => (target.url.clone(), target.templates.clone(), "target"),
+                Err(e) => {
+                    return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                        "status": "error",
+                        "message": e.to_string(),
+                        "filename": filename
+                    })));
+                }
+            },
+            None if use_test => (WEBHOOK_URL_TEST.to_string(), WebhookTemplates::default(), "test"),
+            None => (WEBHOOK_URL_PROD.to_string(), WebhookTemplates::default(), "production"),
+        };
+        info!(
+            "Starting webhook broadcast to {} environment: {} for file: {} with session_id: {}",
+            environment, webhook_url, filename, session.id
+        );
+
+        // Load transcript from file
+        let transcript_path = format!("transcript/{}", filename);
+        let path = StdPath::new(&transcript_path);
+
+        let mut transforms = TranscriptTransforms::new(speakers, redaction);
+        let loaded = match &script.0 {
+            Some(name) => load_script(name).map(|script| (Vec::new(), Some(script))),
+            None => transforms
+                .load(path)
+                .await
+                .and_then(|records| slice(&range, records))
+                .map(|(records, _)| (records, None)),
+        };
+        match loaded {
+            Ok((records, script)) => {
+                // Register a report collecting every webhook response
+                let report_id = Uuid::new_v4().to_string();
+                insert_report(
+                    &self.reports,
+                    WebhookReport::new(
+                        report_id.clone(),
+                        filename.clone(),
+                        webhook_url.clone(),
+                        expectations,
+                    ),
+                )
+                .await;
 
-                        RewindResponse::Ok(Json(websocket_info))
+                // Create WebHook broadcaster on the queue's shared client
+                let (replies, reply_receiver) = tokio::sync::mpsc::unbounded_channel();
+                let mut broadcaster = WebhookBroadcaster::new(webhook_url.clone(), self.webhook_queue.client());
+                if script.is_some() {
+                    // Response bodies choose the script's branches
+                    broadcaster = broadcaster.with_replies(replies);
+                }
+                let broadcaster = Arc::new(
+                    broadcaster
+                        .with_templates(templates)
+                        .with_batch(batch)
+                        .with_report(ReportHandle {
+                            store: self.reports.clone(),
+                            report_id: report_id.clone(),
+                        })
+                        .with_dead_letters(DeadLetterHandle {
+                            store: self.dead_letters.clone(),
+                            target: target.0.clone(),
+                        }),
+                );
+                let playback: JobPlayback = match script {
+                    Some(script) => ScriptPlayback::new(script, reply_receiver, TokioClock::shared()).into(),
+                    None => {
+                        let playback = Playback::new(records, chaos, TokioClock::shared());
+                        if let Some(seed) = playback.chaos_seed() {
+                            info!("Chaos enabled for webhook broadcast {} (seed {})", report_id, seed);
+                        }
+                        playback.into()
                     }
+                };
+
+                // Queue the broadcast; it starts once a global and a per-target slot are free
+                let job = WebhookJob {
+                    target: target.0.clone().unwrap_or_else(|| webhook_url.clone()),
+                    filename: filename.clone(),
+                    report_id: Some(report_id.clone()),
+                    broadcaster,
+                    session,
+                    playback,
+                };
+                let job = match self.webhook_queue.submit(job).await {
+                    Ok(job) => job,
                     Err(e) => {
-                        error!("Error setting up websocket broadcast: {}", e);
-                        let websocket_info = WebsocketInfo {
-                            websocket_url: "".to_string(),
-                            job_description_enrichment_session: None,
-                            candidate_profile_enrichment_session: None,
-                            port: 0,
-                        };
-                        RewindResponse::Ok(Json(websocket_info))
+                        self.reports.lock().await.remove(&report_id);
+                        return WebhookBroadcastResponse::TooManyRequests(Json(serde_json::json!({
+                            "status": "error",
+                            "message": e.to_string(),
+                            "filename": filename
+                        })));
                     }
-                }
+                };
+                info!("Queued webhook broadcast job {}", job.job_id);
+
+                WebhookBroadcastResponse::Ok(Json(serde_json::json!({
+                    "status": "success",
+                    "message": "Webhook broadcast queued",
+                    "filename": filename,
+                    "webhook_url": webhook_url,
+                    "environment": environment,
+                    "target": target.0,
+                    "job_id": job.job_id,
+                    "job_state": job.state,
+                    "job_url": format!("/api/webhook-jobs/{}", job.job_id),
+                    "report_id": report_id,
+                    "report_url": format!("/api/reports/{}", report_id)
+                })))
             }
             Err(e) => {
                 error!("Error loading transcript {}: {}", filename, e);
-                // Return error as websocket info for now (could be improved)
-                let websocket_info = WebsocketInfo {
-                    websocket_url: "".to_string(),
-                    job_description_enrichment_session: None,
-                    candidate_profile_enrichment_session: None,
-                    port: 0,
-                };
-                RewindResponse::Ok(Json(websocket_info))
+                WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Failed to load transcript: {}", e),
+                    "filename": filename
+                })))
+            }
+        }
+    }
+
+    /// Broadcast one transcript to several sinks on a single shared timeline
+    #[oai(path = "/broadcasts", method = "post")]
+    async fn create_broadcast(
+        &self,
+        request: Json<CompositeBroadcastRequest>,
+    ) -> CompositeBroadcastResponse {
+        let request = request.0;
+        let filename = request.describe_transcript();
+
+        match self.launcher.start(request).await {
+            Ok(started) => CompositeBroadcastResponse::Ok(Json(serde_json::json!({
+                "status": "success",
+                "message": "Composite broadcast started",
+                "broadcast_id": started.broadcast_id,
+                "filename": filename,
+                "sinks": started.sinks,
+                "websocket_url": started
+                    .has_subscribers
+                    .then(|| format!("ws://0.0.0.0:8080/ws/broadcasts/{}", started.broadcast_id)),
+                "status_url": format!("/api/broadcasts/{}", started.broadcast_id)
+            }))),
+            Err(message) => CompositeBroadcastResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": message,
+                "filename": filename
+            }))),
+        }
+    }
+
+    /// Schedule a composite broadcast at a given time or on a cron expression
+    #[oai(path = "/schedules", method = "post")]
+    async fn create_schedule(&self, request: Json<ScheduleRequest>) -> ScheduleResponse {
+        let request = request.0;
+        let bad_request = |message: String| {
+            ScheduleResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })))
+        };
+
+        if let Err(message) = self.launcher.validate(&request.broadcast) {
+            return bad_request(message);
+        }
+        for filename in request.broadcast.transcript_filenames() {
+            if !StdPath::new("transcript").join(filename).exists() {
+                return bad_request(format!("Transcript not found: {}", filename));
+            }
+        }
+        let schedule = match Schedule::new(request, chrono::Utc::now()) {
+            Ok(schedule) => schedule,
+            Err(e) => return bad_request(e.to_string()),
+        };
+
+        info!(
+            "Created schedule {} for {}, next run at {}",
+            schedule.schedule_id,
+            schedule.broadcast.describe_transcript(),
+            schedule.next_run.as_deref().unwrap_or("never")
+        );
+        self.schedules.add(schedule.clone()).await;
+        ScheduleResponse::Ok(Json(Box::new(schedule)))
+    }
+
+    /// List scheduled broadcasts
+    #[oai(path = "/schedules", method = "get")]
+    async fn list_schedules(&self) -> ScheduleListResponse {
+        ScheduleListResponse::Ok(Json(self.schedules.list().await))
+    }
+
+    /// Get a scheduled broadcast
+    #[oai(path = "/schedules/:schedule_id", method = "get")]
+    async fn get_schedule(&self, schedule_id: poem_openapi::param::Path<String>) -> ScheduleResponse {
+        match self.schedules.get(&schedule_id.0).await {
+            Some(schedule) => ScheduleResponse::Ok(Json(Box::new(schedule))),
+            None => schedule_not_found(schedule_id.0),
+        }
+    }
+
+    /// Pause a schedule; it keeps its settings but stops firing
+    #[oai(path = "/schedules/:schedule_id/pause", method = "post")]
+    async fn pause_schedule(&self, schedule_id: poem_openapi::param::Path<String>) -> ScheduleResponse {
+        self.set_schedule_paused(schedule_id.0, true).await
+    }
+
+    /// Resume a paused schedule from its next future run
+    #[oai(path = "/schedules/:schedule_id/resume", method = "post")]
+    async fn resume_schedule(&self, schedule_id: poem_openapi::param::Path<String>) -> ScheduleResponse {
+        self.set_schedule_paused(schedule_id.0, false).await
+    }
+
+    /// Delete a schedule
+    #[oai(path = "/schedules/:schedule_id", method = "delete")]
+    async fn delete_schedule(&self, schedule_id: poem_openapi::param::Path<String>) -> ScheduleDeleteResponse {
+        let schedule_id = schedule_id.0;
+
+        if self.schedules.delete(&schedule_id).await {
+            info!("Deleted schedule {}", schedule_id);
+            ScheduleDeleteResponse::Ok(Json(serde_json::json!({
+                "status": "success",
+                "message": "Schedule deleted",
+                "schedule_id": schedule_id
+            })))
+        } else {
+            ScheduleDeleteResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Schedule not found",
+                "schedule_id": schedule_id
+            })))
+        }
+    }
+
+    /// Get the per-sink status of a composite broadcast
+    #[oai(path = "/broadcasts/:broadcast_id", method = "get")]
+    async fn get_broadcast(
+        &self,
+        broadcast_id: poem_openapi::param::Path<String>,
+    ) -> BroadcastStatusResponse {
+        let broadcast_id = broadcast_id.0;
+
+        match self.broadcasts.lock().await.get(&broadcast_id) {
+            Some(entry) => BroadcastStatusResponse::Ok(Json(entry.status.clone())),
+            None => BroadcastStatusResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Broadcast not found",
+                "broadcast_id": broadcast_id
+            }))),
+        }
+    }
+
+    /// Stop a pending or running composite broadcast
+    #[oai(path = "/broadcasts/:broadcast_id/cancel", method = "post")]
+    async fn cancel_broadcast(
+        &self,
+        broadcast_id: poem_openapi::param::Path<String>,
+    ) -> BroadcastStatusResponse {
+        let broadcast_id = broadcast_id.0;
+
+        match self.broadcasts.lock().await.get(&broadcast_id) {
+            Some(entry) => {
+                entry.cancel.cancel();
+                BroadcastStatusResponse::Ok(Json(entry.status.clone()))
+            }
+            None => BroadcastStatusResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Broadcast not found",
+                "broadcast_id": broadcast_id
+            }))),
+        }
+    }
+
+    /// List queued, running and finished webhook broadcast jobs
+    #[oai(path = "/webhook-jobs", method = "get")]
+    async fn list_webhook_jobs(&self) -> WebhookJobListResponse {
+        WebhookJobListResponse::Ok(Json(self.webhook_queue.list().await))
+    }
+
+    /// Get the state of a webhook broadcast job
+    #[oai(path = "/webhook-jobs/:job_id", method = "get")]
+    async fn get_webhook_job(&self, job_id: poem_openapi::param::Path<String>) -> WebhookJobResponse {
+        let job_id = job_id.0;
+
+        match self.webhook_queue.get(&job_id).await {
+            Some(job) => WebhookJobResponse::Ok(Json(job)),
+            None => WebhookJobResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Job not found",
+                "job_id": job_id
+            }))),
+        }
+    }
+
+    /// Cancel a queued or running webhook broadcast job
+    #[oai(path = "/webhook-jobs/:job_id/cancel", method = "post")]
+    async fn cancel_webhook_job(&self, job_id: poem_openapi::param::Path<String>) -> WebhookJobResponse {
+        let job_id = job_id.0;
+
+        match self.webhook_queue.cancel(&job_id).await {
+            Some(job) => WebhookJobResponse::Ok(Json(job)),
+            None => WebhookJobResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Job not found",
+                "job_id": job_id
+            }))),
+        }
+    }
+
+    /// List webhook messages that could not be delivered
+    #[oai(path = "/dead-letters", method = "get")]
+    async fn list_dead_letters(&self) -> DeadLetterListResponse {
+        DeadLetterListResponse::Ok(Json(self.dead_letters.list().await))
+    }
+
+    /// Redeliver selected or all dead letters to their original or another webhook
+    #[oai(path = "/dead-letters/redeliver", method = "post")]
+    async fn redeliver_dead_letters(&self, request: Json<RedeliverRequest>) -> RedeliverResponse {
+        let request = request.0;
+        let bad_request = |message: String| {
+            RedeliverResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })))
+        };
+
+        let destination = match (request.target, request.url) {
+            (Some(_), Some(_)) => return bad_request("Give either target or url, not both".to_string()),
+            (Some(name), None) => match self.webhook_targets.get(&name) {
+                Ok(target) => Some(Destination {
+                    url: target.url.clone(),
+                    target: Some(target.name.clone()),
+                }),
+                Err(e) => return bad_request(e.to_string()),
+            },
+            (None, Some(url)) => match reqwest::Url::parse(&url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                    Some(Destination { url, target: None })
+                }
+                _ => return bad_request(format!("Invalid webhook url: {}", url)),
+            },
+            (None, None) => None,
+        };
+
+        match self
+            .dead_letters
+            .redeliver(&self.webhook_queue.client(), &request.ids, destination.as_ref())
+            .await
+        {
+            Ok(results) => {
+                let delivered = results.iter().filter(|r| r.delivered).count();
+                info!("Redelivered {} of {} dead letter(s)", delivered, results.len());
+                RedeliverResponse::Ok(Json(serde_json::json!({
+                    "status": "success",
+                    "delivered": delivered,
+                    "failed": results.len() - delivered,
+                    "remaining": self.dead_letters.len().await,
+                    "results": results
+                })))
             }
+            Err(e) => RedeliverResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string()
+            }))),
         }
     }
 
-    /// Broadcast a transcript via webhook using POST requests
-    #[oai(path = "/webhook-broadcast", method = "get")]
-    async fn handle_webhook_broadcast(
+    /// Get the response report of a webhook broadcast
+    #[oai(path = "/reports/:report_id", method = "get")]
+    async fn get_report(&self, report_id: poem_openapi::param::Path<String>) -> WebhookReportResponse {
+        let report_id = report_id.0;
+
+        match self.reports.lock().await.get(&report_id) {
+            Some(report) => WebhookReportResponse::Ok(Json(Box::new(report.clone()))),
+            None => WebhookReportResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Report not found",
+                "report_id": report_id
+            }))),
+        }
+    }
+
+    /// Start capturing live transcript events into a new transcript file
+    #[oai(path = "/recordings", method = "post")]
+    async fn start_recording(
         &self,
-        #[oai(name = "use_test", default = "default_test")] use_test: poem_openapi::param::Query<
-            bool,
-        >,
-        #[oai(name = "filename", default = "default_filename")]
-        filename: poem_openapi::param::Query<String>,
-        #[oai(name = "job_description_enrichment_session")]
-        job_description_enrichment_session: poem_openapi::param::Query<Option<i32>>,
-        #[oai(name = "candidate_profile_enrichment_session")]
-        candidate_profile_enrichment_session: poem_openapi::param::Query<Option<i32>>,
-    ) -> WebhookBroadcastResponse {
-        let use_test = use_test.0;
+        #[oai(name = "filename")] filename: poem_openapi::param::Query<String>,
+    ) -> RecordingResponse {
         let filename = filename.0;
-        let job_desc_session = job_description_enrichment_session.0;
-        let candidate_session = candidate_profile_enrichment_session.0;
-        
-        // Validate that exactly one session type is provided
-        let session_id = match (job_desc_session, candidate_session) {
-            (Some(id), None) => id,
-            (None, Some(id)) => id,
-            (Some(_), Some(_)) => {
-                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
-                    "status": "error",
-                    "message": "Both session types provided - only one allowed",
-                    "filename": filename
-                })));
-            }
-            (None, None) => {
-                return WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
-                    "status": "error",
-                    "message": "No session type provided - exactly one required",
-                    "filename": filename
-                })));
-            }
-        };
 
-        // Determine the webhook URL to use
-        let webhook_url = if use_test {
-            WEBHOOK_URL_TEST.to_string()
-        } else {
-            WEBHOOK_URL_PROD.to_string()
-        };
-
-        let environment = if use_test { "test" } else { "production" };
-        info!(
-            "Starting webhook broadcast to {} environment: {} for file: {} with session_id: {}",
-            environment, webhook_url, filename, session_id
-        );
+        if let Err(e) = validate_filename(&filename) {
+            return RecordingResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "filename": filename
+            })));
+        }
+        if StdPath::new("transcript").join(&filename).exists() {
+            return RecordingResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": "Transcript already exists",
+                "filename": filename
+            })));
+        }
 
-        // Load transcript from file
-        let transcript_path = format!("transcript/{}", filename);
-        let path = StdPath::new(&transcript_path);
+        let recording_id = Uuid::new_v4().to_string();
+        let mut recordings = self.recordings.lock().await;
+        recordings.insert(recording_id.clone(), RecordingSession::new(filename.clone()));
+        info!("Started recording {} into {}", recording_id, filename);
 
-        match load_transcript_from_file(path).await {
-            Ok(records) => {
-                // Create WebHook broadcaster
-                let broadcaster = WebhookBroadcaster {
-                    webhook_url: webhook_url.clone(),
-                };
+        RecordingResponse::Ok(Json(RecordingInfo {
+            websocket_url: format!("ws://0.0.0.0:8080/record/{}", recording_id),
+            webhook_url: format!(
+                "http://0.0.0.0:8080/api/recordings/{}/events",
+                recording_id
+            ),
+            recording_id,
+            filename,
+        }))
+    }
 
-                // Start broadcasting in background
-                tokio::spawn(async move {
-                    if let Err(e) = broadcaster.broadcast(session_id, records).await {
-                        error!("Webhook broadcast failed: {}", e);
-                    }
-                });
+    /// Capture a single live event into a recording
+    #[oai(path = "/recordings/:recording_id/events", method = "post")]
+    async fn capture_recording_event(
+        &self,
+        recording_id: poem_openapi::param::Path<String>,
+        event: Json<serde_json::Value>,
+    ) -> RecordingActionResponse {
+        let recording_id = recording_id.0;
 
-                WebhookBroadcastResponse::Ok(Json(serde_json::json!({
-                    "status": "success",
-                    "message": "Webhook broadcast started",
-                    "filename": filename,
-                    "webhook_url": webhook_url,
-                    "environment": environment
-                })))
-            }
+        let event: CapturedEvent = match serde_json::from_value(event.0) {
+            Ok(event) => event,
             Err(e) => {
-                error!("Error loading transcript {}: {}", filename, e);
-                WebhookBroadcastResponse::BadRequest(Json(serde_json::json!({
+                return RecordingActionResponse::BadRequest(Json(serde_json::json!({
                     "status": "error",
-                    "message": format!("Failed to load transcript: {}", e),
-                    "filename": filename
-                })))
+                    "message": format!("Unrecognised event: {}", e),
+                    "recording_id": recording_id
+                })));
             }
+        };
+
+        match apply_captured_event(&self.recordings, &recording_id, event).await {
+            Ok(Some(response)) => RecordingActionResponse::Ok(Json(response)),
+            Ok(None) => RecordingActionResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Recording not found",
+                "recording_id": recording_id
+            }))),
+            Err(e) => RecordingActionResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "recording_id": recording_id
+            }))),
+        }
+    }
+
+    /// Stop a recording and save it into the transcript catalog
+    #[oai(path = "/recordings/:recording_id/finish", method = "post")]
+    async fn finish_recording(
+        &self,
+        recording_id: poem_openapi::param::Path<String>,
+    ) -> RecordingActionResponse {
+        let recording_id = recording_id.0;
+
+        match save_recording(&self.recordings, &recording_id).await {
+            Ok(Some(response)) => RecordingActionResponse::Ok(Json(response)),
+            Ok(None) => RecordingActionResponse::NotFound(Json(serde_json::json!({
+                "status": "error",
+                "message": "Recording not found",
+                "recording_id": recording_id
+            }))),
+            Err(e) => RecordingActionResponse::BadRequest(Json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string(),
+                "recording_id": recording_id
+            }))),
         }
     }
 }
@@ -279,23 +1859,609 @@ fn create_log_filter() -> Result<EnvFilter, tracing_subscriber::filter::ParseErr
     Ok(filter)
 }
 
+#[derive(Parser)]
+#[command(name = "restream", version, about = "Play transcripts over WebSocket, webhook or stdout")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Start the HTTP and WebSocket server (default)
+    Serve,
+    /// Replay a transcript without running the server
+    Play {
+        /// Transcript files (.csv or .json), played back to back as one session
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Target URL: ws:// or wss:// for a WebSocket server, http:// or https:// for a webhook
+        #[arg(
+            long,
+            required_unless_present_any = ["stdout", "target"],
+            conflicts_with_all = ["stdout", "target"]
+        )]
+        to: Option<String>,
+        /// Named webhook target from webhook_targets.json, sent with its payload templates
+        #[arg(long, conflicts_with = "stdout")]
+        target: Option<String>,
+        /// Print messages as JSON lines on stdout instead of sending them
+        #[arg(long)]
+        stdout: bool,
+        #[command(flatten)]
+        session: SessionArgs,
+        #[command(flatten)]
+        chaos: ChaosArgs,
+        #[command(flatten)]
+        batch: BatchArgs,
+        #[command(flatten)]
+        playlist: PlaylistArgs,
+        #[command(flatten)]
+        speakers: SpeakerArgs,
+        #[command(flatten)]
+        redaction: RedactionArgs,
+    },
+    /// Check that a transcript parses and its timestamps are valid
+    Validate {
+        /// Transcript file (.csv or .json)
+        file: PathBuf,
+    },
+    /// Generate synthetic transcripts from a template
+    Generate {
+        /// Generator template (.json)
+        template: PathBuf,
+        /// Transcripts to generate
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Seed of the first transcript; overrides the template's seed
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Directory to write the transcripts to
+        #[arg(long, default_value = "transcript")]
+        out_dir: PathBuf,
+        /// Filename prefix; defaults to the template's name
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Write .json transcripts instead of .csv
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert a transcript between CSV and JSON, based on file extensions
+    Convert {
+        /// Input transcript file
+        input: PathBuf,
+        /// Output transcript file; must not exist
+        output: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct SessionArgs {
+    /// Job description enrichment session ID
+    #[arg(long)]
+    job_description_enrichment_session: Option<String>,
+    /// Candidate profile enrichment session ID
+    #[arg(long)]
+    candidate_profile_enrichment_session: Option<String>,
+    /// Session kind for --session-id, e.g. one listed in RESTREAM_SESSION_KINDS
+    #[arg(long, requires = "session_id")]
+    session_kind: Option<String>,
+    /// Session ID (numeric or text) of the --session-kind session
+    #[arg(long, requires = "session_kind")]
+    session_id: Option<String>,
+}
+
+impl From<SessionArgs> for SessionParams {
+    fn from(args: SessionArgs) -> Self {
+        SessionParams {
+            job_description_enrichment_session: args.job_description_enrichment_session,
+            candidate_profile_enrichment_session: args.candidate_profile_enrichment_session,
+            session_kind: args.session_kind,
+            session_id: args.session_id,
+        }
+    }
+}
+
+#[derive(Args)]
+struct BatchArgs {
+    /// Group webhook records into array payloads: window, records, speaker_turn or full
+    #[arg(long, value_parser = parse_batch_mode)]
+    batch: Option<BatchMode>,
+    /// Window length for --batch window, in seconds
+    #[arg(long, default_value_t = 0)]
+    batch_window_secs: u64,
+    /// Records per request for --batch records
+    #[arg(long, default_value_t = 0)]
+    batch_size: usize,
+}
+
+fn parse_batch_mode(raw: &str) -> Result<BatchMode, String> {
+    serde_json::from_value(serde_json::Value::String(raw.to_string()))
+        .map_err(|_| "expected none, window, records, speaker_turn or full".to_string())
+}
+
+impl From<BatchArgs> for BatchOptions {
+    fn from(args: BatchArgs) -> Self {
+        BatchOptions {
+            mode: args.batch.unwrap_or_default(),
+            window_secs: args.batch_window_secs,
+            size: args.batch_size,
+        }
+    }
+}
+
+#[derive(Args)]
+struct SpeakerArgs {
+    /// Rename a speaker, as `name=new name` (repeatable)
+    #[arg(long)]
+    rename_speaker: Vec<String>,
+    /// Set a speaker's role, as `name=role` (repeatable)
+    #[arg(long)]
+    speaker_role: Vec<String>,
+    /// Replace speaker names not renamed with `Speaker 1`, `Speaker 2`, ...
+    #[arg(long)]
+    anonymize_speakers: bool,
+}
+
+impl SpeakerArgs {
+    fn into_overrides(self) -> anyhow::Result<SpeakerOverrides> {
+        SpeakerOverrides::parse(
+            &self.rename_speaker,
+            &self.speaker_role,
+            self.anonymize_speakers,
+        )
+    }
+}
+
+#[derive(Args)]
+struct RedactionArgs {
+    /// Redact emails, phone numbers, salaries and speaker names before sending
+    #[arg(long)]
+    redact: bool,
+    /// Replace PII with stand-in values instead of tokens such as `[NAME_1]` (implies --redact)
+    #[arg(long)]
+    pseudonymize: bool,
+    /// Further personal name to redact (repeatable; implies --redact)
+    #[arg(long)]
+    redact_name: Vec<String>,
+    /// Company name to redact (repeatable; implies --redact)
+    #[arg(long)]
+    redact_company: Vec<String>,
+}
+
+impl From<RedactionArgs> for Option<RedactionOptions> {
+    fn from(args: RedactionArgs) -> Self {
+        RedactionOptions::from_params(
+            args.redact.then_some(true),
+            args.pseudonymize.then_some(RedactionMode::Pseudonymize),
+            args.redact_name,
+            args.redact_company,
+        )
+    }
+}
+
+#[derive(Args)]
+struct PlaylistArgs {
+    /// Pause between transcripts, in seconds
+    #[arg(long, default_value_t = 0)]
+    gap_secs: u64,
+    /// Passes through the transcripts; 0 loops until Ctrl-C
+    #[arg(long, default_value_t = 1)]
+    repeat: u32,
+    /// Play the transcripts in a random order, reshuffled on every pass
+    #[arg(long)]
+    shuffle: bool,
+    /// Seed for reproducible shuffling
+    #[arg(long, requires = "shuffle")]
+    shuffle_seed: Option<u64>,
+}
+
+impl PlaylistArgs {
+    fn into_options(self, files: &[PathBuf]) -> PlaylistOptions {
+        PlaylistOptions {
+            items: files
+                .iter()
+                .map(|file| PlaylistItem::new(file.to_string_lossy()))
+                .collect(),
+            gap_secs: self.gap_secs,
+            repeat: self.repeat,
+            shuffle: self.shuffle,
+            seed: self.shuffle_seed,
+        }
+    }
+}
+
+#[derive(Args)]
+struct ChaosArgs {
+    /// Seed for reproducible fault injection
+    #[arg(long)]
+    chaos_seed: Option<u64>,
+    /// Probability (0.0 - 1.0) of dropping a message
+    #[arg(long, default_value_t = 0.0)]
+    chaos_drop_rate: f64,
+    /// Probability (0.0 - 1.0) of duplicating a message
+    #[arg(long, default_value_t = 0.0)]
+    chaos_duplicate_rate: f64,
+    /// Shuffle messages within windows of this many records
+    #[arg(long, default_value_t = 0)]
+    chaos_reorder_window: usize,
+    /// Fixed latency added before each message, in milliseconds
+    #[arg(long, default_value_t = 0)]
+    chaos_latency_ms: u64,
+    /// Maximum random jitter added to each message, in milliseconds
+    #[arg(long, default_value_t = 0)]
+    chaos_jitter_ms: u64,
+    /// Force a disconnect before this record index is sent
+    #[arg(long)]
+    chaos_disconnect_at_record: Option<usize>,
+    /// Force a disconnect after this many seconds
+    #[arg(long)]
+    chaos_disconnect_after_secs: Option<u64>,
+}
+
+impl From<ChaosArgs> for ChaosOptions {
+    fn from(args: ChaosArgs) -> Self {
+        ChaosOptions {
+            seed: args.chaos_seed,
+            drop_rate: args.chaos_drop_rate,
+            duplicate_rate: args.chaos_duplicate_rate,
+            reorder_window: args.chaos_reorder_window,
+            latency_ms: args.chaos_latency_ms,
+            jitter_ms: args.chaos_jitter_ms,
+            disconnect_at_record: args.chaos_disconnect_at_record,
+            disconnect_after_secs: args.chaos_disconnect_after_secs,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), std::io::Error> {
+    let cli = Cli::parse();
+
     let filter = create_log_filter().unwrap_or_else(|err| {
         eprintln!("Failed to parse tracing directives {err}. Falling back to 'info'.",);
         EnvFilter::new("info")
     });
 
-    tracing_subscriber::fmt()
+    let command = cli.command.unwrap_or(Command::Serve);
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(filter)
-        .with_ansi(true)
-        .init();
+        .with_ansi(true);
+    if let Command::Serve = command {
+        subscriber.init();
+    } else {
+        // Keep stdout free for `play --stdout` output
+        subscriber.with_writer(std::io::stderr).init();
+    }
+
+    let result = match command {
+        Command::Serve => return serve().await,
+        Command::Play {
+            files,
+            to,
+            target,
+            stdout,
+            session,
+            chaos,
+            batch,
+            playlist,
+            speakers,
+            redaction,
+        } => match speakers.into_overrides() {
+            Ok(speakers) => {
+                play(
+                    playlist.into_options(&files),
+                    TranscriptTransforms::new(speakers, redaction.into()),
+                    if stdout { None } else { to },
+                    target,
+                    session.into(),
+                    chaos.into(),
+                    batch.into(),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        },
+        Command::Validate { file } => validate(&file).await,
+        Command::Generate {
+            template,
+            count,
+            seed,
+            out_dir,
+            prefix,
+            json,
+        } => generate(&template, count, seed, &out_dir, prefix, json),
+        Command::Convert { input, output } => convert(&input, &output).await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+async fn play(
+    playlist: PlaylistOptions,
+    mut transforms: TranscriptTransforms,
+    to: Option<String>,
+    target: Option<String>,
+    session: SessionParams,
+    chaos: ChaosOptions,
+    batch: BatchOptions,
+) -> anyhow::Result<()> {
+    chaos.validate()?;
+    batch.validate()?;
+
+    let session = session.resolve(&SessionKinds::from_env()?)?;
+
+    // A single file played once keeps its original timestamps
+    let source = match playlist.items.as_slice() {
+        [item] if playlist == PlaylistOptions::from_filenames([item.filename.clone()]) => {
+            let records = transforms.load(StdPath::new(&item.filename)).await?;
+            info!("Playing {} ({} records)", item.filename, records.len());
+            BroadcastSource::Transcript(records)
+        }
+        _ => {
+            let loaded = Playlist::load_with(&playlist, StdPath::new(""), &mut transforms).await?;
+            info!(
+                "Playing {} ({} records per pass)",
+                playlist.describe(),
+                loaded.records_per_pass()
+            );
+            BroadcastSource::Playlist(loaded)
+        }
+    };
+
+    let is_webhook = target.is_some()
+        || to
+            .as_deref()
+            .is_some_and(|url| url.starts_with("http://") || url.starts_with("https://"));
+    if batch.is_enabled() && !is_webhook {
+        return Err(anyhow::anyhow!("--batch only applies to webhook targets"));
+    }
+
+    let broadcaster: Arc<dyn Broadcaster> = match to {
+        None if target.is_some() => {
+            let targets = WebhookTargets::load(StdPath::new(WEBHOOK_TARGETS_FILE))?;
+            let target = targets.get(target.as_deref().unwrap_or_default())?;
+            Arc::new(
+                WebhookBroadcaster::new(target.url.clone(), reqwest::Client::new())
+                    .with_templates(target.templates.clone())
+                    .with_batch(batch),
+            )
+        }
+        None => Arc::new(StdoutBroadcaster),
+        Some(url) if url.starts_with("ws://") || url.starts_with("wss://") => {
+            Arc::new(WebSocketClientBroadcaster::new(url))
+        }
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+            Arc::new(WebhookBroadcaster::new(url, reqwest::Client::new()).with_batch(batch))
+        }
+        Some(url) => {
+            return Err(anyhow::anyhow!(
+                "Unsupported target {}: expected ws://, wss://, http:// or https://",
+                url
+            ));
+        }
+    };
+
+    let events = match source {
+        BroadcastSource::Transcript(records) => {
+            let playback = Playback::new(records, chaos, TokioClock::shared());
+            if let Some(seed) = playback.chaos_seed() {
+                eprintln!("Chaos enabled for playback (seed {})", seed);
+            }
+            playback.into_stream()
+        }
+        BroadcastSource::Playlist(playlist) => {
+            if let Some(seed) = playlist.shuffle_seed() {
+                eprintln!("Shuffling playlist (seed {})", seed);
+            }
+            playlist.into_stream(chaos, TokioClock::shared())
+        }
+    };
+
+    // Stop cleanly on Ctrl-C
+    let cancel = CancellationToken::new();
+    let ctrl_c = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            ctrl_c.cancel();
+        }
+    });
+
+    broadcaster
+        .broadcast(session, events, cancel)
+        .report()
+        .await
+        .into_result()
+        .map(|_| ())
+}
+
+async fn validate(file: &StdPath) -> anyhow::Result<()> {
+    let report = lint_file(file, &LintOptions::default())?;
+    for issue in &report.issues {
+        let location = match issue.row {
+            Some(row) => format!("row {}", row),
+            None => "file".to_string(),
+        };
+        println!(
+            "{}: {}: {} ({})",
+            location,
+            issue.severity.as_str(),
+            issue.message,
+            issue.code
+        );
+    }
+
+    if !report.is_valid() {
+        return Err(anyhow::anyhow!(
+            "{} error(s), {} warning(s) in {}",
+            report.errors,
+            report.warnings,
+            file.display()
+        ));
+    }
+
+    println!(
+        "{}: {} records OK, {} warning(s)",
+        file.display(),
+        report.records,
+        report.warnings
+    );
+    Ok(())
+}
+
+fn generate(
+    template_path: &StdPath,
+    count: usize,
+    seed: Option<u64>,
+    out_dir: &StdPath,
+    prefix: Option<String>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let template = GeneratorTemplate::load(template_path)?;
+    let prefix = match prefix {
+        Some(prefix) => prefix,
+        None => template_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|stem| format!("{}_synthetic", stem))
+            .ok_or_else(|| anyhow::anyhow!("Give --prefix for {}", template_path.display()))?,
+    };
+
+    let (seed, transcripts) = generate_transcripts(&template, count, seed)?;
+    std::fs::create_dir_all(out_dir)?;
+    let paths = save_transcripts(out_dir, &prefix, &transcripts, json)?;
+    for (path, transcript) in paths.iter().zip(&transcripts) {
+        println!(
+            "{} ({} records, seed {})",
+            path.display(),
+            transcript.records.len(),
+            transcript.seed
+        );
+    }
+    println!("Generated {} transcript(s) with seed {}", paths.len(), seed);
+    Ok(())
+}
 
+async fn convert(input: &StdPath, output: &StdPath) -> anyhow::Result<()> {
+    let records = load_transcript_from_file(input).await?;
+    write_transcript_to_file(output, &records)?;
+
+    println!(
+        "Converted {} records from {} to {}",
+        records.len(),
+        input.display(),
+        output.display()
+    );
+    Ok(())
+}
+
+async fn serve() -> Result<(), std::io::Error> {
     info!("Starting restream OpenAPI Server...");
 
     let sessions: SessionStore = Arc::new(Mutex::new(HashMap::new()));
+    let recordings: RecordingStore = Arc::new(Mutex::new(HashMap::new()));
+    let reports: ReportStore = Arc::new(Mutex::new(HashMap::new()));
+    let broadcasts: BroadcastStore = Arc::new(Mutex::new(HashMap::new()));
+    let session_kinds = SessionKinds::from_env().map_err(|e| std::io::Error::other(e.to_string()))?;
+    // Invalid templates stop the server here rather than failing mid-broadcast
+    let webhook_targets = WebhookTargets::load(StdPath::new(WEBHOOK_TARGETS_FILE)).map_err(|e| {
+        error!("Failed to load webhook targets: {}", e);
+        std::io::Error::other(e.to_string())
+    })?;
+    if !webhook_targets.is_empty() {
+        info!("Loaded {} webhook target(s) from {}", webhook_targets.len(), WEBHOOK_TARGETS_FILE);
+    }
+    let dead_letters = DeadLetterStore::load(StdPath::new(DEAD_LETTERS_FILE)).map_err(|e| {
+        error!("Failed to load dead letters: {}", e);
+        std::io::Error::other(e.to_string())
+    })?;
+    if !dead_letters.is_empty().await {
+        info!("{} undelivered webhook message(s) in {}", dead_letters.len().await, DEAD_LETTERS_FILE);
+    }
+    let queue_limits = QueueLimits::from_env().map_err(|e| std::io::Error::other(e.to_string()))?;
+    info!(
+        "Webhook queue: {} running, {} per target, {} waiting",
+        queue_limits.max_running, queue_limits.max_running_per_target, queue_limits.max_queued
+    );
+    let webhook_targets = Arc::new(webhook_targets);
+    let dead_letters = Arc::new(dead_letters);
+    let schedules = ScheduleStore::load(StdPath::new(SCHEDULES_FILE)).map_err(|e| {
+        error!("Failed to load schedules: {}", e);
+        std::io::Error::other(e.to_string())
+    })?;
+    if !schedules.is_empty().await {
+        info!("Loaded {} schedule(s) from {}", schedules.len().await, SCHEDULES_FILE);
+    }
+    let schedules = Arc::new(schedules);
+    // Flag bad fixtures up front rather than when a broadcast first loads them
+    let strict = std::env::var(STRICT_TRANSCRIPTS_ENV).is_ok_and(|value| matches!(value.trim(), "true" | "1"));
+    match lint_dir(StdPath::new("transcript"), &LintOptions::default()) {
+        Ok(reports) => {
+            let invalid = reports.iter().filter(|report| !report.is_valid()).count();
+            for report in reports {
+                let filename = report.filename.as_deref().unwrap_or_default();
+                if !report.is_valid() {
+                    error!(
+                        "Transcript {}: {} error(s), {} warning(s); see GET /api/transcripts/invalid",
+                        filename, report.errors, report.warnings
+                    );
+                    for issue in report.issues.iter().filter(|issue| issue.severity == Severity::Error) {
+                        error!("  {} row {}: {}", filename, issue.row.unwrap_or_default(), issue.message);
+                    }
+                } else if report.warnings > 0 {
+                    warn!("Transcript {}: {} warning(s)", filename, report.warnings);
+                }
+            }
+            if strict && invalid > 0 {
+                let message = format!("{} transcript(s) have errors and {} is set", invalid, STRICT_TRANSCRIPTS_ENV);
+                error!("Refusing to start: {}", message);
+                return Err(std::io::Error::other(message));
+            }
+        }
+        Err(e) => {
+            error!("Failed to validate transcripts: {}", e);
+            if strict {
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        }
+    }
+    let webhook_queue = WebhookQueue::new(queue_limits);
+    let launcher = BroadcastLauncher {
+        session_kinds: session_kinds.clone(),
+        webhook_targets: webhook_targets.clone(),
+        broadcasts: broadcasts.clone(),
+        reports: reports.clone(),
+        dead_letters: dead_letters.clone(),
+        webhook_queue: webhook_queue.clone(),
+    };
+    let scheduled = launcher.clone();
+    spawn_scheduler(schedules.clone(), move |request| {
+        let launcher = scheduled.clone();
+        async move {
+            launcher
+                .start(request)
+                .await
+                .map(|started| started.broadcast_id)
+                .map_err(|e| anyhow::anyhow!(e))
+        }
+    });
     let api = Api {
+        session_kinds,
+        webhook_targets,
         sessions: sessions.clone(),
+        recordings: recordings.clone(),
+        reports,
+        broadcasts: broadcasts.clone(),
+        webhook_queue,
+        dead_letters,
+        launcher,
+        schedules,
     };
 
     let api_service =
@@ -309,7 +2475,10 @@ async fn main() -> Result<(), std::io::Error> {
         .nest("/api", api_service)
         .at("/", ui)
         .at("/spec", spec)
-        .at("/ws/:session_id", websocket_handler.data(ws_sessions))
+        .at("/ws/:session_id", websocket_handler.data(ws_sessions.clone()))
+        .at("/ws/:session_id/speakers/:speaker", speaker_websocket_handler.data(ws_sessions))
+        .at("/ws/broadcasts/:broadcast_id", subscriber_handler.data(broadcasts))
+        .at("/record/:recording_id", record_handler.data(recordings))
         .with(Tracing);
 
     // Start server
@@ -325,6 +2494,8 @@ async fn main() -> Result<(), std::io::Error> {
     info!("OpenAPI UI available at http://0.0.0.0:8080/");
     info!("API endpoints available at http://0.0.0.0:8080/api/");
     info!("WebSocket server running at ws://0.0.0.0:8080/ws/");
+    info!("Composite broadcast subscribers at ws://0.0.0.0:8080/ws/broadcasts/");
+    info!("WebSocket capture running at ws://0.0.0.0:8080/record/");
 
     // Wait for server
     let _ = server_handle.await.unwrap();
@@ -332,164 +2503,239 @@ async fn main() -> Result<(), std::io::Error> {
     Ok(())
 }
 
-async fn load_all_transcripts() -> anyhow::Result<Vec<TranscriptFile>> {
-    let transcript_dir = "transcript/";
+#[handler]
+async fn websocket_handler(Path(session_id): Path<String>, websocket: WebSocket, sessions: poem::web::Data<&SessionStore>) -> impl poem::IntoResponse {
+    let sessions = sessions.0.clone();
+    
+    websocket.on_upgrade(move |socket| handle_websocket(socket, sessions, session_id, None))
+}
 
-    if !StdPath::new(transcript_dir).exists() {
-        return Ok(vec![]);
-    }
+#[handler]
+async fn speaker_websocket_handler(Path((session_id, speaker)): Path<(String, String)>, websocket: WebSocket, sessions: poem::web::Data<&SessionStore>) -> impl poem::IntoResponse {
+    let sessions = sessions.0.clone();
 
-    let mut transcript_files = Vec::new();
-    let entries = fs::read_dir(transcript_dir)?;
+    websocket.on_upgrade(move |socket| handle_websocket(socket, sessions, session_id, Some(speaker)))
+}
 
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
+async fn handle_websocket(socket: WebSocketStream, sessions: SessionStore, session_id: String, speaker: Option<String>) {
+    info!("New WebSocket connection for session: {} (speaker: {:?})", session_id, speaker);
 
-        if path.extension().and_then(|s| s.to_str()) == Some("csv") {
-            if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                match load_transcript_from_file(&path).await {
-                    Ok(records) => {
-                        transcript_files.push(TranscriptFile {
-                            filename: filename.to_string(),
-                            records,
-                        });
-                    }
-                    Err(e) => {
-                        error!("Error loading {}: {}", filename, e);
+    // Check if the session exists, and whether it streams audio
+    let has_audio = {
+        let sessions_guard = sessions.lock().await;
+        match sessions_guard.get(&session_id) {
+            Some(session) => session.audio.is_some(),
+            None => {
+                error!("Session not found: {}", session_id);
+                return;
+            }
+        }
+    };
+
+    let (sender, mut receiver) = socket.split();
+    let text_sender = |text: String| async move {
+        Ok::<_, std::io::Error>(poem::web::websocket::Message::Text(text))
+    };
+
+    // Stop the broadcast as soon as the client goes away; text messages are replies for scripts
+    let cancel = CancellationToken::new();
+    let disconnected = cancel.clone();
+    let (replies, reply_receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = disconnected.cancelled() => break,
+                message = receiver.next() => match message {
+                    Some(Ok(poem::web::websocket::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(poem::web::websocket::Message::Text(text))) => {
+                        let _ = replies.send(text);
                     }
-                }
+                    Some(Ok(_)) => {}
+                },
             }
         }
-    }
+        disconnected.cancel();
+    });
 
-    Ok(transcript_files)
+    // Start broadcasting for this session, or for one speaker's channel of it
+    let broadcast = match &speaker {
+        Some(speaker) => join_channel_session(&session_id, Some(speaker), sender.with(text_sender), reply_receiver, sessions, TokioClock::shared(), cancel.clone()).await,
+        None if has_audio => {
+            let sender = sender.with(|message: AudioMessage| async move {
+                Ok::<_, std::io::Error>(match message {
+                    AudioMessage::Text(text) => poem::web::websocket::Message::Text(text),
+                    AudioMessage::Binary(bytes) => poem::web::websocket::Message::Binary(bytes),
+                })
+            });
+            stream_audio_session(&session_id, sender, sessions, TokioClock::shared(), cancel.clone()).await
+        }
+        None => broadcast_session_with_replies(&session_id, sender.with(text_sender), reply_receiver, sessions, TokioClock::shared(), cancel.clone()).await,
+    };
+    match broadcast {
+        Ok(report) if !report.is_completed() => {
+            info!("Session {} stopped: {}", session_id, report.state.as_str());
+        }
+        Ok(_) => {}
+        Err(e) => error!("Error broadcasting messages: {}", e),
+    }
+    cancel.cancel();
 }
 
 #[handler]
-async fn websocket_handler(Path(session_id): Path<String>, websocket: WebSocket, sessions: poem::web::Data<&SessionStore>) -> impl poem::IntoResponse {
-    let sessions = sessions.0.clone();
-    
-    websocket.on_upgrade(move |socket| handle_websocket(socket, sessions, session_id))
+async fn subscriber_handler(Path(broadcast_id): Path<String>, websocket: WebSocket, broadcasts: poem::web::Data<&BroadcastStore>) -> impl poem::IntoResponse {
+    let broadcasts = broadcasts.0.clone();
+
+    websocket.on_upgrade(move |socket| handle_subscriber(socket, broadcasts, broadcast_id))
 }
 
-fn parse_time_to_time(time_str: &str) -> i32 {
-    let parts: Vec<&str> = time_str.split(':').collect();
+async fn handle_subscriber(socket: WebSocketStream, broadcasts: BroadcastStore, broadcast_id: String) {
+    info!("New subscriber for broadcast: {}", broadcast_id);
 
-    match parts.len() {
-        3 => {
-            // HH:MM:SS format
-            let hours = parts[0].parse::<i32>().unwrap_or(0);
-            let minutes = parts[1].parse::<i32>().unwrap_or(0);
-            let time = parts[2].parse::<i32>().unwrap_or(0);
-            hours * 3600 + minutes * 60 + time
-        }
-        2 => {
-            // MM:SS format
-            let minutes = parts[0].parse::<i32>().unwrap_or(0);
-            let time = parts[1].parse::<i32>().unwrap_or(0);
-            minutes * 60 + time
+    let subscription = broadcasts
+        .lock()
+        .await
+        .get(&broadcast_id)
+        .and_then(|entry| entry.subscribers.as_ref().map(|sender| sender.subscribe()));
+
+    let (mut sender, _receiver) = socket.split();
+    let Some(mut subscription) = subscription else {
+        error!("Broadcast not found or not live: {}", broadcast_id);
+        let _ = sender
+            .send(poem::web::websocket::Message::Text("SESSION_NOT_FOUND".to_string()))
+            .await;
+        return;
+    };
+
+    loop {
+        let message = match subscription.recv().await {
+            Ok(message) => message,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                error!("Subscriber of {} lagged, skipped {} messages", broadcast_id, skipped);
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        let complete = message == "SESSION_COMPLETE";
+        if let Err(e) = sender.send(poem::web::websocket::Message::Text(message)).await {
+            error!("Error sending to subscriber of {}: {}", broadcast_id, e);
+            return;
         }
-        1 => {
-            // Just time
-            parts[0].parse::<i32>().unwrap_or(0)
+        if complete {
+            break;
         }
-        _ => 0,
     }
+
+    let _ = sender.close().await;
+}
+
+#[handler]
+async fn record_handler(Path(recording_id): Path<String>, websocket: WebSocket, recordings: poem::web::Data<&RecordingStore>) -> impl poem::IntoResponse {
+    let recordings = recordings.0.clone();
+
+    websocket.on_upgrade(move |socket| handle_record_websocket(socket, recordings, recording_id))
 }
 
-async fn load_transcript_from_file(path: &StdPath) -> anyhow::Result<Vec<TranscriptRecord>> {
-    let contents = fs::read_to_string(path)?;
-    let mut reader = csv::Reader::from_reader(contents.as_bytes());
-    let mut transcripts = Vec::new();
+async fn handle_record_websocket(socket: WebSocketStream, recordings: RecordingStore, recording_id: String) {
+    info!("New capture connection for recording: {}", recording_id);
 
-    for result in reader.deserialize() {
-        let record: TranscriptRecord = result?;
-        transcripts.push(record);
+    if !recordings.lock().await.contains_key(&recording_id) {
+        error!("Recording not found: {}", recording_id);
+        return;
     }
 
-    Ok(transcripts)
-}
+    let (_sender, mut receiver) = socket.split();
 
-async fn handle_websocket(socket: WebSocketStream, sessions: SessionStore, session_id: String) {
-    info!("New WebSocket connection for session: {}", session_id);
+    while let Some(message) = receiver.next().await {
+        let text = match message {
+            Ok(poem::web::websocket::Message::Text(text)) => text,
+            Ok(poem::web::websocket::Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => {
+                error!("Capture connection error for {}: {}", recording_id, e);
+                break;
+            }
+        };
 
-    // Check if the session exists
-    {
-        let sessions_guard = sessions.lock().await;
-        if !sessions_guard.contains_key(&session_id) {
-            error!("Session not found: {}", session_id);
+        let event = match CapturedEvent::parse(&text) {
+            Ok(event) => event,
+            Err(e) => {
+                error!("Ignoring event for recording {}: {}", recording_id, e);
+                continue;
+            }
+        };
+
+        let complete = event.is_complete();
+        if let Err(e) = apply_captured_event(&recordings, &recording_id, event).await {
+            error!("Error saving recording {}: {}", recording_id, e);
+        }
+        if complete {
             return;
         }
     }
 
-    let (mut sender, _receiver) = socket.split();
-
-    // Start broadcasting for this session
-    if let Err(e) = broadcast_session_messages_poem(&session_id, &mut sender, sessions).await {
-        error!("Error broadcasting messages: {}", e);
+    // Connection closed without a completion marker: save what was captured
+    if let Err(e) = save_recording(&recordings, &recording_id).await {
+        error!("Error saving recording {}: {}", recording_id, e);
     }
 }
 
-async fn broadcast_session_messages_poem(
-    session_id: &str,
-    ws_sender: &mut futures_util::stream::SplitSink<WebSocketStream, poem::web::websocket::Message>,
-    sessions: SessionStore,
-) -> anyhow::Result<()> {
-    let session = {
-        let sessions_guard = sessions.lock().await;
-        sessions_guard.get(session_id).cloned()
+/// Applies a captured event; returns `None` when the recording does not exist
+async fn apply_captured_event(
+    recordings: &RecordingStore,
+    recording_id: &str,
+    event: CapturedEvent,
+) -> anyhow::Result<Option<serde_json::Value>> {
+    let record = match event {
+        CapturedEvent::Envelope { body } => body,
+        CapturedEvent::Record(record) => record,
+        CapturedEvent::Status { status } if status == "complete" => {
+            return save_recording(recordings, recording_id).await;
+        }
+        CapturedEvent::Status { status } => {
+            return Err(anyhow::anyhow!("Unsupported status event: {}", status));
+        }
     };
 
-    if let Some(session) = session {
-        let mut last_time = 0;
-
-        // Broadcast all messages from the session
-        for record in &session.records {
-            // Parse the time field from HH:MM:SS format to total time
-            let current_time = parse_time_to_time(&record.time);
-
-            // Calculate how long we should wait before sending this message
-            let wait_duration = if current_time > last_time {
-                current_time - last_time
-            } else {
-                0
-            };
-
-            // Wait for the calculated duration
-            if wait_duration > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(wait_duration as u64)).await;
-            }
-
-            let ws_message = WebSocketMessage {
-                job_description_enrichment_session: session.job_description_enrichment_session,
-                candidate_profile_enrichment_session: session.candidate_profile_enrichment_session,
-                body: record.clone(),
-            };
-            let message = serde_json::to_string(&ws_message)?;
-            ws_sender.send(poem::web::websocket::Message::Text(message)).await.map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))?;
+    let mut recordings_guard = recordings.lock().await;
+    let Some(recording) = recordings_guard.get_mut(recording_id) else {
+        return Ok(None);
+    };
+    recording.capture(record);
+    debug!("Captured event {} for recording {}", recording.records.len(), recording_id);
 
-            last_time = current_time;
-            debug!(
-                "Sent message at {}s: {} - {}",
-                current_time, record.speaker, record.sentence
-            );
-        }
+    Ok(Some(serde_json::json!({
+        "status": "success",
+        "recording_id": recording_id,
+        "captured": recording.records.len()
+    })))
+}
 
-        // Send completion message
-        ws_sender
-            .send(poem::web::websocket::Message::Text("SESSION_COMPLETE".to_string()))
-            .await.map_err(|e| anyhow::anyhow!("Failed to send completion message: {}", e))?;
+/// Removes a recording and writes it to the transcript catalog; returns `None` when it does not exist
+async fn save_recording(
+    recordings: &RecordingStore,
+    recording_id: &str,
+) -> anyhow::Result<Option<serde_json::Value>> {
+    let Some(recording) = recordings.lock().await.get(recording_id).cloned() else {
+        return Ok(None);
+    };
 
-        // Clean up session after broadcasting is complete
-        let mut sessions_guard = sessions.lock().await;
-        sessions_guard.remove(session_id);
-        info!("Session {} completed and cleaned up", session_id);
-    } else {
-        ws_sender
-            .send(poem::web::websocket::Message::Text("SESSION_NOT_FOUND".to_string()))
-            .await.map_err(|e| anyhow::anyhow!("Failed to send not found message: {}", e))?;
-    }
+    // Only drop the recording once it is safely on disk
+    let path = StdPath::new("transcript").join(&recording.filename);
+    write_transcript_to_file(&path, &recording.records)?;
+    recordings.lock().await.remove(recording_id);
+    info!(
+        "Recording {} saved to {} ({} records)",
+        recording_id,
+        path.display(),
+        recording.records.len()
+    );
 
-    Ok(())
+    Ok(Some(serde_json::json!({
+        "status": "success",
+        "message": "Recording saved",
+        "recording_id": recording_id,
+        "filename": recording.filename,
+        "records": recording.records.len()
+    })))
 }
@@ -0,0 +1,153 @@
+use poem_openapi::Object;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Network fault injection settings applied to a single broadcast session
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct ChaosOptions {
+    /// Seed for the fault generator; a random seed is chosen and logged when absent
+    pub seed: Option<u64>,
+    /// Probability (0.0 - 1.0) that a message is dropped
+    pub drop_rate: f64,
+    /// Probability (0.0 - 1.0) that a message is sent twice
+    pub duplicate_rate: f64,
+    /// Shuffle messages within consecutive windows of this many records (0 or 1 disables)
+    pub reorder_window: usize,
+    /// Fixed latency added before every message, in milliseconds
+    pub latency_ms: u64,
+    /// Maximum random jitter added on top of the latency, in milliseconds
+    pub jitter_ms: u64,
+    /// Force a disconnect before the record at this delivery index is sent
+    pub disconnect_at_record: Option<usize>,
+    /// Force a disconnect once the session has been running this many seconds
+    pub disconnect_after_secs: Option<u64>,
+}
+
+impl ChaosOptions {
+    /// Returns true when no fault would ever be injected
+    pub fn is_disabled(&self) -> bool {
+        self.drop_rate <= 0.0
+            && self.duplicate_rate <= 0.0
+            && self.reorder_window <= 1
+            && self.latency_ms == 0
+            && self.jitter_ms == 0
+            && self.disconnect_at_record.is_none()
+            && self.disconnect_after_secs.is_none()
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.drop_rate) {
+            return Err(anyhow::anyhow!("drop_rate must be between 0.0 and 1.0"));
+        }
+        if !(0.0..=1.0).contains(&self.duplicate_rate) {
+            return Err(anyhow::anyhow!(
+                "duplicate_rate must be between 0.0 and 1.0"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A single planned send: the timing slot drives when it is sent, the record is what is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChaosDelivery {
+    /// Index of the record whose timestamp schedules this send
+    pub slot: usize,
+    /// Index of the record whose content is sent
+    pub record: usize,
+}
+
+/// Why a chaos session was cut short
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosDisconnect {
+    AtRecord(usize),
+    AfterSecs(u64),
+}
+
+impl std::fmt::Display for ChaosDisconnect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChaosDisconnect::AtRecord(k) => write!(f, "chaos disconnect at record {}", k),
+            ChaosDisconnect::AfterSecs(t) => write!(f, "chaos disconnect after {}s", t),
+        }
+    }
+}
+
+/// Seeded fault generator shared by the WebSocket and webhook playback loops
+pub struct ChaosEngine {
+    options: ChaosOptions,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl ChaosEngine {
+    pub fn new(options: ChaosOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(|| rand::thread_rng().r#gen());
+        Self {
+            options,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seed actually in use, so a run can be reproduced
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.options.is_disabled()
+    }
+
+    /// Plans the order of sends for `len` records after applying drop, duplicate and reorder
+    pub fn plan(&mut self, len: usize) -> Vec<ChaosDelivery> {
+        let mut order: Vec<usize> = (0..len).collect();
+        if self.options.reorder_window > 1 {
+            for window in order.chunks_mut(self.options.reorder_window) {
+                window.shuffle(&mut self.rng);
+            }
+        }
+
+        let mut plan = Vec::with_capacity(len);
+        for (slot, record) in order.into_iter().enumerate() {
+            if self.options.drop_rate > 0.0 && self.rng.gen_bool(self.options.drop_rate) {
+                continue;
+            }
+            plan.push(ChaosDelivery { slot, record });
+            if self.options.duplicate_rate > 0.0 && self.rng.gen_bool(self.options.duplicate_rate) {
+                plan.push(ChaosDelivery { slot, record });
+            }
+        }
+        plan
+    }
+
+    /// Extra delay to apply before the next send
+    pub fn next_delay(&mut self) -> Duration {
+        let jitter = if self.options.jitter_ms > 0 {
+            self.rng.gen_range(0..=self.options.jitter_ms)
+        } else {
+            0
+        };
+        Duration::from_millis(self.options.latency_ms.saturating_add(jitter))
+    }
+
+    /// Checks whether the session must be cut before sending delivery number `delivered`
+    pub fn disconnect(&self, delivered: usize, elapsed: Duration) -> Option<ChaosDisconnect> {
+        if let Some(k) = self.options.disconnect_at_record
+            && delivered >= k
+        {
+            return Some(ChaosDisconnect::AtRecord(k));
+        }
+        if let Some(t) = self.options.disconnect_after_secs
+            && elapsed >= Duration::from_secs(t)
+        {
+            return Some(ChaosDisconnect::AfterSecs(t));
+        }
+        None
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time source driving playback
+///
+/// `TokioClock` follows the tokio timer, so it also honours a paused test runtime;
+/// `VirtualClock` never waits and only moves forward when playback sleeps.
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync {
+    /// Time elapsed since the clock was created
+    fn elapsed(&self) -> Duration;
+
+    async fn sleep(&self, duration: Duration);
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+pub struct TokioClock {
+    started: tokio::time::Instant,
+}
+
+impl TokioClock {
+    pub fn new() -> Self {
+        Self {
+            started: tokio::time::Instant::now(),
+        }
+    }
+
+    pub fn shared() -> SharedClock {
+        Arc::new(Self::new())
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for TokioClock {
+    fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[derive(Default)]
+pub struct VirtualClock {
+    now: Mutex<Duration>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the clock forward without anyone sleeping
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for VirtualClock {
+    fn elapsed(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        tokio::task::yield_now().await;
+    }
+}
@@ -2,3 +2,33 @@ pub const WEBHOOK_URL_TEST: &str =
     "https://n8n.bounteer.com/webhook-test/00fbc28e-a081-4f79-85d0-68b063cbac23";
 pub const WEBHOOK_URL_PROD: &str =
     "https://n8n.bounteer.com/webhook/00fbc28e-a081-4f79-85d0-68b063cbac23";
+
+/// Comma-separated session kinds accepted in addition to the built-in ones
+pub const SESSION_KINDS_ENV: &str = "RESTREAM_SESSION_KINDS";
+
+/// Set to `true` to refuse to start while a transcript in the catalog has errors
+pub const STRICT_TRANSCRIPTS_ENV: &str = "RESTREAM_STRICT_TRANSCRIPTS";
+
+/// Named webhook targets with their payload templates, loaded at startup
+pub const WEBHOOK_TARGETS_FILE: &str = "webhook_targets.json";
+
+/// Webhook broadcasts running at once across every target
+pub const WEBHOOK_MAX_RUNNING_ENV: &str = "RESTREAM_WEBHOOK_MAX_RUNNING";
+
+/// Webhook broadcasts running at once against a single target
+pub const WEBHOOK_TARGET_MAX_RUNNING_ENV: &str = "RESTREAM_WEBHOOK_TARGET_MAX_RUNNING";
+
+/// Webhook broadcasts allowed to wait for a slot before new ones are rejected
+pub const WEBHOOK_MAX_QUEUED_ENV: &str = "RESTREAM_WEBHOOK_MAX_QUEUED";
+
+/// Webhook messages that could not be delivered, kept for redelivery
+pub const DEAD_LETTERS_FILE: &str = "dead_letters.json";
+
+/// Scheduled and recurring broadcasts
+pub const SCHEDULES_FILE: &str = "schedules.json";
+
+/// Generator templates available to `POST /transcripts/generate` by name
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Branching scripts available to the broadcast endpoints by name
+pub const SCRIPTS_DIR: &str = "scripts";
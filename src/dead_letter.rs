@@ -0,0 +1,270 @@
+use crate::adapter::webhook::post_and_capture;
+use crate::report::DeliveryResult;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Longest wait for the webhook's answer to one redelivered message
+pub const REDELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A webhook message that could not be delivered
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct DeadLetter {
+    /// Dead letter ID
+    pub id: String,
+    /// Webhook URL the message was last sent to
+    pub url: String,
+    /// Named webhook target, when the broadcast used one
+    pub target: Option<String>,
+    /// Payload exactly as it was posted
+    pub payload: serde_json::Value,
+    /// Error of the last attempt
+    pub error: String,
+    /// Status code of the last attempt, if the webhook answered
+    pub status: Option<u16>,
+    /// Number of delivery attempts so far
+    pub attempts: u32,
+    /// Response report of the broadcast the message belonged to, if any
+    pub report_id: Option<String>,
+    /// Index of the transcript record, or of the first record of a batch; none for completion messages
+    pub record_index: Option<usize>,
+    /// RFC 3339 timestamp of the first failure
+    pub failed_at: String,
+    /// RFC 3339 timestamp of the last attempt
+    pub last_attempt_at: String,
+}
+
+impl DeadLetter {
+    /// A message that failed on its first attempt
+    pub fn new(url: &str, payload: serde_json::Value, error: String, status: Option<u16>) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            target: None,
+            payload,
+            error,
+            status,
+            attempts: 1,
+            report_id: None,
+            record_index: None,
+            failed_at: now.clone(),
+            last_attempt_at: now,
+        }
+    }
+}
+
+/// Where a broadcaster files its undeliverable messages
+#[derive(Clone)]
+pub struct DeadLetterHandle {
+    pub store: Arc<DeadLetterStore>,
+    /// Named webhook target recorded with each letter, if any
+    pub target: Option<String>,
+}
+
+impl DeadLetterHandle {
+    pub async fn push(
+        &self,
+        url: &str,
+        payload: serde_json::Value,
+        response: &DeliveryResult,
+        report_id: Option<String>,
+    ) {
+        let error = match (&response.error, response.status) {
+            (Some(error), _) => error.clone(),
+            (None, Some(status)) => format!("Webhook returned status {}", status),
+            (None, None) => "no response".to_string(),
+        };
+        let mut letter = DeadLetter::new(url, payload, error, response.status);
+        letter.target = self.target.clone();
+        letter.report_id = report_id;
+        letter.record_index = response.record_index;
+        self.store.push(letter).await;
+    }
+}
+
+/// Webhook dead letters are redelivered to instead of their original one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destination {
+    pub url: String,
+    /// Named webhook target the URL belongs to, if any
+    pub target: Option<String>,
+}
+
+/// Outcome of redelivering one dead letter
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct RedeliveryResult {
+    /// Dead letter ID
+    pub id: String,
+    /// URL the message was sent to
+    pub url: String,
+    /// Whether the webhook accepted the message; delivered letters leave the store
+    pub delivered: bool,
+    /// Response status code, if any
+    pub status: Option<u16>,
+    /// Error, if the message is still undeliverable
+    pub error: Option<String>,
+}
+
+/// Dead letters persisted as a JSON array in a local file
+///
+/// The file is rewritten on every change, so failed deliveries survive a restart.
+pub struct DeadLetterStore {
+    path: PathBuf,
+    letters: Mutex<Vec<DeadLetter>>,
+    /// Held by a running redelivery, so two cannot send the same letter
+    redelivering: Mutex<()>,
+}
+
+impl DeadLetterStore {
+    /// Opens the store at `path`; a missing file means no dead letters
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let letters = if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            letters: Mutex::new(letters),
+            redelivering: Mutex::new(()),
+        })
+    }
+
+    async fn save(&self, letters: &[DeadLetter]) {
+        let result = async {
+            let json = serde_json::to_string_pretty(letters)?;
+            tokio::fs::write(&self.path, json).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!(
+                "Failed to save dead letters to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    pub async fn push(&self, letter: DeadLetter) {
+        let mut letters = self.letters.lock().await;
+        letters.push(letter);
+        self.save(&letters).await;
+    }
+
+    /// Every dead letter, oldest first
+    pub async fn list(&self) -> Vec<DeadLetter> {
+        self.letters.lock().await.clone()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.letters.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.letters.lock().await.is_empty()
+    }
+
+    /// Posts the selected dead letters again, or all of them when `ids` is empty
+    ///
+    /// Each message goes to `destination` when given, otherwise to the URL it last failed
+    /// on; payloads are resent as originally rendered. Only a `2xx` response counts as
+    /// delivered: redirects are followed by the client, so a `3xx` that still comes back
+    /// is kept like any other failure, as is a message without an answer within
+    /// [`REDELIVERY_TIMEOUT`]. Delivered messages are removed; the others keep their place
+    /// with the new error and attempt count. Unknown IDs are an error and nothing is sent.
+    ///
+    /// The store stays available while messages are sent; redeliveries run one at a time.
+    pub async fn redeliver(
+        &self,
+        client: &reqwest::Client,
+        ids: &[String],
+        destination: Option<&Destination>,
+    ) -> anyhow::Result<Vec<RedeliveryResult>> {
+        let _redelivering = self.redelivering.lock().await;
+        let selected: Vec<DeadLetter> = {
+            let letters = self.letters.lock().await;
+            if let Some(missing) = ids
+                .iter()
+                .find(|id| !letters.iter().any(|letter| &letter.id == *id))
+            {
+                return Err(anyhow::anyhow!("Dead letter not found: {}", missing));
+            }
+            letters
+                .iter()
+                .filter(|letter| ids.is_empty() || ids.contains(&letter.id))
+                .cloned()
+                .collect()
+        };
+
+        let mut results = Vec::with_capacity(selected.len());
+        let mut failed = Vec::new();
+        for mut letter in selected {
+            let target_url = destination.map_or(&letter.url, |d| &d.url).clone();
+            let response = tokio::time::timeout(
+                REDELIVERY_TIMEOUT,
+                post_and_capture(client, &target_url, &letter.payload),
+            )
+            .await
+            .unwrap_or_else(|_| DeliveryResult {
+                error: Some(format!(
+                    "No response within {}s",
+                    REDELIVERY_TIMEOUT.as_secs()
+                )),
+                ..Default::default()
+            });
+            let error = match (response.status, response.error) {
+                (_, Some(error)) => Some(error),
+                (Some(status), None) if (300..400).contains(&status) => Some(format!(
+                    "Webhook returned status {}; only a 2xx response counts as delivered",
+                    status
+                )),
+                (Some(status), None) if !(200..300).contains(&status) => {
+                    Some(format!("Webhook returned status {}", status))
+                }
+                (None, None) => Some("no response".to_string()),
+                _ => None,
+            };
+            results.push(RedeliveryResult {
+                id: letter.id.clone(),
+                url: target_url.clone(),
+                delivered: error.is_none(),
+                status: response.status,
+                error: error.clone(),
+            });
+
+            if let Some(error) = error {
+                if let Some(destination) = destination {
+                    letter.url = destination.url.clone();
+                    letter.target = destination.target.clone();
+                }
+                letter.error = error;
+                letter.status = response.status;
+                letter.attempts += 1;
+                letter.last_attempt_at = chrono::Utc::now().to_rfc3339();
+                failed.push(letter);
+            }
+        }
+
+        // Letters filed while sending are left as they are
+        let mut letters = self.letters.lock().await;
+        letters.retain_mut(|letter| {
+            let Some(result) = results.iter().find(|result| result.id == letter.id) else {
+                return true;
+            };
+            if let Some(index) = failed.iter().position(|f| f.id == letter.id) {
+                *letter = failed.swap_remove(index);
+            }
+            !result.delivered
+        });
+        self.save(&letters).await;
+        Ok(results)
+    }
+}
@@ -0,0 +1,390 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::{format_time, write_transcript_to_file};
+use poem_openapi::Object;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Upper bound on records in one generated transcript, guarding against runaway repeats
+pub const MAX_GENERATED_RECORDS: usize = 10_000;
+
+/// Upper bound on transcripts generated by one request
+pub const MAX_GENERATED_TRANSCRIPTS: usize = 1_000;
+
+/// Seconds drawn uniformly from `min` to `max`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Object)]
+pub struct PauseRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// A count drawn uniformly from `min` to `max`, inclusive
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Object)]
+pub struct RepeatRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// A speaker in a generated conversation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct TemplateSpeaker {
+    /// Identifier turns refer to; also a placeholder for the chosen name, e.g. `{{recruiter}}`
+    pub id: String,
+    /// Names to pick from, once per transcript
+    pub names: Vec<String>,
+    /// Role sent with each record
+    pub role: Option<String>,
+}
+
+/// One step of the conversation: an utterance by `speaker`, or a block of nested `turns`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct TemplateTurn {
+    /// Speaker id; required for an utterance
+    pub speaker: Option<String>,
+    /// Utterances to pick from, with `{{slot}}` and `{{speaker id}}` placeholders
+    #[oai(default)]
+    #[serde(default)]
+    pub utterances: Vec<String>,
+    /// Nested turns played in order, making this turn a block
+    #[oai(default)]
+    #[serde(default)]
+    pub turns: Vec<TemplateTurn>,
+    /// Chance (0.0 - 1.0) that the turn plays at all
+    #[oai(default = "always")]
+    #[serde(default = "always")]
+    pub probability: f64,
+    /// Times the turn plays; an utterance is not repeated until its pool is used up
+    pub repeat: Option<RepeatRange>,
+    /// Slots given a new value each time the turn plays, e.g. the skill a question is about
+    #[oai(default)]
+    #[serde(default)]
+    pub redraw: Vec<String>,
+    /// Pause before the turn, overriding the template's timing
+    pub pause_secs: Option<PauseRange>,
+}
+
+fn always() -> f64 {
+    1.0
+}
+
+/// Pacing of a generated conversation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct TemplateTiming {
+    /// Speaking rate used to work out how long each utterance lasts
+    pub words_per_minute: f64,
+    /// Pause before each utterance, in seconds
+    pub pause_secs: PauseRange,
+}
+
+impl Default for TemplateTiming {
+    fn default() -> Self {
+        Self {
+            words_per_minute: 150.0,
+            pause_secs: PauseRange { min: 1.0, max: 4.0 },
+        }
+    }
+}
+
+/// Describes a family of conversations for `generate`
+///
+/// Slots and speaker names are picked once per transcript, so a call stays
+/// consistent (one salary range, one location), except for slots a turn redraws.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct GeneratorTemplate {
+    pub speakers: Vec<TemplateSpeaker>,
+    /// Value pools by slot name, e.g. `{"skill": ["React", "Node.js"]}`
+    #[oai(default)]
+    #[serde(default)]
+    pub slots: BTreeMap<String, Vec<String>>,
+    pub turns: Vec<TemplateTurn>,
+    #[oai(default)]
+    #[serde(default)]
+    pub timing: TemplateTiming,
+    /// Seed for reproducible output; a random seed is chosen when absent
+    pub seed: Option<u64>,
+}
+
+/// Names of the `{{placeholder}}`s in `text`
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    text.split("{{")
+        .skip(1)
+        .filter_map(|part| part.split_once("}}").map(|(name, _)| name.trim()))
+}
+
+fn check_pause(range: &PauseRange) -> anyhow::Result<()> {
+    if range.min < 0.0 || range.min > range.max {
+        return Err(anyhow::anyhow!("pause_secs must have 0 <= min <= max"));
+    }
+    Ok(())
+}
+
+impl GeneratorTemplate {
+    /// Reads a JSON template
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let template: Self = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Checks speakers, slots, turns and timing, naming the first problem found
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.speakers.is_empty() {
+            return Err(anyhow::anyhow!("Template needs at least one speaker"));
+        }
+        if self.turns.is_empty() {
+            return Err(anyhow::anyhow!("Template needs at least one turn"));
+        }
+        for speaker in &self.speakers {
+            if speaker.names.is_empty() {
+                return Err(anyhow::anyhow!("Speaker {} has no names", speaker.id));
+            }
+            if self.slots.contains_key(&speaker.id) {
+                return Err(anyhow::anyhow!(
+                    "Speaker {} has the same name as a slot",
+                    speaker.id
+                ));
+            }
+        }
+        for (slot, values) in &self.slots {
+            if values.is_empty() {
+                return Err(anyhow::anyhow!("Slot {} has no values", slot));
+            }
+        }
+        if self.timing.words_per_minute <= 0.0 {
+            return Err(anyhow::anyhow!("words_per_minute must be positive"));
+        }
+        check_pause(&self.timing.pause_secs)?;
+        self.turns
+            .iter()
+            .try_for_each(|turn| self.validate_turn(turn))
+    }
+
+    fn validate_turn(&self, turn: &TemplateTurn) -> anyhow::Result<()> {
+        match (&turn.speaker, turn.turns.is_empty()) {
+            (Some(speaker), true) => {
+                if !self.speakers.iter().any(|s| &s.id == speaker) {
+                    return Err(anyhow::anyhow!("Unknown speaker in turn: {}", speaker));
+                }
+                if turn.utterances.is_empty() {
+                    return Err(anyhow::anyhow!("Turn by {} has no utterances", speaker));
+                }
+            }
+            (None, false) => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "A turn needs either a speaker and utterances, or nested turns"
+                ));
+            }
+        }
+        for name in turn.utterances.iter().flat_map(|u| placeholders(u)) {
+            if !self.slots.contains_key(name) && !self.speakers.iter().any(|s| s.id == name) {
+                return Err(anyhow::anyhow!("Unknown placeholder {{{{{}}}}}", name));
+            }
+        }
+        if let Some(slot) = turn.redraw.iter().find(|s| !self.slots.contains_key(*s)) {
+            return Err(anyhow::anyhow!("Unknown slot to redraw: {}", slot));
+        }
+        if !(0.0..=1.0).contains(&turn.probability) {
+            return Err(anyhow::anyhow!("probability must be between 0.0 and 1.0"));
+        }
+        if let Some(repeat) = &turn.repeat
+            && repeat.min > repeat.max
+        {
+            return Err(anyhow::anyhow!("repeat must have min <= max"));
+        }
+        if let Some(pause) = &turn.pause_secs {
+            check_pause(pause)?;
+        }
+        turn.turns
+            .iter()
+            .try_for_each(|turn| self.validate_turn(turn))
+    }
+
+    /// Generates one transcript; the same template and seed always give the same records
+    pub fn generate(&self, seed: u64) -> Vec<TranscriptRecord> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut values = HashMap::new();
+        let mut roles = HashMap::new();
+        for speaker in &self.speakers {
+            let name = speaker.names.choose(&mut rng).cloned().unwrap_or_default();
+            values.insert(speaker.id.clone(), name);
+            roles.insert(speaker.id.clone(), speaker.role.clone());
+        }
+        for (slot, pool) in &self.slots {
+            values.insert(
+                slot.clone(),
+                pool.choose(&mut rng).cloned().unwrap_or_default(),
+            );
+        }
+
+        let mut generator = Generator {
+            template: self,
+            rng,
+            values,
+            roles,
+            clock: 0.0,
+            records: Vec::new(),
+        };
+        for turn in &self.turns {
+            generator.play(turn);
+        }
+        generator.records
+    }
+}
+
+struct Generator<'a> {
+    template: &'a GeneratorTemplate,
+    rng: StdRng,
+    /// Current value of every slot and speaker placeholder
+    values: HashMap<String, String>,
+    roles: HashMap<String, Option<String>>,
+    /// End of the last utterance, in seconds
+    clock: f64,
+    records: Vec<TranscriptRecord>,
+}
+
+impl Generator<'_> {
+    fn uniform(&mut self, range: &PauseRange) -> f64 {
+        if range.max > range.min {
+            self.rng.gen_range(range.min..=range.max)
+        } else {
+            range.min
+        }
+    }
+
+    fn fill(&self, utterance: &str) -> String {
+        let mut text = utterance.to_string();
+        for name in placeholders(utterance) {
+            if let Some(value) = self.values.get(name) {
+                text = text.replace(&format!("{{{{{}}}}}", name), value);
+            }
+        }
+        text
+    }
+
+    fn play(&mut self, turn: &TemplateTurn) {
+        if turn.probability < 1.0 && !self.rng.gen_bool(turn.probability) {
+            return;
+        }
+        let times = match &turn.repeat {
+            Some(repeat) => self.rng.gen_range(repeat.min..=repeat.max),
+            None => 1,
+        };
+
+        // Each pass draws from the utterances not used yet, refilling once all are used
+        let mut unused: Vec<&String> = Vec::new();
+        for _ in 0..times {
+            if self.records.len() >= MAX_GENERATED_RECORDS {
+                return;
+            }
+            for slot in &turn.redraw {
+                if let Some(value) = self.template.slots[slot].choose(&mut self.rng) {
+                    self.values.insert(slot.clone(), value.clone());
+                }
+            }
+            let Some(speaker) = &turn.speaker else {
+                for nested in &turn.turns {
+                    self.play(nested);
+                }
+                continue;
+            };
+
+            if unused.is_empty() {
+                unused = turn.utterances.iter().collect();
+                unused.shuffle(&mut self.rng);
+            }
+            let Some(utterance) = unused.pop() else {
+                continue;
+            };
+            let sentence = self.fill(utterance);
+
+            let pause = turn.pause_secs.unwrap_or(self.template.timing.pause_secs);
+            let start = if self.records.is_empty() {
+                0.0
+            } else {
+                self.clock + self.uniform(&pause)
+            };
+            let words = sentence.split_whitespace().count() as f64;
+            self.clock = start + words * 60.0 / self.template.timing.words_per_minute;
+            self.records.push(TranscriptRecord {
+                time: format_time(start as u64),
+                speaker: self.values[speaker].clone(),
+                role: self.roles[speaker].clone(),
+                sentence,
+                audio: None,
+            });
+        }
+    }
+}
+
+/// One transcript from `generate_transcripts`
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct GeneratedTranscript {
+    /// Seed that reproduces this transcript from the same template
+    pub seed: u64,
+    pub records: Vec<TranscriptRecord>,
+}
+
+/// Generates `count` transcripts; transcript `i` uses `seed + i`
+///
+/// The seed defaults to the template's, then to a random one.
+pub fn generate_transcripts(
+    template: &GeneratorTemplate,
+    count: usize,
+    seed: Option<u64>,
+) -> anyhow::Result<(u64, Vec<GeneratedTranscript>)> {
+    template.validate()?;
+    if count == 0 || count > MAX_GENERATED_TRANSCRIPTS {
+        return Err(anyhow::anyhow!(
+            "count must be between 1 and {}",
+            MAX_GENERATED_TRANSCRIPTS
+        ));
+    }
+    let seed = seed.or(template.seed).unwrap_or_else(rand::random);
+    let transcripts = (0..count as u64)
+        .map(|i| {
+            let seed = seed.wrapping_add(i);
+            GeneratedTranscript {
+                seed,
+                records: template.generate(seed),
+            }
+        })
+        .collect();
+    Ok((seed, transcripts))
+}
+
+/// Paths `save_transcripts` writes to: `<prefix>_001.csv`, `<prefix>_002.csv`, ...
+pub fn numbered_paths(dir: &Path, prefix: &str, count: usize, json: bool) -> Vec<PathBuf> {
+    let extension = if json { "json" } else { "csv" };
+    (1..=count)
+        .map(|n| dir.join(format!("{}_{:03}.{}", prefix, n, extension)))
+        .collect()
+}
+
+/// Writes each transcript to its numbered path, refusing to overwrite any existing file
+pub fn save_transcripts(
+    dir: &Path,
+    prefix: &str,
+    transcripts: &[GeneratedTranscript],
+    json: bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let paths = numbered_paths(dir, prefix, transcripts.len(), json);
+    // Check every path up front so a clash does not leave a partial batch behind
+    if let Some(path) = paths.iter().find(|path| path.exists()) {
+        return Err(anyhow::anyhow!(
+            "Transcript already exists: {}",
+            path.display()
+        ));
+    }
+    for (path, transcript) in paths.iter().zip(transcripts) {
+        write_transcript_to_file(path, &transcript.records)?;
+    }
+    Ok(paths)
+}
@@ -1,13 +1,140 @@
-use poem_openapi::Object;
+use crate::playback::{EventStream, PlaybackStep};
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
+pub use crate::session::{SessionContext, SessionId, SessionKind};
+
+/// Progress reported by a running broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// A record was delivered
+    Delivered { index: usize, time: i32 },
+    /// Delivery failed and the broadcast stopped
+    Failed { index: Option<usize>, error: String },
+    /// Every record and the completion message were delivered
+    Completed,
+    /// Playback was cut short, e.g. by a chaos disconnect
+    Interrupted { reason: String },
+    /// The broadcast was cancelled through its token
+    Cancelled,
+}
+
+/// Final state of a broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryState {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+    Interrupted,
+    Cancelled,
+}
+
+impl DeliveryState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryState::Running => "running",
+            DeliveryState::Completed => "completed",
+            DeliveryState::Failed => "failed",
+            DeliveryState::Interrupted => "interrupted",
+            DeliveryState::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Summary returned once a broadcast finishes
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Object)]
+pub struct DeliveryReport {
+    /// Final state of the broadcast
+    pub state: DeliveryState,
+    /// Number of records delivered
+    pub delivered: usize,
+    /// Error or interruption reason, if the broadcast did not complete
+    pub error: Option<String>,
+}
+
+impl DeliveryReport {
+    pub fn is_completed(&self) -> bool {
+        self.state == DeliveryState::Completed
+    }
+
+    /// Converts a non-completed report into an error
+    pub fn into_result(self) -> anyhow::Result<Self> {
+        match self.state {
+            DeliveryState::Completed => Ok(self),
+            state => Err(anyhow::anyhow!(
+                "Broadcast {:?}: {}",
+                state,
+                self.error.as_deref().unwrap_or("no error recorded")
+            )),
+        }
+    }
+}
+
+/// Running broadcast: progress events as they happen, then a final report
+pub struct BroadcastHandle {
+    pub progress: mpsc::UnboundedReceiver<ProgressEvent>,
+    task: JoinHandle<DeliveryReport>,
+}
+
+impl BroadcastHandle {
+    pub fn new(
+        progress: mpsc::UnboundedReceiver<ProgressEvent>,
+        task: JoinHandle<DeliveryReport>,
+    ) -> Self {
+        Self { progress, task }
+    }
+
+    /// Waits for the broadcast to finish
+    pub async fn report(self) -> DeliveryReport {
+        self.task.await.unwrap_or_else(|e| DeliveryReport {
+            state: DeliveryState::Failed,
+            delivered: 0,
+            error: Some(format!("Broadcast task failed: {}", e)),
+        })
+    }
+}
+
+/// Delivers a paced stream of transcript events for a session
+///
+/// Implementations return immediately; the work runs in the background until
+/// the stream ends, delivery fails or `cancel` fires.
+pub trait Broadcaster: Send + Sync {
+    fn broadcast(
+        self: std::sync::Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle;
+}
+
+/// A single destination; `playback::deliver` drives it from an event stream
+///
+/// Only `send` and `complete` are required. `open` runs before the first event,
+/// `close` when playback is interrupted or cancelled, and `finish` always runs last.
 #[async_trait::async_trait]
-pub trait Broadcaster {
-    async fn broadcast(
-        &self,
-        session_id: i32,
-        records: Vec<TranscriptRecord>,
-    ) -> anyhow::Result<()>;
+pub trait BroadcastSink: Send + Sync {
+    /// Human readable name used in logs and status
+    fn describe(&self) -> String;
+
+    async fn open(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn send(&self, session: &SessionContext, step: &PlaybackStep) -> anyhow::Result<()>;
+
+    async fn complete(&self, session: &SessionContext) -> anyhow::Result<()>;
+
+    async fn close(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn finish(&self, _report: &DeliveryReport) {}
 }
 
 #[derive(Serialize, Deserialize, Debug, Object)]
@@ -24,26 +151,56 @@ pub struct TranscriptRecord {
     pub time: String,
     /// Speaker name
     pub speaker: String,
+    /// Speaker role, e.g. `recruiter`; left out of messages when unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
     /// Transcript sentence
     pub sentence: String,
+    /// WAV file spoken for the record, relative to the transcript's directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BroadcastMessage {
-    /// Job description enrichment session ID (if applicable)
-    pub job_description_enrichment_session: Option<i32>,
-    /// Candidate profile enrichment session ID (if applicable)
-    pub candidate_profile_enrichment_session: Option<i32>,
+    /// Session the message belongs to, serialized as its `<kind>_enrichment_session` field
+    #[serde(flatten)]
+    pub session: SessionContext,
     /// Transcript record body
     pub body: TranscriptRecord,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+impl BroadcastMessage {
+    pub fn new(session: &SessionContext, body: TranscriptRecord) -> Self {
+        Self {
+            session: session.clone(),
+            body,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WebSocketMessage {
-    /// Job description enrichment session ID (if applicable)
-    pub job_description_enrichment_session: Option<i32>,
-    /// Candidate profile enrichment session ID (if applicable)
-    pub candidate_profile_enrichment_session: Option<i32>,
+    /// Session the message belongs to, serialized as its `<kind>_enrichment_session` field
+    #[serde(flatten)]
+    pub session: SessionContext,
+    /// Channel of the speaker, by order of first appearance, on channel-separated sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+    /// Estimated end of the utterance as HH:MM:SS, on channel-separated sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
     /// Transcript record body
     pub body: TranscriptRecord,
 }
+
+impl WebSocketMessage {
+    pub fn new(session: &SessionContext, body: TranscriptRecord) -> Self {
+        Self {
+            session: session.clone(),
+            channel: None,
+            end: None,
+            body,
+        }
+    }
+}
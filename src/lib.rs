@@ -1,4 +1,27 @@
 pub mod adapter;
+pub mod audio;
+pub mod batch;
+pub mod chaos;
+pub mod clock;
+pub mod dead_letter;
+pub mod generate;
 pub mod interface;
+pub mod lint;
+pub mod playback;
+pub mod playlist;
+pub mod queue;
+pub mod range;
+pub mod recorder;
+pub mod redact;
+pub mod report;
+pub mod schedule;
+pub mod script;
+pub mod session;
+pub mod speakers;
+pub mod stats;
+pub mod template;
+pub mod transcript;
+pub mod transform;
+pub mod turns;
 
 pub mod consts;
@@ -0,0 +1,395 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::parse_time_to_time;
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How serious a transcript issue is; errors make the transcript invalid
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    /// The transcript will not play as written
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// One problem found in a transcript
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct LintIssue {
+    /// 1-based record number, not counting the CSV header; none for whole-file issues
+    pub row: Option<usize>,
+    /// Line of the file the record starts on, for CSV transcripts
+    pub line: Option<u64>,
+    pub severity: Severity,
+    /// Stable identifier of the check, e.g. `time_backwards`
+    pub code: String,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn new(row: Option<usize>, severity: Severity, code: &str, message: String) -> Self {
+        Self {
+            row,
+            line: None,
+            severity,
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+/// Tuning for `lint_records`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct LintOptions {
+    /// Expected speakers; any other speaker is reported. Unset skips the check
+    pub known_speakers: Option<Vec<String>>,
+    /// Pauses between consecutive records longer than this are reported, in seconds
+    pub max_gap_secs: u64,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            known_speakers: None,
+            max_gap_secs: 120,
+        }
+    }
+}
+
+/// Every issue found in one transcript
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct LintReport {
+    /// Transcript filename, when the transcript came from a file
+    pub filename: Option<String>,
+    /// Records that parsed
+    pub records: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Issues in row order
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    fn new(filename: Option<String>, records: usize, mut issues: Vec<LintIssue>) -> Self {
+        issues.sort_by_key(|issue| issue.row.unwrap_or(0));
+        let count = |severity| issues.iter().filter(|i| i.severity == severity).count();
+        Self {
+            filename,
+            records,
+            errors: count(Severity::Error),
+            warnings: count(Severity::Warning),
+            issues,
+        }
+    }
+
+    /// Whether the transcript has no errors; warnings are allowed
+    pub fn is_valid(&self) -> bool {
+        self.errors == 0
+    }
+}
+
+fn time_is_valid(time: &str) -> bool {
+    let parts: Vec<&str> = time.split(':').collect();
+    parts.len() <= 3 && parts.iter().all(|part| part.trim().parse::<u32>().is_ok())
+}
+
+/// Checks parsed records: timestamps, empty or untrimmed text, speakers, duplicates and gaps
+///
+/// Record `i` is reported as row `i + 1`.
+pub fn lint_records(records: &[TranscriptRecord], options: &LintOptions) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut last_time: Option<i32> = None;
+    let mut seen: HashMap<(&str, &str, &str), usize> = HashMap::new();
+    let mut speakers: HashMap<String, &str> = HashMap::new();
+
+    for (index, record) in records.iter().enumerate() {
+        let row = Some(index + 1);
+        let mut issue = |severity, code, message| {
+            issues.push(LintIssue::new(row, severity, code, message));
+        };
+
+        if time_is_valid(&record.time) {
+            let time = parse_time_to_time(&record.time);
+            if let Some(last) = last_time {
+                if time < last {
+                    issue(
+                        Severity::Error,
+                        "time_backwards",
+                        format!(
+                            "Time {} goes backwards from the previous record",
+                            record.time
+                        ),
+                    );
+                } else if (time - last) as u64 > options.max_gap_secs {
+                    issue(
+                        Severity::Warning,
+                        "long_gap",
+                        format!("{}s pause before this record", time - last),
+                    );
+                }
+            }
+            last_time = Some(time);
+        } else {
+            issue(
+                Severity::Error,
+                "time_unparseable",
+                format!("Unparseable time {:?}, played as 00:00:00", record.time),
+            );
+        }
+
+        if record.sentence.trim().is_empty() {
+            issue(
+                Severity::Warning,
+                "empty_sentence",
+                "Empty sentence".to_string(),
+            );
+        } else if record.sentence.ends_with(['\n', '\r']) {
+            issue(
+                Severity::Warning,
+                "trailing_newline",
+                "Sentence ends with a newline".to_string(),
+            );
+        } else if record.sentence.trim() != record.sentence {
+            issue(
+                Severity::Info,
+                "untrimmed_sentence",
+                "Sentence has leading or trailing whitespace".to_string(),
+            );
+        }
+
+        let speaker = record.speaker.as_str();
+        if speaker.trim().is_empty() {
+            issue(
+                Severity::Warning,
+                "empty_speaker",
+                "Empty speaker".to_string(),
+            );
+        } else if let Some(known) = &options.known_speakers {
+            if !known.iter().any(|name| name == speaker) {
+                issue(
+                    Severity::Warning,
+                    "unknown_speaker",
+                    format!("Unknown speaker {:?}", speaker),
+                );
+            }
+        } else {
+            // Names that differ only in case or spacing are usually typos
+            let key = speaker.trim().to_lowercase();
+            match speakers.get(&key) {
+                Some(first) if *first != speaker => issue(
+                    Severity::Warning,
+                    "speaker_variant",
+                    format!("Speaker {:?} is spelled {:?} elsewhere", speaker, first),
+                ),
+                Some(_) => {}
+                None => {
+                    speakers.insert(key, speaker);
+                }
+            }
+        }
+
+        let key = (
+            record.time.as_str(),
+            record.speaker.as_str(),
+            record.sentence.as_str(),
+        );
+        match seen.get(&key) {
+            Some(first) => issue(
+                Severity::Warning,
+                "duplicate_row",
+                format!("Duplicate of row {}", first),
+            ),
+            None => {
+                seen.insert(key, index + 1);
+            }
+        }
+    }
+
+    if records.is_empty() {
+        issues.push(LintIssue::new(
+            None,
+            Severity::Error,
+            "empty_transcript",
+            "Transcript has no records".to_string(),
+        ));
+    }
+    issues
+}
+
+/// Lints CSV transcript text, reporting rows that fail to parse along with the other checks
+pub fn lint_csv(contents: &str, options: &LintOptions) -> (Vec<TranscriptRecord>, Vec<LintIssue>) {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let mut records = Vec::new();
+    let mut lines = Vec::new();
+    let mut issues = Vec::new();
+
+    let mut row = 0;
+    let mut raw = csv::StringRecord::new();
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            issues.push(LintIssue::new(
+                None,
+                Severity::Error,
+                "unparseable_row",
+                format!("Invalid header: {}", e),
+            ));
+            return (records, issues);
+        }
+    };
+    loop {
+        match reader.read_record(&mut raw) {
+            Ok(false) => break,
+            Ok(true) => {
+                row += 1;
+                let line = raw.position().map(|position| position.line());
+                match raw.deserialize::<TranscriptRecord>(Some(&headers)) {
+                    Ok(record) => {
+                        records.push(record);
+                        lines.push((row, line));
+                    }
+                    Err(e) => issues.push(LintIssue {
+                        line,
+                        ..LintIssue::new(
+                            Some(row),
+                            Severity::Error,
+                            "unparseable_row",
+                            e.to_string(),
+                        )
+                    }),
+                }
+            }
+            Err(e) => {
+                row += 1;
+                issues.push(LintIssue {
+                    line: e.position().map(|position| position.line()),
+                    ..LintIssue::new(Some(row), Severity::Error, "unparseable_row", e.to_string())
+                });
+                // The reader cannot resynchronise after a malformed record
+                if !matches!(e.kind(), csv::ErrorKind::UnequalLengths { .. }) {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Record checks number rows by parsed record; map them back to file rows
+    issues.extend(
+        lint_records(&records, options)
+            .into_iter()
+            .map(|mut issue| {
+                if let Some((row, line)) = issue.row.and_then(|row| lines.get(row - 1)) {
+                    issue.row = Some(*row);
+                    issue.line = *line;
+                }
+                issue
+            }),
+    );
+    (records, issues)
+}
+
+/// Lints JSON transcript text: an array of records
+pub fn lint_json(contents: &str, options: &LintOptions) -> (Vec<TranscriptRecord>, Vec<LintIssue>) {
+    let values: Vec<serde_json::Value> = match serde_json::from_str(contents) {
+        Ok(values) => values,
+        Err(e) => {
+            let issue = LintIssue::new(
+                None,
+                Severity::Error,
+                "unparseable_row",
+                format!("Expected a JSON array of records: {}", e),
+            );
+            return (Vec::new(), vec![issue]);
+        }
+    };
+
+    let mut records = Vec::new();
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+    for (index, value) in values.into_iter().enumerate() {
+        match serde_json::from_value::<TranscriptRecord>(value) {
+            Ok(record) => {
+                records.push(record);
+                rows.push(index + 1);
+            }
+            Err(e) => issues.push(LintIssue::new(
+                Some(index + 1),
+                Severity::Error,
+                "unparseable_row",
+                e.to_string(),
+            )),
+        }
+    }
+
+    issues.extend(
+        lint_records(&records, options)
+            .into_iter()
+            .map(|mut issue| {
+                issue.row = issue.row.and_then(|row| rows.get(row - 1).copied());
+                issue
+            }),
+    );
+    (records, issues)
+}
+
+/// Lints transcript text in the given format (`csv` or `json`)
+pub fn lint_contents(
+    filename: Option<String>,
+    contents: &str,
+    json: bool,
+    options: &LintOptions,
+) -> LintReport {
+    let (records, issues) = if json {
+        lint_json(contents, options)
+    } else {
+        lint_csv(contents, options)
+    };
+    LintReport::new(filename, records.len(), issues)
+}
+
+/// Lints a `.csv` or `.json` transcript file
+pub fn lint_file(path: &Path, options: &LintOptions) -> anyhow::Result<LintReport> {
+    let contents = std::fs::read_to_string(path)?;
+    let json = path.extension().and_then(|s| s.to_str()) == Some("json");
+    let filename = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(str::to_string);
+    Ok(lint_contents(filename, &contents, json, options))
+}
+
+/// Lints every `.csv` and `.json` transcript in a directory, in filename order, skipping speaker sidecars
+pub fn lint_dir(dir: &Path, options: &LintOptions) -> anyhow::Result<Vec<LintReport>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|s| s.to_str()),
+                Some("csv" | "json")
+            )
+        })
+        // Speaker sidecars sit next to the transcripts they describe
+        .filter(|path| !path.to_string_lossy().ends_with(".speakers.json"))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| lint_file(path, options)).collect()
+}
@@ -0,0 +1,220 @@
+use crate::chaos::{ChaosDelivery, ChaosDisconnect, ChaosEngine, ChaosOptions};
+use crate::clock::SharedClock;
+use crate::interface::{
+    BroadcastHandle, BroadcastSink, DeliveryReport, DeliveryState, ProgressEvent, SessionContext,
+    TranscriptRecord,
+};
+use crate::transcript::parse_time_to_time;
+use futures_util::stream::{BoxStream, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Paced transcript events handed to a `Broadcaster`; an `Err` ends playback early
+pub type EventStream = BoxStream<'static, Result<PlaybackStep, ChaosDisconnect>>;
+
+/// A record that is due to be sent
+#[derive(Debug, Clone)]
+pub struct PlaybackStep {
+    /// Index of the record in the transcript
+    pub index: usize,
+    /// Playback offset the record was scheduled at, in seconds
+    pub time: i32,
+    pub record: TranscriptRecord,
+}
+
+/// Paces a transcript in real time, applying the session's chaos options
+///
+/// Broadcasters consume it through `into_stream`, so timing and fault injection
+/// behave identically whatever the transport.
+pub struct Playback {
+    records: Vec<TranscriptRecord>,
+    chaos: ChaosEngine,
+    plan: Vec<ChaosDelivery>,
+    position: usize,
+    last_time: i32,
+    clock: SharedClock,
+    started: Duration,
+}
+
+impl Playback {
+    pub fn new(records: Vec<TranscriptRecord>, chaos: ChaosOptions, clock: SharedClock) -> Self {
+        let mut chaos = ChaosEngine::new(chaos);
+        let plan = chaos.plan(records.len());
+        let started = clock.elapsed();
+
+        Self {
+            records,
+            chaos,
+            plan,
+            position: 0,
+            last_time: 0,
+            clock,
+            started,
+        }
+    }
+
+    /// Chaos seed in use, when fault injection is enabled
+    pub fn chaos_seed(&self) -> Option<u64> {
+        (!self.chaos.is_disabled()).then(|| self.chaos.seed())
+    }
+
+    /// Time since playback started, as seen by its clock
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed().saturating_sub(self.started)
+    }
+
+    /// Waits until the next record is due and returns it, or `None` once playback is done
+    pub async fn next(&mut self) -> Option<Result<PlaybackStep, ChaosDisconnect>> {
+        let delivery = *self.plan.get(self.position)?;
+
+        // Parse the time field from HH:MM:SS format to total seconds
+        let current_time = parse_time_to_time(&self.records[delivery.slot].time);
+
+        // Calculate how long we should wait before sending this message
+        let wait_duration = if current_time > self.last_time {
+            current_time - self.last_time
+        } else {
+            0
+        };
+
+        // Wait for the calculated duration
+        if wait_duration > 0 {
+            self.clock
+                .sleep(Duration::from_secs(wait_duration as u64))
+                .await;
+        }
+
+        // Injected network latency and jitter
+        let delay = self.chaos.next_delay();
+        if !delay.is_zero() {
+            self.clock.sleep(delay).await;
+        }
+
+        if let Some(reason) = self.chaos.disconnect(self.position, self.elapsed()) {
+            self.position = self.plan.len();
+            return Some(Err(reason));
+        }
+
+        self.position += 1;
+        self.last_time = current_time;
+
+        Some(Ok(PlaybackStep {
+            index: delivery.record,
+            time: current_time,
+            record: self.records[delivery.record].clone(),
+        }))
+    }
+}
+
+impl Playback {
+    /// Turns the playback into a stream that yields each event when it is due
+    pub fn into_stream(self) -> EventStream {
+        futures_util::stream::unfold(self, |mut playback| async move {
+            playback.next().await.map(|event| (event, playback))
+        })
+        .boxed()
+    }
+}
+
+/// Drives a sink from an event stream until it ends, fails or is cancelled
+///
+/// This is the one delivery loop shared by every broadcaster.
+pub fn deliver(
+    sink: Arc<dyn BroadcastSink>,
+    session: SessionContext,
+    mut events: EventStream,
+    cancel: CancellationToken,
+) -> BroadcastHandle {
+    let (progress, receiver) = mpsc::unbounded_channel();
+
+    let task = tokio::spawn(async move {
+        let mut report = DeliveryReport::default();
+        let event = |event: ProgressEvent| {
+            // Nobody listening to progress is fine
+            let _ = progress.send(event);
+        };
+
+        if let Err(e) = sink.open(&session).await {
+            report.state = DeliveryState::Failed;
+            report.error = Some(e.to_string());
+            event(ProgressEvent::Failed {
+                index: None,
+                error: e.to_string(),
+            });
+            sink.finish(&report).await;
+            return report;
+        }
+
+        loop {
+            let next = tokio::select! {
+                // Cancelling wins over an event that is ready at the same time
+                biased;
+                _ = cancel.cancelled() => {
+                    report.state = DeliveryState::Cancelled;
+                    if let Err(e) = sink.close(&session).await {
+                        tracing::debug!("Error closing {}: {}", sink.describe(), e);
+                    }
+                    event(ProgressEvent::Cancelled);
+                    break;
+                }
+                next = events.next() => next,
+            };
+
+            match next {
+                Some(Ok(step)) => match sink.send(&session, &step).await {
+                    Ok(()) => {
+                        report.delivered += 1;
+                        event(ProgressEvent::Delivered {
+                            index: step.index,
+                            time: step.time,
+                        });
+                    }
+                    Err(e) => {
+                        report.state = DeliveryState::Failed;
+                        report.error = Some(e.to_string());
+                        event(ProgressEvent::Failed {
+                            index: Some(step.index),
+                            error: e.to_string(),
+                        });
+                        break;
+                    }
+                },
+                Some(Err(reason)) => {
+                    report.state = DeliveryState::Interrupted;
+                    report.error = Some(reason.to_string());
+                    if let Err(e) = sink.close(&session).await {
+                        tracing::debug!("Error closing {}: {}", sink.describe(), e);
+                    }
+                    event(ProgressEvent::Interrupted {
+                        reason: reason.to_string(),
+                    });
+                    break;
+                }
+                None => {
+                    match sink.complete(&session).await {
+                        Ok(()) => {
+                            report.state = DeliveryState::Completed;
+                            event(ProgressEvent::Completed);
+                        }
+                        Err(e) => {
+                            report.state = DeliveryState::Failed;
+                            report.error = Some(e.to_string());
+                            event(ProgressEvent::Failed {
+                                index: None,
+                                error: e.to_string(),
+                            });
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        sink.finish(&report).await;
+        report
+    });
+
+    BroadcastHandle::new(receiver, task)
+}
@@ -0,0 +1,305 @@
+use crate::chaos::ChaosOptions;
+use crate::clock::SharedClock;
+use crate::interface::TranscriptRecord;
+use crate::playback::{EventStream, Playback};
+use crate::transcript::{format_time, parse_time_to_time};
+use crate::transform::TranscriptTransforms;
+use futures_util::StreamExt;
+use poem_openapi::Object;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One transcript of a playlist, optionally cut to a time slice
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct PlaylistItem {
+    /// Transcript filename
+    pub filename: String,
+    /// Skip records before this time (HH:MM:SS, MM:SS or seconds)
+    pub start: Option<String>,
+    /// Skip records after this time (HH:MM:SS, MM:SS or seconds)
+    pub end: Option<String>,
+    /// Pause after this transcript, in seconds; overrides the playlist's `gap_secs`
+    pub gap_secs: Option<u64>,
+}
+
+impl PlaylistItem {
+    pub fn new(filename: impl Into<String>) -> Self {
+        Self {
+            filename: filename.into(),
+            start: None,
+            end: None,
+            gap_secs: None,
+        }
+    }
+}
+
+/// Transcripts played back to back as one session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct PlaylistOptions {
+    /// Transcripts in play order
+    pub items: Vec<PlaylistItem>,
+    /// Pause between transcripts, in seconds
+    pub gap_secs: u64,
+    /// Passes through the playlist; 0 loops until the broadcast is cancelled
+    pub repeat: u32,
+    /// Play the transcripts in a random order, reshuffled on every pass
+    pub shuffle: bool,
+    /// Seed for reproducible shuffling
+    pub seed: Option<u64>,
+}
+
+impl Default for PlaylistOptions {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            gap_secs: 0,
+            repeat: 1,
+            shuffle: false,
+            seed: None,
+        }
+    }
+}
+
+impl PlaylistOptions {
+    /// A single pass through `filenames` with no gaps
+    pub fn from_filenames<I, T>(filenames: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            items: filenames.into_iter().map(PlaylistItem::new).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Filenames in play order, for logs and status
+    pub fn describe(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| item.filename.as_str())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// A transcript slice rebased to start at zero, plus the pause that follows it
+#[derive(Debug, Clone)]
+struct Segment {
+    records: Vec<TranscriptRecord>,
+    /// Offset of the last record, in seconds
+    duration: i32,
+    gap: i32,
+}
+
+/// A loaded playlist, ready to be played
+///
+/// Each transcript slice is rebased to start where the previous one ended plus the
+/// gap, and record times in messages follow that continuous timeline. Chaos options
+/// apply to each pass through the playlist.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    segments: Vec<Segment>,
+    repeat: u32,
+    shuffle: Option<u64>,
+}
+
+impl Playlist {
+    /// Loads every transcript, resolving filenames against `dir`
+    pub async fn load(options: &PlaylistOptions, dir: &Path) -> anyhow::Result<Self> {
+        Self::load_with(options, dir, &mut TranscriptTransforms::default()).await
+    }
+
+    /// Loads every transcript, passing each through `transforms`
+    pub async fn load_with(
+        options: &PlaylistOptions,
+        dir: &Path,
+        transforms: &mut TranscriptTransforms,
+    ) -> anyhow::Result<Self> {
+        if options.items.is_empty() {
+            return Err(anyhow::anyhow!("Playlist needs at least one transcript"));
+        }
+
+        let mut segments = Vec::with_capacity(options.items.len());
+        for item in &options.items {
+            if item.filename.is_empty()
+                || item.filename.contains(['/', '\\'])
+                || item.filename.starts_with('.')
+            {
+                return Err(anyhow::anyhow!("Invalid filename: {}", item.filename));
+            }
+            let records = transforms
+                .load(&dir.join(&item.filename))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load {}: {}", item.filename, e))?;
+            let start = item.start.as_deref().map(parse_time_to_time);
+            let end = item.end.as_deref().map(parse_time_to_time);
+            if let (Some(start), Some(end)) = (start, end)
+                && start > end
+            {
+                return Err(anyhow::anyhow!("{}: start is after end", item.filename));
+            }
+
+            let records: Vec<TranscriptRecord> = records
+                .into_iter()
+                .filter(|record| {
+                    let time = parse_time_to_time(&record.time);
+                    start.is_none_or(|start| time >= start) && end.is_none_or(|end| time <= end)
+                })
+                .collect();
+            let Some(first) = records.first() else {
+                return Err(anyhow::anyhow!(
+                    "{}: no records in the selected slice",
+                    item.filename
+                ));
+            };
+
+            let base = parse_time_to_time(&first.time);
+            let records: Vec<TranscriptRecord> = records
+                .into_iter()
+                .map(|mut record| {
+                    let time = (parse_time_to_time(&record.time) - base).max(0);
+                    record.time = format_time(time as u64);
+                    record
+                })
+                .collect();
+            let duration = records
+                .iter()
+                .map(|record| parse_time_to_time(&record.time))
+                .max()
+                .unwrap_or(0);
+            segments.push(Segment {
+                records,
+                duration,
+                gap: item.gap_secs.unwrap_or(options.gap_secs) as i32,
+            });
+        }
+
+        let shuffle = options
+            .shuffle
+            .then(|| options.seed.unwrap_or_else(|| rand::thread_rng().r#gen()));
+        Ok(Self {
+            segments,
+            repeat: options.repeat,
+            shuffle,
+        })
+    }
+
+    /// Shuffle seed in use, when shuffling
+    pub fn shuffle_seed(&self) -> Option<u64> {
+        self.shuffle
+    }
+
+    /// Records in one pass through the playlist
+    pub fn records_per_pass(&self) -> usize {
+        self.segments.iter().map(|s| s.records.len()).sum()
+    }
+
+    /// Records of one pass, on a timeline starting at `start`
+    fn pass(segments: &[&Segment], start: i32) -> Vec<TranscriptRecord> {
+        let mut records = Vec::new();
+        let mut offset = start;
+        for segment in segments {
+            records.extend(segment.records.iter().map(|record| TranscriptRecord {
+                time: format_time((offset + parse_time_to_time(&record.time)) as u64),
+                ..record.clone()
+            }));
+            offset += segment.duration + segment.gap;
+        }
+        records
+    }
+
+    /// Plays the playlist as one stream, pass after pass
+    ///
+    /// Indices and offsets in the steps keep counting across passes.
+    pub fn into_stream(self, chaos: ChaosOptions, clock: SharedClock) -> EventStream {
+        struct State {
+            playlist: Playlist,
+            rng: Option<StdRng>,
+            passes: u32,
+            current: Option<EventStream>,
+            /// Set once a chaos disconnect ended playback
+            disconnected: bool,
+            /// Offset and record count of the passes already played
+            base_time: i32,
+            base_index: usize,
+            /// Offset of the last record of the current pass
+            pass_end: i32,
+            /// Pause before the next pass starts
+            next_gap: i32,
+        }
+
+        let state = State {
+            rng: self.shuffle.map(StdRng::seed_from_u64),
+            playlist: self,
+            passes: 0,
+            current: None,
+            disconnected: false,
+            base_time: 0,
+            base_index: 0,
+            pass_end: 0,
+            next_gap: 0,
+        };
+
+        futures_util::stream::unfold(state, move |mut state| {
+            let chaos = chaos.clone();
+            let clock = clock.clone();
+            async move {
+                loop {
+                    if let Some(current) = &mut state.current {
+                        match current.next().await {
+                            Some(Ok(mut step)) => {
+                                step.index += state.base_index;
+                                step.time += state.base_time;
+                                step.record.time = format_time(step.time as u64);
+                                return Some((Ok(step), state));
+                            }
+                            Some(Err(reason)) => {
+                                state.current = None;
+                                state.disconnected = true;
+                                return Some((Err(reason), state));
+                            }
+                            None => {
+                                state.current = None;
+                                state.base_index += state.playlist.records_per_pass();
+                                state.base_time += state.pass_end;
+                            }
+                        }
+                    }
+
+                    let repeat = state.playlist.repeat;
+                    if state.disconnected || (repeat != 0 && state.passes >= repeat) {
+                        return None;
+                    }
+
+                    let mut order: Vec<&Segment> = state.playlist.segments.iter().collect();
+                    if let Some(rng) = &mut state.rng {
+                        order.shuffle(rng);
+                    }
+                    // A later pass starts after the gap that follows the previous one
+                    let records = Self::pass(&order, state.next_gap);
+                    state.pass_end = records
+                        .iter()
+                        .map(|record| parse_time_to_time(&record.time))
+                        .max()
+                        .unwrap_or(0);
+                    state.next_gap = order.last().map_or(0, |segment| segment.gap);
+
+                    let mut chaos = chaos.clone();
+                    chaos.seed = chaos
+                        .seed
+                        .map(|seed| seed.wrapping_add(state.passes as u64));
+                    state.passes += 1;
+                    state.current =
+                        Some(Playback::new(records, chaos, clock.clone()).into_stream());
+                }
+            }
+        })
+        .boxed()
+    }
+}
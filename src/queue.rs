@@ -0,0 +1,391 @@
+use crate::consts::{
+    WEBHOOK_MAX_QUEUED_ENV, WEBHOOK_MAX_RUNNING_ENV, WEBHOOK_TARGET_MAX_RUNNING_ENV,
+};
+use crate::interface::{Broadcaster, DeliveryState, ProgressEvent, SessionContext};
+use crate::playback::{EventStream, Playback};
+use crate::script::ScriptPlayback;
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// Lifecycle of a queued webhook broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for a global or per-target slot
+    Queued,
+    Running,
+    Completed,
+    /// Delivery failed or playback was interrupted
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+impl From<DeliveryState> for JobState {
+    fn from(state: DeliveryState) -> Self {
+        match state {
+            DeliveryState::Running => JobState::Running,
+            DeliveryState::Completed => JobState::Completed,
+            DeliveryState::Failed | DeliveryState::Interrupted => JobState::Failed,
+            DeliveryState::Cancelled => JobState::Cancelled,
+        }
+    }
+}
+
+/// Finished jobs kept for `GET /webhook-jobs`; older ones are dropped
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// Status of one webhook job as returned by the API
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct JobStatus {
+    /// Job ID
+    pub job_id: String,
+    /// Target the job is limited by: a named target or the webhook URL
+    pub target: String,
+    /// Transcript filename
+    pub filename: String,
+    /// Current state
+    pub state: JobState,
+    /// Response report of the broadcast, if any
+    pub report_id: Option<String>,
+    /// Number of records delivered so far
+    pub delivered: usize,
+    /// Error or interruption reason, once failed
+    pub error: Option<String>,
+    /// RFC 3339 timestamp of submission
+    pub submitted_at: String,
+}
+
+/// Concurrency limits of the webhook queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueLimits {
+    /// Jobs running at once across every target
+    pub max_running: usize,
+    /// Jobs running at once against a single target
+    pub max_running_per_target: usize,
+    /// Jobs waiting for a slot before new submissions are rejected
+    pub max_queued: usize,
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self {
+            max_running: 8,
+            max_running_per_target: 2,
+            max_queued: 100,
+        }
+    }
+}
+
+impl QueueLimits {
+    /// The defaults, overridden by `RESTREAM_WEBHOOK_MAX_RUNNING`,
+    /// `RESTREAM_WEBHOOK_TARGET_MAX_RUNNING` and `RESTREAM_WEBHOOK_MAX_QUEUED`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let read = |name: &str, default: usize| -> anyhow::Result<usize> {
+            match std::env::var(name) {
+                Ok(value) => match value.trim().parse::<usize>() {
+                    Ok(limit) if limit > 0 => Ok(limit),
+                    _ => Err(anyhow::anyhow!(
+                        "{} must be a positive integer, got '{}'",
+                        name,
+                        value
+                    )),
+                },
+                Err(_) => Ok(default),
+            }
+        };
+        let defaults = Self::default();
+        Ok(Self {
+            max_running: read(WEBHOOK_MAX_RUNNING_ENV, defaults.max_running)?,
+            max_running_per_target: read(
+                WEBHOOK_TARGET_MAX_RUNNING_ENV,
+                defaults.max_running_per_target,
+            )?,
+            max_queued: read(WEBHOOK_MAX_QUEUED_ENV, defaults.max_queued)?,
+        })
+    }
+}
+
+/// A webhook broadcast waiting to be run by the queue
+pub struct WebhookJob {
+    /// Key the per-target limit applies to, e.g. a target name or URL
+    pub target: String,
+    pub filename: String,
+    pub report_id: Option<String>,
+    pub broadcaster: Arc<dyn Broadcaster>,
+    pub session: SessionContext,
+    /// Playback started once the job gets a slot, so queued time is not part of the timeline
+    pub playback: JobPlayback,
+}
+
+/// What a queued job plays
+pub enum JobPlayback {
+    Transcript(Box<Playback>),
+    /// A branching script, following the webhook's responses
+    Script(Box<ScriptPlayback>),
+}
+
+impl From<Playback> for JobPlayback {
+    fn from(playback: Playback) -> Self {
+        JobPlayback::Transcript(Box::new(playback))
+    }
+}
+
+impl From<ScriptPlayback> for JobPlayback {
+    fn from(playback: ScriptPlayback) -> Self {
+        JobPlayback::Script(Box::new(playback))
+    }
+}
+
+impl JobPlayback {
+    pub fn into_stream(self) -> EventStream {
+        match self {
+            JobPlayback::Transcript(playback) => playback.into_stream(),
+            JobPlayback::Script(playback) => playback.into_stream(),
+        }
+    }
+}
+
+/// Slots held by a webhook broadcast that runs outside the queue; released on drop
+pub struct QueueSlots {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+struct JobEntry {
+    status: JobStatus,
+    cancel: CancellationToken,
+    /// Submission order, used to list jobs oldest first
+    order: usize,
+}
+
+/// Drops the oldest finished jobs beyond [`MAX_FINISHED_JOBS`]
+fn evict_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(usize, String)> = jobs
+        .iter()
+        .filter(|(_, entry)| entry.status.state.is_finished())
+        .map(|(job_id, entry)| (entry.order, job_id.clone()))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    for (_, job_id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+        jobs.remove(job_id);
+    }
+}
+
+/// Runs webhook broadcasts with global and per-target concurrency limits
+///
+/// Every job shares one pooled HTTP client. Jobs wait in the queue until both a global
+/// and a per-target slot are free; the last [`MAX_FINISHED_JOBS`] finished jobs stay listed
+/// with their final state.
+#[derive(Clone)]
+pub struct WebhookQueue {
+    client: reqwest::Client,
+    limits: QueueLimits,
+    running: Arc<Semaphore>,
+    targets: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+    submitted: Arc<AtomicUsize>,
+}
+
+impl WebhookQueue {
+    pub fn new(limits: QueueLimits) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            limits,
+            running: Arc::new(Semaphore::new(limits.max_running)),
+            targets: Arc::new(Mutex::new(HashMap::new())),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            submitted: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Shared client every webhook broadcaster should post with
+    pub fn client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    pub fn limits(&self) -> QueueLimits {
+        self.limits
+    }
+
+    /// Queues a job and returns its status; fails when the queue is full
+    pub async fn submit(&self, job: WebhookJob) -> anyhow::Result<JobStatus> {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let cancel = CancellationToken::new();
+        let status = JobStatus {
+            job_id: job_id.clone(),
+            target: job.target.clone(),
+            filename: job.filename.clone(),
+            state: JobState::Queued,
+            report_id: job.report_id.clone(),
+            delivered: 0,
+            error: None,
+            submitted_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        {
+            let mut jobs = self.jobs.lock().await;
+            let queued = jobs
+                .values()
+                .filter(|entry| entry.status.state == JobState::Queued)
+                .count();
+            if queued >= self.limits.max_queued {
+                return Err(anyhow::anyhow!(
+                    "Webhook queue is full ({} jobs waiting)",
+                    queued
+                ));
+            }
+            let order = self.submitted.fetch_add(1, Ordering::Relaxed);
+            jobs.insert(
+                job_id.clone(),
+                JobEntry {
+                    status: status.clone(),
+                    cancel: cancel.clone(),
+                    order,
+                },
+            );
+        }
+
+        let queue = self.clone();
+        tokio::spawn(async move { queue.run(job_id, job, cancel).await });
+        Ok(status)
+    }
+
+    async fn target_slots(&self, target: &str) -> Arc<Semaphore> {
+        let mut targets = self.targets.lock().await;
+        // Nothing holds or waits on an idle target's semaphore, so it can be recreated later
+        targets.retain(|_, slots| Arc::strong_count(slots) > 1);
+        targets
+            .entry(target.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limits.max_running_per_target)))
+            .clone()
+    }
+
+    /// Waits for a slot on each target, then one global slot per entry
+    ///
+    /// For broadcasts that post to webhooks without being submitted, e.g. composite
+    /// broadcasts, so they share the queue's limits. Targets are given by the same key as
+    /// [`WebhookJob::target`] and may repeat; no slots are taken for an empty list.
+    pub async fn acquire_slots(&self, targets: &[String]) -> QueueSlots {
+        if targets.is_empty() {
+            return QueueSlots {
+                _permits: Vec::new(),
+            };
+        }
+        // Targets are always taken in the same order so two broadcasts cannot deadlock
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        let mut sorted: Vec<&str> = targets.iter().map(String::as_str).collect();
+        sorted.sort_unstable();
+        for target in sorted {
+            match counts.last_mut() {
+                Some((last, count)) if *last == target => *count += 1,
+                _ => counts.push((target, 1)),
+            }
+        }
+
+        // Per-target slots first, so a busy target does not hold global slots
+        let mut permits = Vec::with_capacity(counts.len() + 1);
+        for (target, count) in counts {
+            let count = count.min(self.limits.max_running_per_target) as u32;
+            let permit = self
+                .target_slots(target)
+                .await
+                .acquire_many_owned(count)
+                .await
+                .expect("queue semaphores are never closed");
+            permits.push(permit);
+        }
+        let count = targets.len().min(self.limits.max_running) as u32;
+        let permit = self
+            .running
+            .clone()
+            .acquire_many_owned(count)
+            .await
+            .expect("queue semaphores are never closed");
+        permits.push(permit);
+        QueueSlots { _permits: permits }
+    }
+
+    async fn update(&self, job_id: &str, update: impl FnOnce(&mut JobStatus)) {
+        if let Some(entry) = self.jobs.lock().await.get_mut(job_id) {
+            update(&mut entry.status);
+        }
+    }
+
+    /// Applies a job's final state and drops the oldest finished jobs beyond the cap
+    async fn finish(&self, job_id: &str, update: impl FnOnce(&mut JobStatus)) {
+        let mut jobs = self.jobs.lock().await;
+        if let Some(entry) = jobs.get_mut(job_id) {
+            update(&mut entry.status);
+        }
+        evict_finished(&mut jobs);
+    }
+
+    async fn run(&self, job_id: String, job: WebhookJob, cancel: CancellationToken) {
+        let _slots = tokio::select! {
+            slots = self.acquire_slots(std::slice::from_ref(&job.target)) => slots,
+            _ = cancel.cancelled() => {
+                self.finish(&job_id, |status| status.state = JobState::Cancelled).await;
+                return;
+            }
+        };
+        self.update(&job_id, |status| status.state = JobState::Running)
+            .await;
+
+        let mut handle = job
+            .broadcaster
+            .broadcast(job.session, job.playback.into_stream(), cancel);
+        while let Some(event) = handle.progress.recv().await {
+            if let ProgressEvent::Delivered { .. } = event {
+                self.update(&job_id, |status| status.delivered += 1).await;
+            }
+        }
+
+        let report = handle.report().await;
+        self.finish(&job_id, |status| {
+            status.state = report.state.into();
+            status.delivered = report.delivered;
+            status.error = report.error.clone();
+        })
+        .await;
+    }
+
+    pub async fn get(&self, job_id: &str) -> Option<JobStatus> {
+        self.jobs
+            .lock()
+            .await
+            .get(job_id)
+            .map(|entry| entry.status.clone())
+    }
+
+    /// Every known job, oldest first
+    pub async fn list(&self) -> Vec<JobStatus> {
+        let jobs = self.jobs.lock().await;
+        let mut entries: Vec<&JobEntry> = jobs.values().collect();
+        entries.sort_by_key(|entry| entry.order);
+        entries.iter().map(|entry| entry.status.clone()).collect()
+    }
+
+    /// Cancels a queued or running job; returns `None` for an unknown job
+    pub async fn cancel(&self, job_id: &str) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().await;
+        let entry = jobs.get(job_id)?;
+        if !entry.status.state.is_finished() {
+            entry.cancel.cancel();
+        }
+        Some(entry.status.clone())
+    }
+}
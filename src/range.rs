@@ -0,0 +1,128 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::{format_time, parse_time_to_time};
+
+/// One end of a transcript range: a timestamp or a record index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordBound {
+    /// Offset in seconds, from `HH:MM:SS` or `MM:SS`
+    Time(i32),
+    /// Zero-based record index, from a bare number
+    Index(usize),
+}
+
+impl RecordBound {
+    /// Parses `HH:MM:SS` or `MM:SS` as a timestamp and a bare number as a record index
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let raw = raw.trim();
+        if raw.contains(':') {
+            let valid = raw.split(':').count() <= 3
+                && raw
+                    .split(':')
+                    .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+            if !valid {
+                return Err(anyhow::anyhow!("Invalid timestamp: {}", raw));
+            }
+            return Ok(Self::Time(parse_time_to_time(raw)));
+        }
+        raw.parse()
+            .map(Self::Index)
+            .map_err(|_| anyhow::anyhow!("Expected HH:MM:SS, MM:SS or a record index: {}", raw))
+    }
+}
+
+/// Part of a transcript to play, plus context records leading up to it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptRange {
+    /// First record to play; the start of the transcript when unset
+    pub start: Option<RecordBound>,
+    /// Last record to play, inclusive; the end of the transcript when unset
+    pub end: Option<RecordBound>,
+    /// Records before `start` sent instantly, ahead of the paced ones
+    pub context: usize,
+}
+
+/// Records selected by a `TranscriptRange`, ready to play
+#[derive(Debug, Clone)]
+pub struct TranscriptSlice {
+    /// Context records followed by the range, with times rebased to its start
+    pub records: Vec<TranscriptRecord>,
+    /// Index in the full transcript of the first record in `records`
+    pub offset: usize,
+    /// Number of leading context records
+    pub context: usize,
+}
+
+impl TranscriptRange {
+    pub fn parse(start: Option<&str>, end: Option<&str>, context: usize) -> anyhow::Result<Self> {
+        let start = start.map(RecordBound::parse).transpose()?;
+        let end = end.map(RecordBound::parse).transpose()?;
+        let reversed = match (start, end) {
+            (Some(RecordBound::Time(start)), Some(RecordBound::Time(end))) => start > end,
+            (Some(RecordBound::Index(start)), Some(RecordBound::Index(end))) => start > end,
+            _ => false,
+        };
+        if reversed {
+            return Err(anyhow::anyhow!("start is after end"));
+        }
+        Ok(Self {
+            start,
+            end,
+            context,
+        })
+    }
+
+    /// Whether the range covers the whole transcript
+    pub fn is_full(&self) -> bool {
+        self.start.is_none() && self.end.is_none()
+    }
+
+    /// Cuts `records` to the range
+    ///
+    /// The first record of the range plays at offset zero and later ones keep their
+    /// spacing; context records are sent at offset zero too.
+    pub fn apply(&self, records: Vec<TranscriptRecord>) -> anyhow::Result<TranscriptSlice> {
+        let times: Vec<i32> = records
+            .iter()
+            .map(|record| parse_time_to_time(&record.time))
+            .collect();
+
+        let first = match self.start {
+            None => 0,
+            Some(RecordBound::Index(index)) => index,
+            Some(RecordBound::Time(start)) => times
+                .iter()
+                .position(|time| *time >= start)
+                .unwrap_or(records.len()),
+        };
+        let last = match self.end {
+            None => records.len().checked_sub(1),
+            Some(RecordBound::Index(index)) => Some(index.min(records.len().saturating_sub(1))),
+            Some(RecordBound::Time(end)) => times.iter().rposition(|time| *time <= end),
+        };
+        let last = match last {
+            Some(last) if first < records.len() && first <= last => last,
+            _ => return Err(anyhow::anyhow!("No records in the selected range")),
+        };
+
+        let offset = first.saturating_sub(self.context);
+        let base = times[first];
+        let records = records
+            .into_iter()
+            .zip(times)
+            .enumerate()
+            .skip(offset)
+            .take(last + 1 - offset)
+            .map(|(index, (mut record, time))| {
+                let time = if index < first { 0 } else { time - base };
+                record.time = format_time(time.max(0) as u64);
+                record
+            })
+            .collect();
+
+        Ok(TranscriptSlice {
+            records,
+            offset,
+            context: first - offset,
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::format_time;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// A live event accepted by the capture endpoints
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CapturedEvent {
+    /// A `BroadcastMessage` / `WebSocketMessage` envelope as sent by restream itself
+    Envelope { body: CapturedRecord },
+    /// A bare `TranscriptRecord`-shaped event
+    Record(CapturedRecord),
+    /// A completion marker such as `{"status": "complete"}`
+    Status { status: String },
+}
+
+/// A transcript line as received; any incoming `time` is ignored in favour of arrival time
+#[derive(Deserialize, Debug, Clone)]
+pub struct CapturedRecord {
+    pub speaker: String,
+    #[serde(default)]
+    pub role: Option<String>,
+    pub sentence: String,
+}
+
+impl CapturedEvent {
+    /// Parses a raw JSON event; the plain `SESSION_COMPLETE` marker is treated as completion
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        if raw.trim() == "SESSION_COMPLETE" {
+            return Ok(CapturedEvent::Status {
+                status: "complete".to_string(),
+            });
+        }
+        serde_json::from_str(raw).map_err(|e| anyhow::anyhow!("Unrecognised event: {}", e))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self, CapturedEvent::Status { status } if status == "complete")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordingSession {
+    pub filename: String,
+    pub started_at: Option<Instant>,
+    pub records: Vec<TranscriptRecord>,
+}
+
+pub type RecordingStore = Arc<Mutex<HashMap<String, RecordingSession>>>;
+
+impl RecordingSession {
+    pub fn new(filename: String) -> Self {
+        Self {
+            filename,
+            started_at: None,
+            records: Vec::new(),
+        }
+    }
+
+    /// Appends an event, timestamped relative to the first captured event
+    pub fn capture(&mut self, record: CapturedRecord) {
+        let now = Instant::now();
+        let started_at = *self.started_at.get_or_insert(now);
+        let offset = now.duration_since(started_at).as_secs_f64().round() as u64;
+
+        self.records.push(TranscriptRecord {
+            time: format_time(offset),
+            speaker: record.speaker,
+            role: record.role,
+            sentence: record.sentence,
+            audio: None,
+        });
+    }
+}
+
+/// Checks that a recording target is a bare `.csv` filename inside the catalog
+pub fn validate_filename(filename: &str) -> anyhow::Result<()> {
+    if filename.is_empty()
+        || filename.contains('/')
+        || filename.contains('\\')
+        || filename.starts_with('.')
+    {
+        return Err(anyhow::anyhow!("Invalid filename: {}", filename));
+    }
+    if !filename.ends_with(".csv") {
+        return Err(anyhow::anyhow!("Filename must end with .csv: {}", filename));
+    }
+    Ok(())
+}
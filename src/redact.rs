@@ -0,0 +1,274 @@
+use crate::interface::TranscriptRecord;
+use poem_openapi::{Enum, Object};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How detected PII is replaced
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMode {
+    /// Placeholder tokens such as `[NAME_1]` or `[EMAIL_2]`
+    #[default]
+    Redact,
+    /// Realistic stand-ins such as `Person 1` or `user2@example.com`
+    Pseudonymize,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// What to redact from a replay
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct RedactionOptions {
+    #[oai(default)]
+    #[serde(default)]
+    pub mode: RedactionMode,
+    /// Email addresses
+    #[oai(default = "enabled")]
+    #[serde(default = "enabled")]
+    pub emails: bool,
+    /// Phone numbers of seven or more digits
+    #[oai(default = "enabled")]
+    #[serde(default = "enabled")]
+    pub phones: bool,
+    /// Salary figures and ranges: currency amounts and `k` amounts such as `80-100k`
+    #[oai(default = "enabled")]
+    #[serde(default = "enabled")]
+    pub salaries: bool,
+    /// Treat every name in the speaker column as a personal name
+    #[oai(default = "enabled")]
+    #[serde(default = "enabled")]
+    pub speaker_names: bool,
+    /// Further personal names
+    #[oai(default)]
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Company names
+    #[oai(default)]
+    #[serde(default)]
+    pub companies: Vec<String>,
+}
+
+impl Default for RedactionOptions {
+    fn default() -> Self {
+        Self {
+            mode: RedactionMode::default(),
+            emails: true,
+            phones: true,
+            salaries: true,
+            speaker_names: true,
+            names: Vec::new(),
+            companies: Vec::new(),
+        }
+    }
+}
+
+impl RedactionOptions {
+    /// Builds options from query or command-line flags
+    ///
+    /// Redaction is on when `redact` is set, or when it is unset and a mode, name or
+    /// company is given; `redact=false` turns it off.
+    pub fn from_params(
+        redact: Option<bool>,
+        mode: Option<RedactionMode>,
+        names: Vec<String>,
+        companies: Vec<String>,
+    ) -> Option<Self> {
+        let requested = mode.is_some() || !names.is_empty() || !companies.is_empty();
+        if !redact.unwrap_or(requested) {
+            return None;
+        }
+        Some(Self {
+            mode: mode.unwrap_or_default(),
+            names,
+            companies,
+            ..Self::default()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Email,
+    Phone,
+    Salary,
+    Company,
+    Name,
+}
+
+impl Kind {
+    fn label(&self) -> &'static str {
+        match self {
+            Kind::Email => "EMAIL",
+            Kind::Phone => "PHONE",
+            Kind::Salary => "SALARY",
+            Kind::Company => "COMPANY",
+            Kind::Name => "NAME",
+        }
+    }
+
+    /// Key under which two spellings of the same value share a token
+    fn normalize(&self, value: &str) -> String {
+        match self {
+            Kind::Phone => value.chars().filter(char::is_ascii_digit).collect(),
+            _ => value.trim().to_lowercase(),
+        }
+    }
+}
+
+/// Pattern matching any of `words` as whole words, longest first, ignoring case
+fn word_list(words: &[String]) -> Option<Regex> {
+    let mut words: Vec<&str> = words
+        .iter()
+        .map(|word| word.trim())
+        .filter(|word| word.chars().count() > 1)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+    words.dedup();
+    let alternatives: Vec<String> = words.iter().map(|word| regex::escape(word)).collect();
+    Some(Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|"))).expect("escaped words"))
+}
+
+/// Replaces PII in transcript records with tokens that stay consistent for its lifetime
+///
+/// Use one redactor per broadcast: the same email, name or figure gets the same
+/// token every time it appears, including across the transcripts of a playlist.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    options: RedactionOptions,
+    email: Regex,
+    phone: Regex,
+    salary: Regex,
+    names: Vec<String>,
+    name_pattern: Option<Regex>,
+    company_pattern: Option<Regex>,
+    tokens: HashMap<(Kind, String), String>,
+    counts: HashMap<Kind, usize>,
+}
+
+impl Redactor {
+    pub fn new(options: RedactionOptions) -> Self {
+        let names = options.names.clone();
+        Self {
+            email: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+            phone: Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,5}\)[\s.-]?)?\d{2,6}(?:[\s.-]?\d{2,6}){1,3}\b")
+                .unwrap(),
+            salary: Regex::new(
+                r"(?i)(?:[$£€]\s?\d[\d,.]*\s?(?:k|m|bn)?\b(?:\s?(?:-|to)\s?[$£€]?\s?\d[\d,.]*\s?(?:k|m|bn)?\b)?|\b\d[\d,.]*(?:\s?(?:-|to)\s?\d[\d,.]*)?\s?k\b)",
+            )
+            .unwrap(),
+            name_pattern: word_list(&names),
+            company_pattern: word_list(&options.companies),
+            names,
+            options,
+            tokens: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Adds the speakers of `records` to the personal names, if enabled
+    ///
+    /// Call it with the records as loaded, before speakers are renamed. Speakers labelled
+    /// by one of `roles`, such as `Interviewer`, are not names and are skipped.
+    pub fn learn_names(&mut self, records: &[TranscriptRecord], roles: &[String]) {
+        if !self.options.speaker_names {
+            return;
+        }
+        let before = self.names.len();
+        for record in records {
+            let known = |names: &[String]| {
+                names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&record.speaker))
+            };
+            if !known(&self.names) && !known(roles) {
+                self.names.push(record.speaker.clone());
+            }
+        }
+        if self.names.len() != before {
+            self.name_pattern = word_list(&self.names);
+        }
+    }
+
+    fn token(&mut self, kind: Kind, value: &str) -> String {
+        let key = (kind, kind.normalize(value));
+        if let Some(token) = self.tokens.get(&key) {
+            return token.clone();
+        }
+        let count = self.counts.entry(kind).or_default();
+        *count += 1;
+        let n = *count;
+        let token = match self.options.mode {
+            RedactionMode::Redact => format!("[{}_{}]", kind.label(), n),
+            RedactionMode::Pseudonymize => match kind {
+                Kind::Email => format!("user{}@example.com", n),
+                Kind::Phone => format!("555-01{:02}", n % 100),
+                Kind::Salary => {
+                    let currency = value
+                        .chars()
+                        .find(|c| matches!(c, '$' | '£' | '€'))
+                        .map(String::from)
+                        .unwrap_or_default();
+                    format!("{}{}k", currency, 40 + 10 * n)
+                }
+                Kind::Company => format!("Company {}", n),
+                Kind::Name => format!("Person {}", n),
+            },
+        };
+        self.tokens.insert(key, token.clone());
+        token
+    }
+
+    fn replace(&mut self, kind: Kind, pattern: &Regex, text: &str) -> String {
+        pattern
+            .replace_all(text, |caps: &Captures| {
+                let value = &caps[0];
+                // Short digit runs are times, years or counts rather than phone numbers
+                if kind == Kind::Phone && value.chars().filter(char::is_ascii_digit).count() < 7 {
+                    return value.to_string();
+                }
+                self.token(kind, value)
+            })
+            .into_owned()
+    }
+
+    /// Redacts one piece of text
+    pub fn redact_text(&mut self, text: &str) -> String {
+        // Emails first, as they often contain names
+        let mut text = text.to_string();
+        if self.options.emails {
+            text = self.replace(Kind::Email, &self.email.clone(), &text);
+        }
+        if self.options.salaries {
+            text = self.replace(Kind::Salary, &self.salary.clone(), &text);
+        }
+        if self.options.phones {
+            text = self.replace(Kind::Phone, &self.phone.clone(), &text);
+        }
+        if let Some(pattern) = self.company_pattern.clone() {
+            text = self.replace(Kind::Company, &pattern, &text);
+        }
+        if let Some(pattern) = self.name_pattern.clone() {
+            text = self.replace(Kind::Name, &pattern, &text);
+        }
+        text
+    }
+
+    /// Redacts the sentence of each record, and speaker names that are personal names
+    pub fn apply(&mut self, records: &mut [TranscriptRecord]) {
+        for record in records {
+            record.sentence = self.redact_text(&record.sentence);
+            if let Some(pattern) = self.name_pattern.clone()
+                && pattern.is_match(&record.speaker)
+            {
+                record.speaker = self.replace(Kind::Name, &pattern, &record.speaker);
+            }
+        }
+    }
+}
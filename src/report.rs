@@ -0,0 +1,304 @@
+use crate::interface::TranscriptRecord;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Largest response body kept per delivery, in bytes
+pub const MAX_CAPTURED_BODY: usize = 64 * 1024;
+
+/// Finished reports kept for `GET /reports/{report_id}`; the oldest are dropped first
+pub const MAX_FINISHED_REPORTS: usize = 100;
+
+/// Deliveries kept per report: the first and the most recent half of them, so a long
+/// broadcast's report stays bounded
+pub const MAX_REPORTED_DELIVERIES: usize = 1000;
+
+/// A JSON path that must exist (and optionally equal a value) in a webhook response body
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct JsonExpectation {
+    /// Dotted path (`data.items.0.id`, optional `$.` prefix) or JSON pointer (`/data/items/0/id`)
+    pub path: String,
+    /// Expected value at the path; only existence is checked when absent
+    pub equals: Option<serde_json::Value>,
+}
+
+impl JsonExpectation {
+    /// Parses `path` or `path=value`; the value is read as JSON, falling back to a plain string
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let (path, equals) = match raw.split_once('=') {
+            Some((path, value)) => (
+                path,
+                Some(
+                    serde_json::from_str(value)
+                        .unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+                ),
+            ),
+            None => (raw, None),
+        };
+
+        let path = path.trim();
+        if path.is_empty() || path == "$" {
+            return Err(anyhow::anyhow!("Empty JSON path in expectation: {}", raw));
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            equals,
+        })
+    }
+
+    fn pointer(&self) -> String {
+        if self.path.starts_with('/') {
+            return self.path.clone();
+        }
+        let dotted = self
+            .path
+            .strip_prefix("$.")
+            .or_else(|| self.path.strip_prefix('$'))
+            .unwrap_or(&self.path);
+        dotted
+            .split('.')
+            .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+            .collect()
+    }
+
+    /// Returns a failure message when the body does not satisfy this expectation
+    pub fn check(&self, body: Option<&serde_json::Value>) -> Option<String> {
+        let Some(body) = body else {
+            return Some(format!("{}: response body is not JSON", self.path));
+        };
+        match (body.pointer(&self.pointer()), &self.equals) {
+            (None, _) => Some(format!("{}: path not found", self.path)),
+            (Some(actual), Some(expected)) if actual != expected => Some(format!(
+                "{}: expected {}, got {}",
+                self.path, expected, actual
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Expectations evaluated against every transcript record delivery
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct WebhookExpectations {
+    /// Accepted status codes; any status is accepted when empty
+    pub status_codes: Vec<u16>,
+    /// JSON paths that must match in the response body
+    pub json: Vec<JsonExpectation>,
+    /// Maximum acceptable response latency, in milliseconds
+    pub max_latency_ms: Option<u64>,
+}
+
+impl WebhookExpectations {
+    pub fn is_empty(&self) -> bool {
+        self.status_codes.is_empty() && self.json.is_empty() && self.max_latency_ms.is_none()
+    }
+
+    /// Evaluates a delivery and returns every failed expectation
+    pub fn evaluate(&self, delivery: &DeliveryResult) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        let Some(status) = delivery.status else {
+            failures.push(format!(
+                "no response: {}",
+                delivery.error.as_deref().unwrap_or("unknown error")
+            ));
+            return failures;
+        };
+
+        if !self.status_codes.is_empty() && !self.status_codes.contains(&status) {
+            failures.push(format!(
+                "status {} not in expected {:?}",
+                status, self.status_codes
+            ));
+        }
+
+        if let Some(max) = self.max_latency_ms
+            && delivery.latency_ms > max
+        {
+            failures.push(format!(
+                "latency {}ms exceeds {}ms",
+                delivery.latency_ms, max
+            ));
+        }
+
+        if !self.json.is_empty() {
+            let body = delivery
+                .body
+                .as_deref()
+                .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok());
+            failures.extend(self.json.iter().filter_map(|e| e.check(body.as_ref())));
+        }
+
+        failures
+    }
+}
+
+/// Captured outcome of a single webhook POST
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Object)]
+pub struct DeliveryResult {
+    /// Index of the delivered (or first batched) transcript record; absent for the completion message
+    pub record_index: Option<usize>,
+    /// Delivered transcript record; absent for the completion message and batches
+    pub record: Option<TranscriptRecord>,
+    /// Indices of every record sent in this request, when records were batched
+    pub batch: Vec<usize>,
+    /// HTTP status returned by the webhook
+    pub status: Option<u16>,
+    /// Time until the full response body was received, in milliseconds
+    pub latency_ms: u64,
+    /// Response headers
+    pub headers: HashMap<String, String>,
+    /// Response body (truncated to 64 KiB)
+    pub body: Option<String>,
+    /// Transport error, if the request failed
+    pub error: Option<String>,
+    /// Whether this delivery met the expectations
+    pub passed: bool,
+    /// Failed expectations for this delivery
+    pub failures: Vec<String>,
+}
+
+/// Per-session report of a webhook broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct WebhookReport {
+    /// Report ID
+    pub report_id: String,
+    /// Transcript filename being broadcast
+    pub filename: String,
+    /// Target webhook URL
+    pub webhook_url: String,
+    /// running, completed or failed
+    pub status: String,
+    /// Start time (RFC 3339)
+    pub started_at: String,
+    /// Finish time (RFC 3339)
+    pub finished_at: Option<String>,
+    /// Error that stopped the broadcast, if any
+    pub error: Option<String>,
+    /// Expectations evaluated for each record delivery
+    pub expectations: WebhookExpectations,
+    /// Overall pass/fail; absent while the broadcast is running
+    pub passed: Option<bool>,
+    /// Number of record deliveries that failed their expectations
+    pub failed_deliveries: usize,
+    /// Captured responses in delivery order, without the omitted ones
+    pub deliveries: Vec<DeliveryResult>,
+    /// Deliveries dropped from the middle of `deliveries` to keep the report bounded
+    pub omitted_deliveries: usize,
+}
+
+impl WebhookReport {
+    pub fn new(
+        report_id: String,
+        filename: String,
+        webhook_url: String,
+        expectations: WebhookExpectations,
+    ) -> Self {
+        Self {
+            report_id,
+            filename,
+            webhook_url,
+            status: "running".to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            error: None,
+            expectations,
+            passed: None,
+            failed_deliveries: 0,
+            deliveries: Vec::new(),
+            omitted_deliveries: 0,
+        }
+    }
+
+    /// Evaluates and appends a delivery, dropping the oldest of the most recent ones when full
+    pub fn push(&mut self, mut delivery: DeliveryResult) {
+        if delivery.record_index.is_some() {
+            delivery.failures = self.expectations.evaluate(&delivery);
+        } else if let Some(error) = &delivery.error {
+            delivery.failures = vec![error.clone()];
+        }
+        delivery.passed = delivery.failures.is_empty();
+        if !delivery.passed && delivery.record_index.is_some() {
+            self.failed_deliveries += 1;
+        }
+        if self.deliveries.len() >= MAX_REPORTED_DELIVERIES {
+            self.deliveries.remove(MAX_REPORTED_DELIVERIES / 2);
+            self.omitted_deliveries += 1;
+        }
+        self.deliveries.push(delivery);
+    }
+
+    /// Marks the report finished and computes the overall verdict
+    pub fn finish(&mut self, error: Option<String>) {
+        self.status = if error.is_some() {
+            "failed"
+        } else {
+            "completed"
+        }
+        .to_string();
+        self.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        self.passed = Some(error.is_none() && self.failed_deliveries == 0);
+        self.error = error;
+    }
+}
+
+pub type ReportStore = Arc<Mutex<HashMap<String, WebhookReport>>>;
+
+/// Drops the oldest finished reports beyond `MAX_FINISHED_REPORTS`; running ones are kept
+fn evict_finished(reports: &mut HashMap<String, WebhookReport>) {
+    let mut finished: Vec<(String, String)> = reports
+        .values()
+        .filter_map(|report| Some((report.finished_at.clone()?, report.report_id.clone())))
+        .collect();
+    if finished.len() <= MAX_FINISHED_REPORTS {
+        return;
+    }
+    finished.sort();
+    for (_, report_id) in &finished[..finished.len() - MAX_FINISHED_REPORTS] {
+        reports.remove(report_id);
+    }
+}
+
+/// Registers a new report, evicting old finished ones
+pub async fn insert_report(store: &ReportStore, report: WebhookReport) {
+    let mut reports = store.lock().await;
+    reports.insert(report.report_id.clone(), report);
+    evict_finished(&mut reports);
+}
+
+/// Shared handle used by a broadcaster to fill in its report
+#[derive(Clone)]
+pub struct ReportHandle {
+    pub store: ReportStore,
+    pub report_id: String,
+}
+
+impl ReportHandle {
+    pub async fn push(&self, delivery: DeliveryResult) {
+        if let Some(report) = self.store.lock().await.get_mut(&self.report_id) {
+            report.push(delivery);
+        }
+    }
+
+    pub async fn finish(&self, error: Option<String>) {
+        let mut reports = self.store.lock().await;
+        if let Some(report) = reports.get_mut(&self.report_id) {
+            report.finish(error);
+        }
+        evict_finished(&mut reports);
+    }
+
+    /// Whether the report's expectations accept `status`, e.g. a deliberately provoked 4xx
+    pub async fn expects_status(&self, status: u16) -> bool {
+        self.store
+            .lock()
+            .await
+            .get(&self.report_id)
+            .is_some_and(|report| report.expectations.status_codes.contains(&status))
+    }
+}
@@ -0,0 +1,476 @@
+use crate::adapter::composite::CompositeBroadcastRequest;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Timelike, Utc};
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
+
+/// Longest the scheduler sleeps before looking at the schedules again
+const MAX_IDLE: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Years searched for the next match before a cron expression is considered unsatisfiable
+const MAX_SEARCH_YEARS: i32 = 5;
+
+/// Allowed values of one cron field
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronField {
+    allowed: Vec<bool>,
+    /// Whether the field starts with `*`, which matters for the day-of-month / day-of-week rule
+    any: bool,
+}
+
+impl CronField {
+    fn parse(raw: &str, min: u32, max: u32, name: &str) -> anyhow::Result<Self> {
+        let mut allowed = vec![false; max as usize + 1];
+        for part in raw.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = step
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|step| *step > 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid step in {}: {}", name, part))?;
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+            let value = |text: &str| {
+                text.parse::<u32>()
+                    .ok()
+                    .filter(|value| (min..=max).contains(value))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Invalid {} '{}': expected {}-{}", name, text, min, max)
+                    })
+            };
+            let (start, end) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (value(start)?, value(end)?),
+                    // `5/15` means from 5 to the end in steps of 15
+                    None if part.contains('/') => (value(range)?, max),
+                    None => (value(range)?, value(range)?),
+                },
+            };
+            if start > end {
+                return Err(anyhow::anyhow!("Invalid range in {}: {}", name, part));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                allowed[value as usize] = true;
+            }
+        }
+        Ok(Self {
+            allowed,
+            any: raw.starts_with('*'),
+        })
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.allowed.get(value as usize).copied().unwrap_or(false)
+    }
+}
+
+/// A standard five-field cron expression, evaluated in UTC
+///
+/// Fields are minute, hour, day of month, month and day of week (0-7, Sunday being 0
+/// or 7), each accepting `*`, values, `a-b` ranges, `/n` steps and comma lists. As in
+/// cron, when both day fields are restricted a day matching either one runs. The
+/// `@yearly`, `@monthly`, `@weekly`, `@daily`, `@midnight` and `@hourly` shorthands
+/// are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: CronField,
+    hours: CronField,
+    days: CronField,
+    months: CronField,
+    weekdays: CronField,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> anyhow::Result<Self> {
+        let expression = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields.as_slice() else {
+            return Err(anyhow::anyhow!(
+                "Invalid cron expression '{}': expected 5 fields",
+                expression
+            ));
+        };
+
+        let mut weekdays = CronField::parse(weekdays, 0, 7, "day of week")?;
+        if weekdays.allowed[7] {
+            weekdays.allowed[0] = true;
+        }
+        Ok(Self {
+            minutes: CronField::parse(minutes, 0, 59, "minute")?,
+            hours: CronField::parse(hours, 0, 23, "hour")?,
+            days: CronField::parse(days, 1, 31, "day of month")?,
+            months: CronField::parse(months, 1, 12, "month")?,
+            weekdays,
+        })
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let day = self.days.matches(time.day());
+        let weekday = self.weekdays.matches(time.weekday().num_days_from_sunday());
+        match (self.days.any, self.weekdays.any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start_of_day = |time: DateTime<Utc>| {
+            Utc.from_utc_datetime(&time.date_naive().and_time(NaiveTime::MIN))
+        };
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after.year() + MAX_SEARCH_YEARS;
+
+        while time.year() <= limit {
+            if !self.months.matches(time.month()) {
+                let (year, month) = match time.month() {
+                    12 => (time.year() + 1, 1),
+                    month => (time.year(), month + 1),
+                };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.matches_day(&time) {
+                time = start_of_day(time) + Duration::days(1);
+            } else if !self.hours.matches(time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !self.minutes.matches(time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+/// Whether a schedule still fires
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleState {
+    Active,
+    Paused,
+    /// A one-off schedule that already ran, or a cron expression with no future match
+    Completed,
+}
+
+/// When and what to broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct ScheduleRequest {
+    /// Optional name shown when listing schedules
+    pub name: Option<String>,
+    /// RFC 3339 time of a one-off broadcast, e.g. `2026-01-31T02:00:00Z`
+    pub at: Option<String>,
+    /// Five-field cron expression (UTC) of a recurring broadcast, e.g. `0 2 * * *`
+    pub cron: Option<String>,
+    /// Transcript, session and sinks of each run
+    pub broadcast: CompositeBroadcastRequest,
+}
+
+/// A scheduled composite broadcast
+#[derive(Serialize, Deserialize, Debug, Clone, Object)]
+pub struct Schedule {
+    /// Schedule ID
+    pub schedule_id: String,
+    pub name: Option<String>,
+    /// RFC 3339 time of a one-off broadcast
+    pub at: Option<String>,
+    /// Cron expression (UTC) of a recurring broadcast
+    pub cron: Option<String>,
+    /// Transcript, session and sinks of each run
+    pub broadcast: CompositeBroadcastRequest,
+    pub state: ScheduleState,
+    /// RFC 3339 time of the next run, while active
+    pub next_run: Option<String>,
+    /// RFC 3339 time of the last run
+    pub last_run: Option<String>,
+    /// Composite broadcast started by the last run
+    pub last_broadcast_id: Option<String>,
+    /// Why the last run could not start, if it failed
+    pub last_error: Option<String>,
+    /// Number of runs so far
+    pub runs: u32,
+    /// RFC 3339 creation time
+    pub created_at: String,
+}
+
+impl Schedule {
+    /// Validates the timing of a request; the broadcast itself is checked by the caller
+    pub fn new(request: ScheduleRequest, now: DateTime<Utc>) -> anyhow::Result<Self> {
+        let next_run = match (&request.at, &request.cron) {
+            (Some(at), None) => {
+                let at = DateTime::parse_from_rfc3339(at)
+                    .map_err(|e| anyhow::anyhow!("Invalid at '{}': {}", at, e))?
+                    .with_timezone(&Utc);
+                if at <= now {
+                    return Err(anyhow::anyhow!("at must be in the future"));
+                }
+                at
+            }
+            (None, Some(cron)) => CronSchedule::parse(cron)?
+                .next_after(now)
+                .ok_or_else(|| anyhow::anyhow!("Cron expression '{}' never matches", cron))?,
+            (None, None) => return Err(anyhow::anyhow!("Either at or cron is required")),
+            (Some(_), Some(_)) => return Err(anyhow::anyhow!("Give either at or cron, not both")),
+        };
+
+        Ok(Self {
+            schedule_id: uuid::Uuid::new_v4().to_string(),
+            name: request.name,
+            at: request.at,
+            cron: request.cron,
+            broadcast: request.broadcast,
+            state: ScheduleState::Active,
+            next_run: Some(next_run.to_rfc3339()),
+            last_run: None,
+            last_broadcast_id: None,
+            last_error: None,
+            runs: 0,
+            created_at: now.to_rfc3339(),
+        })
+    }
+
+    fn next_run_time(&self) -> Option<DateTime<Utc>> {
+        self.next_run
+            .as_deref()
+            .and_then(|next| DateTime::parse_from_rfc3339(next).ok())
+            .map(|next| next.with_timezone(&Utc))
+    }
+
+    /// Moves the schedule past `now`: the next cron match, or completed
+    fn advance(&mut self, now: DateTime<Utc>) {
+        let next = self
+            .cron
+            .as_deref()
+            .and_then(|cron| CronSchedule::parse(cron).ok())
+            .and_then(|cron| cron.next_after(now));
+        match next {
+            Some(next) => self.next_run = Some(next.to_rfc3339()),
+            None => {
+                self.state = ScheduleState::Completed;
+                self.next_run = None;
+            }
+        }
+    }
+}
+
+/// Schedules persisted as a JSON array in a local file
+///
+/// The file is rewritten on every change, so schedules survive a restart; runs missed
+/// while the server was down fire once when it comes back.
+pub struct ScheduleStore {
+    path: PathBuf,
+    schedules: Mutex<Vec<Schedule>>,
+    changed: Notify,
+}
+
+impl ScheduleStore {
+    /// Opens the store at `path`; a missing file means no schedules
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let schedules = if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            schedules: Mutex::new(schedules),
+            changed: Notify::new(),
+        })
+    }
+
+    async fn save(&self, schedules: &[Schedule]) {
+        let result = async {
+            let json = serde_json::to_string_pretty(schedules)?;
+            tokio::fs::write(&self.path, json).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!("Failed to save schedules to {}: {}", self.path.display(), e);
+        }
+        self.changed.notify_one();
+    }
+
+    pub async fn add(&self, schedule: Schedule) {
+        let mut schedules = self.schedules.lock().await;
+        schedules.push(schedule);
+        self.save(&schedules).await;
+    }
+
+    /// Every schedule, oldest first
+    pub async fn list(&self) -> Vec<Schedule> {
+        self.schedules.lock().await.clone()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.schedules.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.schedules.lock().await.is_empty()
+    }
+
+    pub async fn get(&self, schedule_id: &str) -> Option<Schedule> {
+        self.schedules
+            .lock()
+            .await
+            .iter()
+            .find(|schedule| schedule.schedule_id == schedule_id)
+            .cloned()
+    }
+
+    /// Pauses or resumes a schedule; `None` for an unknown schedule
+    ///
+    /// A resumed cron schedule skips the runs it missed while paused; completed
+    /// schedules cannot be resumed.
+    pub async fn set_paused(
+        &self,
+        schedule_id: &str,
+        paused: bool,
+        now: DateTime<Utc>,
+    ) -> Option<anyhow::Result<Schedule>> {
+        let mut schedules = self.schedules.lock().await;
+        let schedule = schedules
+            .iter_mut()
+            .find(|schedule| schedule.schedule_id == schedule_id)?;
+
+        match (schedule.state, paused) {
+            (ScheduleState::Completed, _) => {
+                return Some(Err(anyhow::anyhow!("Schedule already completed")));
+            }
+            (ScheduleState::Active, true) => schedule.state = ScheduleState::Paused,
+            (ScheduleState::Paused, false) => {
+                schedule.state = ScheduleState::Active;
+                if schedule.cron.is_some() {
+                    schedule.advance(now);
+                }
+            }
+            _ => {}
+        }
+        let schedule = schedule.clone();
+        self.save(&schedules).await;
+        Some(Ok(schedule))
+    }
+
+    /// Removes a schedule; returns whether it existed
+    pub async fn delete(&self, schedule_id: &str) -> bool {
+        let mut schedules = self.schedules.lock().await;
+        let before = schedules.len();
+        schedules.retain(|schedule| schedule.schedule_id != schedule_id);
+        let deleted = schedules.len() != before;
+        if deleted {
+            self.save(&schedules).await;
+        }
+        deleted
+    }
+
+    /// Takes the active schedules due at `now` and advances them to their next run
+    ///
+    /// Advancing before the broadcast starts means a slow start cannot fire a run twice.
+    pub async fn take_due(&self, now: DateTime<Utc>) -> Vec<Schedule> {
+        let mut schedules = self.schedules.lock().await;
+        let mut due = Vec::new();
+        for schedule in schedules.iter_mut() {
+            if schedule.state != ScheduleState::Active
+                || schedule.next_run_time().is_none_or(|next| next > now)
+            {
+                continue;
+            }
+            due.push(schedule.clone());
+            schedule.runs += 1;
+            schedule.last_run = Some(now.to_rfc3339());
+            schedule.advance(now);
+        }
+        if !due.is_empty() {
+            self.save(&schedules).await;
+        }
+        due
+    }
+
+    /// Records the broadcast a run started, or why it could not start
+    pub async fn record_run(&self, schedule_id: &str, result: &anyhow::Result<String>) {
+        let mut schedules = self.schedules.lock().await;
+        let Some(schedule) = schedules
+            .iter_mut()
+            .find(|schedule| schedule.schedule_id == schedule_id)
+        else {
+            return;
+        };
+        match result {
+            Ok(broadcast_id) => {
+                schedule.last_broadcast_id = Some(broadcast_id.clone());
+                schedule.last_error = None;
+            }
+            Err(e) => schedule.last_error = Some(e.to_string()),
+        }
+        self.save(&schedules).await;
+    }
+
+    async fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.schedules
+            .lock()
+            .await
+            .iter()
+            .filter(|schedule| schedule.state == ScheduleState::Active)
+            .filter_map(Schedule::next_run_time)
+            .min()
+    }
+}
+
+/// Runs due schedules until the task is aborted
+///
+/// `launch` starts one broadcast and returns its ID. The loop sleeps until the next run
+/// is due, waking early whenever the schedules change.
+pub fn spawn_scheduler<F, Fut>(store: Arc<ScheduleStore>, launch: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(CompositeBroadcastRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = anyhow::Result<String>> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            let now = Utc::now();
+            for schedule in store.take_due(now).await {
+                let result = launch(schedule.broadcast.clone()).await;
+                match &result {
+                    Ok(broadcast_id) => tracing::info!(
+                        "Schedule {} started broadcast {}",
+                        schedule.schedule_id,
+                        broadcast_id
+                    ),
+                    Err(e) => {
+                        tracing::error!("Schedule {} failed to start: {}", schedule.schedule_id, e)
+                    }
+                }
+                store.record_run(&schedule.schedule_id, &result).await;
+            }
+
+            let wait = store
+                .next_due()
+                .await
+                .map(|next| (next - Utc::now()).to_std().unwrap_or_default())
+                .map_or(MAX_IDLE, |wait| wait.min(MAX_IDLE));
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                // Changes made while running due schedules leave a permit, so none is missed
+                _ = store.changed.notified() => {}
+            }
+        }
+    })
+}
@@ -0,0 +1,342 @@
+use crate::chaos::ChaosDisconnect;
+use crate::clock::SharedClock;
+use crate::interface::TranscriptRecord;
+use crate::playback::{EventStream, PlaybackStep};
+use crate::transcript::format_time;
+use futures_util::StreamExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Nodes a script may pass through in one session, guarding against loops that never wait
+pub const MAX_SCRIPT_NODES: usize = 1_000;
+
+/// Replies from the system under test: WebSocket messages or webhook response bodies
+pub type ReplySender = mpsc::UnboundedSender<String>;
+pub type ReplyReceiver = mpsc::UnboundedReceiver<String>;
+
+/// One line a script says
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScriptLine {
+    pub speaker: String,
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Text sent; `{{reply}}` and named regex groups of the last matched reply are filled in
+    pub sentence: String,
+    /// Pause before the line, in seconds
+    #[serde(default)]
+    pub pause_secs: u64,
+}
+
+/// Where to go for a reply; a branch with neither keywords nor a regex matches any reply
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScriptBranch {
+    /// Matches replies containing any of these, ignoring case
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Matches replies matching this regex
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Node to continue with
+    pub next: String,
+}
+
+impl ScriptBranch {
+    fn matches(&self, reply: &str, regex: Option<&Regex>) -> bool {
+        let reply_lower = reply.to_lowercase();
+        let keyword = self
+            .keywords
+            .iter()
+            .any(|keyword| reply_lower.contains(&keyword.to_lowercase()));
+        match regex {
+            Some(regex) => keyword || regex.is_match(reply),
+            None => keyword || self.keywords.is_empty(),
+        }
+    }
+}
+
+/// A step of the conversation: lines to say, then where to go next
+///
+/// A node with `branches` or `otherwise` waits for a reply after its lines; a node
+/// with `next` moves on straight away; a node with neither ends the script.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ScriptNode {
+    pub lines: Vec<ScriptLine>,
+    /// Checked in order against each reply
+    pub branches: Vec<ScriptBranch>,
+    /// Node for a reply no branch matches; without it such replies are ignored
+    pub otherwise: Option<String>,
+    /// Seconds to wait for a matching reply; defaults to the script's `timeout_secs`
+    pub timeout_secs: Option<u64>,
+    /// Node to continue with when no reply matches in time; the script ends without it
+    pub on_timeout: Option<String>,
+    /// Node that follows without waiting for a reply
+    pub next: Option<String>,
+}
+
+impl ScriptNode {
+    pub fn waits_for_reply(&self) -> bool {
+        !self.branches.is_empty() || self.otherwise.is_some()
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+/// A branching transcript that reacts to the replies of the system under test
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Script {
+    /// Node the script starts at
+    pub start: String,
+    pub nodes: BTreeMap<String, ScriptNode>,
+    /// Seconds a node waits for a matching reply
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Script {
+    /// Reads a JSON script
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let script: Self = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+        script.validate()?;
+        Ok(script)
+    }
+
+    /// Speakers in order of first appearance, starting from the start node
+    pub fn speakers(&self) -> Vec<String> {
+        let start = self.nodes.get(&self.start);
+        let others = self
+            .nodes
+            .iter()
+            .filter(|(name, _)| **name != self.start)
+            .map(|(_, node)| node);
+        let mut speakers: Vec<String> = Vec::new();
+        for line in start.into_iter().chain(others).flat_map(|node| &node.lines) {
+            if !speakers.contains(&line.speaker) {
+                speakers.push(line.speaker.clone());
+            }
+        }
+        speakers
+    }
+
+    /// Checks that every node a script refers to exists and every regex compiles
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let exists = |name: &str, from: &str| {
+            if self.nodes.contains_key(name) {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("Unknown node {} in {}", name, from))
+            }
+        };
+        exists(&self.start, "start")?;
+        for (name, node) in &self.nodes {
+            if node.next.is_some() && node.waits_for_reply() {
+                return Err(anyhow::anyhow!(
+                    "Node {} has both next and branches; give one",
+                    name
+                ));
+            }
+            let targets = node
+                .branches
+                .iter()
+                .map(|branch| &branch.next)
+                .chain(&node.otherwise)
+                .chain(&node.on_timeout)
+                .chain(&node.next);
+            for target in targets {
+                exists(target, name)?;
+            }
+            for branch in &node.branches {
+                if let Some(regex) = &branch.regex {
+                    Regex::new(regex)
+                        .map_err(|e| anyhow::anyhow!("Invalid regex in node {}: {}", name, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The text of a reply: plain text, or the first of `reply`, `text`, `message`,
+/// `sentence` or `body.sentence` in a JSON object
+pub fn reply_text(raw: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(serde_json::Value::Object(object)) => ["reply", "text", "message", "sentence"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(|v| v.as_str()))
+            .or_else(|| object.get("body")?.get("sentence")?.as_str())
+            .unwrap_or(raw)
+            .to_string(),
+        _ => raw.to_string(),
+    }
+}
+
+/// Plays a script, choosing each branch from the replies received
+///
+/// Record times are the session's elapsed time. Replies that arrive before a node's
+/// last line is sent are stale and discarded; chaos options do not apply to scripts.
+pub struct ScriptPlayback {
+    script: Script,
+    regexes: HashMap<(String, usize), Regex>,
+    replies: ReplyReceiver,
+    clock: SharedClock,
+    /// Set on the first step, so time spent queued is not part of the timeline
+    started: Option<Duration>,
+    node: Option<String>,
+    line: usize,
+    index: usize,
+    visits: usize,
+    /// `reply` and named groups of the last matched reply
+    variables: HashMap<String, String>,
+}
+
+impl ScriptPlayback {
+    /// Starts a validated script
+    pub fn new(script: Script, replies: ReplyReceiver, clock: SharedClock) -> Self {
+        let mut regexes = HashMap::new();
+        for (name, node) in &script.nodes {
+            for (i, branch) in node.branches.iter().enumerate() {
+                if let Some(Ok(regex)) = branch.regex.as_deref().map(Regex::new) {
+                    regexes.insert((name.clone(), i), regex);
+                }
+            }
+        }
+        Self {
+            node: Some(script.start.clone()),
+            script,
+            regexes,
+            replies,
+            clock,
+            started: None,
+            line: 0,
+            index: 0,
+            visits: 0,
+            variables: HashMap::new(),
+        }
+    }
+
+    fn elapsed(&mut self) -> Duration {
+        let now = self.clock.elapsed();
+        now.saturating_sub(*self.started.get_or_insert(now))
+    }
+
+    fn fill(&self, sentence: &str) -> String {
+        let placeholder = Regex::new(r"\{\{\s*(\w+)\s*\}\}").expect("placeholder pattern");
+        placeholder
+            .replace_all(sentence, |caps: &regex::Captures| {
+                self.variables
+                    .get(&caps[1])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    /// Picks the node after `name` from the replies, or `None` when the script ends
+    async fn await_reply(&mut self, name: &str) -> Option<String> {
+        let node = self.script.nodes[name].clone();
+        let timeout = Duration::from_secs(node.timeout_secs.unwrap_or(self.script.timeout_secs));
+        let deadline = self.elapsed() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_sub(self.elapsed());
+            let raw = tokio::select! {
+                // A reply already received wins over the timeout
+                biased;
+                raw = self.replies.recv() => raw,
+                _ = self.clock.sleep(remaining) => None,
+            };
+            let Some(raw) = raw else {
+                tracing::info!("No matching reply in node {} within {:?}", name, timeout);
+                return node.on_timeout.clone();
+            };
+
+            let reply = reply_text(&raw);
+            if reply.trim().is_empty() {
+                continue;
+            }
+            for (i, branch) in node.branches.iter().enumerate() {
+                let regex = self.regexes.get(&(name.to_string(), i));
+                if branch.matches(&reply, regex) {
+                    self.variables.insert("reply".to_string(), reply.clone());
+                    if let Some(caps) = regex.and_then(|regex| regex.captures(&reply)) {
+                        for group in regex.into_iter().flat_map(|r| r.capture_names()).flatten() {
+                            if let Some(value) = caps.name(group) {
+                                self.variables
+                                    .insert(group.to_string(), value.as_str().to_string());
+                            }
+                        }
+                    }
+                    tracing::debug!("Reply {:?} took branch {} of node {}", reply, i, name);
+                    return Some(branch.next.clone());
+                }
+            }
+            if let Some(otherwise) = &node.otherwise {
+                self.variables.insert("reply".to_string(), reply);
+                return Some(otherwise.clone());
+            }
+            tracing::debug!("Ignoring reply {:?} in node {}", reply, name);
+        }
+    }
+
+    /// Waits until the next line is due and returns it, or `None` once the script ends
+    pub async fn next(&mut self) -> Option<Result<PlaybackStep, ChaosDisconnect>> {
+        loop {
+            let name = self.node.clone()?;
+            self.elapsed();
+            let node = self.script.nodes[&name].clone();
+            if let Some(line) = node.lines.get(self.line).cloned() {
+                if line.pause_secs > 0 {
+                    self.clock.sleep(Duration::from_secs(line.pause_secs)).await;
+                }
+                self.line += 1;
+                if self.line == node.lines.len() && node.waits_for_reply() {
+                    while self.replies.try_recv().is_ok() {}
+                }
+
+                let time = self.elapsed().as_secs();
+                let step = PlaybackStep {
+                    index: self.index,
+                    time: time as i32,
+                    record: TranscriptRecord {
+                        time: format_time(time),
+                        speaker: line.speaker,
+                        role: line.role,
+                        sentence: self.fill(&line.sentence),
+                        audio: None,
+                    },
+                };
+                self.index += 1;
+                return Some(Ok(step));
+            }
+
+            self.line = 0;
+            self.visits += 1;
+            if self.visits >= MAX_SCRIPT_NODES {
+                tracing::warn!("Script stopped after {} nodes", self.visits);
+                self.node = None;
+                return None;
+            }
+            self.node = if node.waits_for_reply() {
+                self.await_reply(&name).await
+            } else {
+                node.next
+            };
+        }
+    }
+
+    pub fn into_stream(self) -> EventStream {
+        futures_util::stream::unfold(self, |mut playback| async move {
+            playback.next().await.map(|event| (event, playback))
+        })
+        .boxed()
+    }
+}
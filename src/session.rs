@@ -0,0 +1,296 @@
+use crate::consts::SESSION_KINDS_ENV;
+use serde::de::Error as _;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Suffix turning a kind name into its serialized field, e.g. `job_description_enrichment_session`
+const FIELD_SUFFIX: &str = "_enrichment_session";
+
+/// Kinds that are always serialized, as `null` when not in use, so existing consumers keep
+/// seeing both fields
+const LEGACY_KINDS: [&str; 2] = [SessionKind::JOB_DESCRIPTION, SessionKind::CANDIDATE_PROFILE];
+
+/// Kind of enrichment session a broadcast belongs to, e.g. `job_description`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionKind(String);
+
+impl SessionKind {
+    pub const JOB_DESCRIPTION: &'static str = "job_description";
+    pub const CANDIDATE_PROFILE: &'static str = "candidate_profile";
+
+    /// Creates a kind from a lowercase snake_case name
+    pub fn new(name: &str) -> anyhow::Result<Self> {
+        let name = name.trim();
+        let name = name.strip_suffix(FIELD_SUFFIX).unwrap_or(name);
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid session kind '{}': expected lowercase snake_case",
+                name
+            ));
+        }
+        Ok(Self(name.to_string()))
+    }
+
+    pub fn job_description() -> Self {
+        Self(Self::JOB_DESCRIPTION.to_string())
+    }
+
+    pub fn candidate_profile() -> Self {
+        Self(Self::CANDIDATE_PROFILE.to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// Field name used in serialized messages
+    pub fn field_name(&self) -> String {
+        format!("{}{}", self.0, FIELD_SUFFIX)
+    }
+}
+
+impl std::fmt::Display for SessionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Session ID; numeric IDs stay numbers when serialized
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum SessionId {
+    Number(i64),
+    Text(String),
+}
+
+impl SessionId {
+    /// Parses an ID given as text, keeping it numeric only when it is written as a
+    /// canonical integer, so IDs such as `007` or `+7` survive unchanged
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(anyhow::anyhow!("Session ID must not be empty"));
+        }
+        Ok(match raw.parse::<i64>() {
+            Ok(id) if id.to_string() == raw => SessionId::Number(id),
+            _ => SessionId::Text(raw.to_string()),
+        })
+    }
+}
+
+impl From<i64> for SessionId {
+    fn from(id: i64) -> Self {
+        SessionId::Number(id)
+    }
+}
+
+impl From<String> for SessionId {
+    fn from(id: String) -> Self {
+        SessionId::Text(id)
+    }
+}
+
+impl std::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionId::Number(id) => write!(f, "{}", id),
+            SessionId::Text(id) => f.write_str(id),
+        }
+    }
+}
+
+/// Typed session a broadcast is delivered for
+///
+/// Serializes as `<kind>_enrichment_session: <id>`, alongside `null` for the other
+/// legacy kinds, so it can be flattened into messages without changing their shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionContext {
+    pub kind: SessionKind,
+    pub id: SessionId,
+}
+
+impl SessionContext {
+    pub fn new(kind: SessionKind, id: impl Into<SessionId>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+        }
+    }
+}
+
+/// Session fields of a message that may not have a session, e.g. an error response
+///
+/// Serializes every legacy field as `null` when empty; flatten it into the containing struct.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFields(pub Option<SessionContext>);
+
+impl Serialize for SessionFields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_session_fields(self.0.as_ref(), serializer)
+    }
+}
+
+fn serialize_session_fields<S: Serializer>(
+    session: Option<&SessionContext>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(None)?;
+    for legacy in LEGACY_KINDS {
+        let id = session.filter(|s| s.kind.name() == legacy).map(|s| &s.id);
+        map.serialize_entry(&format!("{}{}", legacy, FIELD_SUFFIX), &id)?;
+    }
+    if let Some(session) = session
+        && !LEGACY_KINDS.contains(&session.kind.name())
+    {
+        map.serialize_entry(&session.kind.field_name(), &session.id)?;
+    }
+    map.end()
+}
+
+impl Serialize for SessionContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_session_fields(Some(self), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionContext {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        let mut sessions = fields.into_iter().filter_map(|(field, value)| {
+            let kind = field.strip_suffix(FIELD_SUFFIX)?.to_string();
+            (!value.is_null()).then_some((kind, value))
+        });
+
+        let (kind, id) = sessions
+            .next()
+            .ok_or_else(|| D::Error::custom("no enrichment session field set"))?;
+        if sessions.next().is_some() {
+            return Err(D::Error::custom(
+                "more than one enrichment session field set",
+            ));
+        }
+
+        Ok(Self {
+            kind: SessionKind::new(&kind).map_err(D::Error::custom)?,
+            id: SessionId::deserialize(id).map_err(D::Error::custom)?,
+        })
+    }
+}
+
+/// Session kinds accepted by this instance
+///
+/// Always includes `job_description` and `candidate_profile`; more can be added with a
+/// comma-separated list in `RESTREAM_SESSION_KINDS`.
+#[derive(Debug, Clone)]
+pub struct SessionKinds {
+    kinds: Vec<SessionKind>,
+}
+
+impl Default for SessionKinds {
+    fn default() -> Self {
+        Self {
+            kinds: vec![
+                SessionKind::job_description(),
+                SessionKind::candidate_profile(),
+            ],
+        }
+    }
+}
+
+impl SessionKinds {
+    /// The default kinds plus `extra`
+    pub fn new<I, T>(extra: I) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let mut kinds = Self::default();
+        for name in extra {
+            if name.as_ref().trim().is_empty() {
+                continue;
+            }
+            let kind = SessionKind::new(name.as_ref())?;
+            if !kinds.kinds.contains(&kind) {
+                kinds.kinds.push(kind);
+            }
+        }
+        Ok(kinds)
+    }
+
+    /// The default kinds plus those listed in `RESTREAM_SESSION_KINDS`
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(SESSION_KINDS_ENV) {
+            Ok(value) => Self::new(value.split(',')),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SessionKind> {
+        self.kinds.iter()
+    }
+
+    /// Looks up a configured kind by name or field name
+    pub fn get(&self, name: &str) -> anyhow::Result<SessionKind> {
+        let kind = SessionKind::new(name)?;
+        if !self.kinds.contains(&kind) {
+            return Err(anyhow::anyhow!(
+                "Unknown session kind '{}': expected one of {}",
+                kind,
+                self.kinds
+                    .iter()
+                    .map(|k| k.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        Ok(kind)
+    }
+}
+
+/// Session parameters as accepted by the API and the CLI
+///
+/// The legacy per-kind parameters remain; any configured kind can be given with
+/// `session_kind` plus `session_id`. Exactly one session must be provided.
+#[derive(Debug, Clone, Default)]
+pub struct SessionParams {
+    pub job_description_enrichment_session: Option<String>,
+    pub candidate_profile_enrichment_session: Option<String>,
+    pub session_kind: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl SessionParams {
+    pub fn resolve(self, kinds: &SessionKinds) -> anyhow::Result<SessionContext> {
+        let mut provided = Vec::new();
+        if let Some(id) = self.job_description_enrichment_session {
+            provided.push((SessionKind::job_description(), id));
+        }
+        if let Some(id) = self.candidate_profile_enrichment_session {
+            provided.push((SessionKind::candidate_profile(), id));
+        }
+        match (self.session_kind, self.session_id) {
+            (Some(kind), Some(id)) => provided.push((kinds.get(&kind)?, id)),
+            (Some(_), None) => return Err(anyhow::anyhow!("session_kind requires session_id")),
+            (None, Some(_)) => return Err(anyhow::anyhow!("session_id requires session_kind")),
+            (None, None) => {}
+        }
+
+        match provided.len() {
+            0 => Err(anyhow::anyhow!(
+                "No session type provided - exactly one required"
+            )),
+            1 => {
+                let (kind, id) = provided.remove(0);
+                Ok(SessionContext::new(kind, SessionId::parse(&id)?))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Multiple session types provided - only one allowed"
+            )),
+        }
+    }
+}
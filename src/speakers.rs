@@ -0,0 +1,152 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::load_transcript_from_file;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// What a transcript's speaker stands for
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Object)]
+pub struct SpeakerInfo {
+    /// Role sent with each message, e.g. `recruiter` or `candidate`
+    pub role: Option<String>,
+    /// Name sent instead of the transcript's speaker name
+    pub display_name: Option<String>,
+}
+
+/// Speaker names in a transcript mapped to their roles and display names
+pub type SpeakerMetadata = HashMap<String, SpeakerInfo>;
+
+/// Sidecar holding a transcript's speaker metadata: `intake_call.csv` → `intake_call.speakers.json`
+pub fn sidecar_path(transcript: &Path) -> PathBuf {
+    let stem = transcript
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    transcript.with_file_name(format!("{}.speakers.json", stem))
+}
+
+/// Loads a transcript's speaker sidecar; a missing sidecar means no metadata
+pub fn load_sidecar(transcript: &Path) -> anyhow::Result<SpeakerMetadata> {
+    let path = sidecar_path(transcript);
+    if !path.exists() {
+        return Ok(SpeakerMetadata::new());
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))
+}
+
+/// Per-broadcast speaker changes, keyed by the transcript's speaker names
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Object)]
+#[serde(default)]
+#[oai(default)]
+pub struct SpeakerOverrides {
+    /// New names; these win over display names and anonymization
+    pub rename: HashMap<String, String>,
+    /// Roles, overriding the transcript and its sidecar
+    pub roles: HashMap<String, String>,
+    /// Replace names without a `rename` entry with `Speaker 1`, `Speaker 2`, ... in order of appearance
+    pub anonymize: bool,
+}
+
+fn parse_pairs(pairs: &[String], what: &str) -> anyhow::Result<HashMap<String, String>> {
+    pairs
+        .iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() && !value.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(anyhow::anyhow!("Expected speaker={}: {}", what, pair)),
+        })
+        .collect()
+}
+
+impl SpeakerOverrides {
+    /// Builds overrides from `name=new name` and `name=role` pairs
+    pub fn parse(rename: &[String], roles: &[String], anonymize: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            rename: parse_pairs(rename, "name")?,
+            roles: parse_pairs(roles, "role")?,
+            anonymize,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rename.is_empty() && self.roles.is_empty() && !self.anonymize
+    }
+}
+
+/// Applies speaker metadata and overrides to records
+///
+/// Anonymous names are numbered once per mapper, so transcripts mapped one after
+/// another (as in a playlist) keep a speaker's number.
+#[derive(Debug, Clone, Default)]
+pub struct SpeakerMapper {
+    overrides: SpeakerOverrides,
+    anonymous: HashMap<String, String>,
+}
+
+impl SpeakerMapper {
+    pub fn new(overrides: SpeakerOverrides) -> Self {
+        Self {
+            overrides,
+            anonymous: HashMap::new(),
+        }
+    }
+
+    /// Every role label known for a transcript: the overrides', its metadata's and its records' own
+    pub fn roles(&self, metadata: &SpeakerMetadata, records: &[TranscriptRecord]) -> Vec<String> {
+        self.overrides
+            .roles
+            .values()
+            .chain(metadata.values().filter_map(|info| info.role.as_ref()))
+            .chain(records.iter().filter_map(|record| record.role.as_ref()))
+            .cloned()
+            .collect()
+    }
+
+    /// Sets each record's name and role from, in order of precedence, the overrides, the
+    /// transcript's metadata and the record itself
+    pub fn apply(&mut self, metadata: &SpeakerMetadata, records: &mut [TranscriptRecord]) {
+        for record in records {
+            let info = metadata.get(&record.speaker);
+            let role = self
+                .overrides
+                .roles
+                .get(&record.speaker)
+                .cloned()
+                .or_else(|| info.and_then(|info| info.role.clone()));
+            if role.is_some() {
+                record.role = role;
+            }
+
+            let name = match self.overrides.rename.get(&record.speaker) {
+                Some(name) => name.clone(),
+                None if self.overrides.anonymize => {
+                    let next = self.anonymous.len() + 1;
+                    self.anonymous
+                        .entry(record.speaker.clone())
+                        .or_insert_with(|| format!("Speaker {}", next))
+                        .clone()
+                }
+                None => match info.and_then(|info| info.display_name.clone()) {
+                    Some(name) => name,
+                    None => continue,
+                },
+            };
+            record.speaker = name;
+        }
+    }
+}
+
+/// Loads a transcript and maps its speakers through its sidecar and `mapper`
+pub async fn load_with_speakers(
+    path: &Path,
+    mapper: &mut SpeakerMapper,
+) -> anyhow::Result<Vec<TranscriptRecord>> {
+    let mut records = load_transcript_from_file(path).await?;
+    let metadata = load_sidecar(path)?;
+    mapper.apply(&metadata, &mut records);
+    Ok(records)
+}
@@ -0,0 +1,194 @@
+use crate::interface::TranscriptRecord;
+use crate::transcript::parse_time_to_time;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// Nominal speaking rate used to estimate how long an utterance lasts
+pub const SPEECH_WORDS_PER_MINUTE: f64 = 150.0;
+
+/// Talk figures for one speaker
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct SpeakerStats {
+    pub speaker: String,
+    pub records: usize,
+    /// Runs of consecutive records by this speaker
+    pub turns: usize,
+    pub words: usize,
+    /// Estimated time spent talking, in seconds
+    pub talk_secs: f64,
+    /// Share of the transcript's talk time, 0.0 - 1.0
+    pub talk_share: f64,
+    /// Words per minute of talk time
+    pub words_per_minute: f64,
+    /// Average turn length, in seconds
+    pub average_turn_secs: f64,
+}
+
+/// Activity within one slice of the timeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct TimelineBucket {
+    /// Offset the bucket starts at, in seconds
+    pub start_secs: u64,
+    /// Records starting within the bucket
+    pub records: usize,
+    pub words: usize,
+}
+
+/// Conversation analytics for one transcript
+///
+/// Records only carry a start time, so each utterance is assumed to last as long as its
+/// words take at `SPEECH_WORDS_PER_MINUTE`, cut short by the next record. Silences are
+/// the rest of the gap; an overlap is a speaker change that comes before the previous
+/// utterance could have finished.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Object)]
+pub struct TranscriptStats {
+    pub records: usize,
+    /// From the first record to the estimated end of the last, in seconds
+    pub duration_secs: f64,
+    pub words: usize,
+    /// Words per minute over the whole duration
+    pub words_per_minute: f64,
+    pub turns: usize,
+    /// Average turn length, in seconds of talk
+    pub average_turn_secs: f64,
+    pub average_turn_words: f64,
+    /// Longest pause between the end of one utterance and the start of the next, in seconds
+    pub longest_silence_secs: f64,
+    /// 1-based row after which the longest silence falls
+    pub longest_silence_after_row: Option<usize>,
+    /// Speaker changes that interrupt the previous utterance
+    pub overlaps: usize,
+    /// Speakers in order of first appearance
+    pub speakers: Vec<SpeakerStats>,
+    /// Records and words per `bucket_secs` slice of the timeline
+    pub timeline: Vec<TimelineBucket>,
+}
+
+fn word_count(sentence: &str) -> usize {
+    sentence.split_whitespace().count()
+}
+
+/// Estimated time an utterance takes to say, in seconds
+pub fn utterance_secs(sentence: &str) -> f64 {
+    word_count(sentence) as f64 * 60.0 / SPEECH_WORDS_PER_MINUTE
+}
+
+fn per_minute(words: usize, secs: f64) -> f64 {
+    if secs > 0.0 {
+        words as f64 * 60.0 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Rounds to hundredths, which is all the precision whole-second timestamps support
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl TranscriptStats {
+    /// Computes the statistics, with timeline buckets of `bucket_secs` (at least 1)
+    pub fn compute(records: &[TranscriptRecord], bucket_secs: u64) -> Self {
+        let bucket_secs = bucket_secs.max(1);
+        let times: Vec<f64> = records
+            .iter()
+            .map(|record| parse_time_to_time(&record.time) as f64)
+            .collect();
+        let words: Vec<usize> = records
+            .iter()
+            .map(|record| word_count(&record.sentence))
+            .collect();
+        let start = times.first().copied().unwrap_or(0.0);
+
+        let mut speakers: Vec<SpeakerStats> = Vec::new();
+        let mut end = start;
+        let mut turns = 0;
+        let mut overlaps = 0;
+        let mut longest_silence: Option<(f64, usize)> = None;
+        let mut timeline: Vec<TimelineBucket> = Vec::new();
+
+        for (index, record) in records.iter().enumerate() {
+            let spoken = utterance_secs(&record.sentence);
+            let talk = match times.get(index + 1) {
+                Some(next) => {
+                    let gap = (next - times[index]).max(0.0);
+                    let changes_speaker = records[index + 1].speaker != record.speaker;
+                    if spoken > gap && changes_speaker {
+                        overlaps += 1;
+                    }
+                    let silence = gap - spoken;
+                    if silence > longest_silence.map_or(0.0, |(secs, _)| secs) {
+                        longest_silence = Some((silence, index + 1));
+                    }
+                    spoken.min(gap)
+                }
+                None => spoken,
+            };
+            end = end.max(times[index] + talk);
+
+            let new_turn = index == 0 || records[index - 1].speaker != record.speaker;
+            turns += usize::from(new_turn);
+            let position = match speakers.iter().position(|s| s.speaker == record.speaker) {
+                Some(position) => position,
+                None => {
+                    speakers.push(SpeakerStats {
+                        speaker: record.speaker.clone(),
+                        records: 0,
+                        turns: 0,
+                        words: 0,
+                        talk_secs: 0.0,
+                        talk_share: 0.0,
+                        words_per_minute: 0.0,
+                        average_turn_secs: 0.0,
+                    });
+                    speakers.len() - 1
+                }
+            };
+            let speaker = &mut speakers[position];
+            speaker.records += 1;
+            speaker.turns += usize::from(new_turn);
+            speaker.words += words[index];
+            speaker.talk_secs += talk;
+
+            let bucket = ((times[index] - start).max(0.0) as u64 / bucket_secs) as usize;
+            while timeline.len() <= bucket {
+                timeline.push(TimelineBucket {
+                    start_secs: timeline.len() as u64 * bucket_secs,
+                    records: 0,
+                    words: 0,
+                });
+            }
+            timeline[bucket].records += 1;
+            timeline[bucket].words += words[index];
+        }
+
+        let total_words: usize = words.iter().sum();
+        let total_talk: f64 = speakers.iter().map(|s| s.talk_secs).sum();
+        for speaker in &mut speakers {
+            speaker.talk_share = if total_talk > 0.0 {
+                round(speaker.talk_secs / total_talk)
+            } else {
+                0.0
+            };
+            speaker.words_per_minute = round(per_minute(speaker.words, speaker.talk_secs));
+            speaker.average_turn_secs = round(speaker.talk_secs / speaker.turns.max(1) as f64);
+            speaker.talk_secs = round(speaker.talk_secs);
+        }
+
+        let duration_secs = end - start;
+        Self {
+            records: records.len(),
+            duration_secs: round(duration_secs),
+            words: total_words,
+            words_per_minute: round(per_minute(total_words, duration_secs)),
+            turns,
+            average_turn_secs: round(total_talk / turns.max(1) as f64),
+            average_turn_words: round(total_words as f64 / turns.max(1) as f64),
+            longest_silence_secs: round(longest_silence.map_or(0.0, |(secs, _)| secs)),
+            longest_silence_after_row: longest_silence.map(|(_, row)| row),
+            overlaps,
+            speakers,
+            timeline,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::interface::{BroadcastMessage, SessionContext, SessionId};
+use crate::playback::PlaybackStep;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Value a placeholder can stand for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Speaker,
+    Role,
+    Sentence,
+    Time,
+    Offset,
+    Index,
+    SessionKind,
+    SessionId,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "speaker" => Field::Speaker,
+            "role" => Field::Role,
+            "sentence" => Field::Sentence,
+            "time" => Field::Time,
+            "offset" => Field::Offset,
+            "index" => Field::Index,
+            "session_kind" => Field::SessionKind,
+            "session_id" => Field::SessionId,
+            _ => return None,
+        })
+    }
+
+    /// Whether the field only exists for a transcript record
+    fn is_record_field(&self) -> bool {
+        !matches!(self, Field::SessionKind | Field::SessionId)
+    }
+}
+
+/// Where a template is used, which decides the placeholders it may contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateScope {
+    /// One payload per record: every placeholder is available
+    Record,
+    /// The completion message: only `session_kind` and `session_id`
+    Completion,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Object(Vec<(Vec<Segment>, Node)>),
+    Array(Vec<Node>),
+    Text(Vec<Segment>),
+    Value(Value),
+}
+
+/// Values available to a template when it is rendered
+pub struct TemplateContext<'a> {
+    pub session: &'a SessionContext,
+    pub step: Option<&'a PlaybackStep>,
+}
+
+/// A JSON payload with `{{placeholder}}` strings
+///
+/// Placeholders: `speaker`, `role` (null when unknown), `sentence`, `time`, `offset` (seconds), `index`,
+/// `session_kind` and `session_id`. A string that is exactly one placeholder keeps
+/// the value's JSON type, e.g. `"{{index}}"` renders as a number; placeholders may
+/// also appear inside longer strings and in object keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadTemplate {
+    root: Node,
+}
+
+fn parse_segments(text: &str, scope: TemplateScope) -> anyhow::Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Literal(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| anyhow::anyhow!("Unclosed placeholder in \"{}\"", text))?;
+        let name = after[..end].trim();
+        let field = Field::parse(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown placeholder {{{{{}}}}}", name))?;
+        if scope == TemplateScope::Completion && field.is_record_field() {
+            return Err(anyhow::anyhow!(
+                "Placeholder {{{{{}}}}} is not available in completion templates",
+                name
+            ));
+        }
+        segments.push(Segment::Field(field));
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest.to_string()));
+    }
+    Ok(segments)
+}
+
+fn parse_node(value: &Value, scope: TemplateScope) -> anyhow::Result<Node> {
+    Ok(match value {
+        Value::Object(map) => Node::Object(
+            map.iter()
+                .map(|(key, value)| Ok((parse_segments(key, scope)?, parse_node(value, scope)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Array(items) => Node::Array(
+            items
+                .iter()
+                .map(|item| parse_node(item, scope))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::String(text) => Node::Text(parse_segments(text, scope)?),
+        other => Node::Value(other.clone()),
+    })
+}
+
+impl PayloadTemplate {
+    /// Parses and validates a template
+    pub fn parse(value: &Value, scope: TemplateScope) -> anyhow::Result<Self> {
+        if !value.is_object() {
+            return Err(anyhow::anyhow!("Template must be a JSON object"));
+        }
+        Ok(Self {
+            root: parse_node(value, scope)?,
+        })
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> Value {
+        render_node(&self.root, context)
+    }
+}
+
+fn field_value(field: Field, context: &TemplateContext) -> Value {
+    let step = context.step;
+    match field {
+        Field::Speaker => step.map_or(Value::Null, |s| s.record.speaker.clone().into()),
+        Field::Role => step
+            .and_then(|s| s.record.role.clone())
+            .map_or(Value::Null, Value::from),
+        Field::Sentence => step.map_or(Value::Null, |s| s.record.sentence.clone().into()),
+        Field::Time => step.map_or(Value::Null, |s| s.record.time.clone().into()),
+        Field::Offset => step.map_or(Value::Null, |s| s.time.into()),
+        Field::Index => step.map_or(Value::Null, |s| s.index.into()),
+        Field::SessionKind => context.session.kind.name().into(),
+        Field::SessionId => match &context.session.id {
+            SessionId::Number(id) => (*id).into(),
+            SessionId::Text(id) => id.clone().into(),
+        },
+    }
+}
+
+fn render_text(segments: &[Segment], context: &TemplateContext) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Literal(text) => text.clone(),
+            Segment::Field(field) => match field_value(*field, context) {
+                Value::String(text) => text,
+                Value::Null => String::new(),
+                other => other.to_string(),
+            },
+        })
+        .collect()
+}
+
+fn render_node(node: &Node, context: &TemplateContext) -> Value {
+    match node {
+        Node::Object(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (render_text(key, context), render_node(value, context)))
+                .collect::<Map<_, _>>(),
+        ),
+        Node::Array(items) => Value::Array(items.iter().map(|i| render_node(i, context)).collect()),
+        Node::Text(segments) => match segments.as_slice() {
+            [Segment::Field(field)] => field_value(*field, context),
+            _ => Value::String(render_text(segments, context)),
+        },
+        Node::Value(value) => value.clone(),
+    }
+}
+
+/// Payload shapes used by one webhook target; `None` keeps restream's default shape
+#[derive(Debug, Clone, Default)]
+pub struct WebhookTemplates {
+    pub payload: Option<PayloadTemplate>,
+    pub completion: Option<PayloadTemplate>,
+}
+
+impl WebhookTemplates {
+    /// Payload for one record
+    pub fn payload(&self, session: &SessionContext, step: &PlaybackStep) -> Value {
+        match &self.payload {
+            Some(template) => template.render(&TemplateContext {
+                session,
+                step: Some(step),
+            }),
+            None => serde_json::to_value(BroadcastMessage::new(session, step.record.clone()))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Message sent once every record was delivered
+    pub fn completion(&self, session: &SessionContext) -> Value {
+        match &self.completion {
+            Some(template) => template.render(&TemplateContext {
+                session,
+                step: None,
+            }),
+            None => serde_json::json!({
+                "status": "complete",
+                "message": "Broadcast completed"
+            }),
+        }
+    }
+}
+
+/// A named webhook destination as written in the targets file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookTargetConfig {
+    /// Webhook URL
+    pub url: String,
+    /// Template for each record's payload
+    #[serde(default)]
+    pub payload: Option<Value>,
+    /// Template for the completion message
+    #[serde(default)]
+    pub completion: Option<Value>,
+}
+
+/// A validated webhook destination
+#[derive(Debug, Clone)]
+pub struct WebhookTarget {
+    pub name: String,
+    pub url: String,
+    pub templates: WebhookTemplates,
+}
+
+impl WebhookTarget {
+    pub fn from_config(name: &str, config: &WebhookTargetConfig) -> anyhow::Result<Self> {
+        let url = reqwest::Url::parse(&config.url)
+            .map_err(|e| anyhow::anyhow!("Webhook target {}: invalid url: {}", name, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow::anyhow!(
+                "Webhook target {}: url must be http:// or https://",
+                name
+            ));
+        }
+
+        let parse = |template: &Option<Value>, scope, what| {
+            template
+                .as_ref()
+                .map(|t| PayloadTemplate::parse(t, scope))
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Webhook target {}: {} template: {}", name, what, e))
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            url: config.url.clone(),
+            templates: WebhookTemplates {
+                payload: parse(&config.payload, TemplateScope::Record, "payload")?,
+                completion: parse(&config.completion, TemplateScope::Completion, "completion")?,
+            },
+        })
+    }
+}
+
+/// Named webhook targets, loaded and validated once at startup
+#[derive(Debug, Clone, Default)]
+pub struct WebhookTargets {
+    targets: BTreeMap<String, WebhookTarget>,
+}
+
+impl WebhookTargets {
+    /// Loads a JSON object of `name -> WebhookTargetConfig`; a missing file means no targets
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        let configs: BTreeMap<String, WebhookTargetConfig> = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+        let targets = configs
+            .iter()
+            .map(|(name, config)| Ok((name.clone(), WebhookTarget::from_config(name, config)?)))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { targets })
+    }
+
+    pub fn get(&self, name: &str) -> anyhow::Result<&WebhookTarget> {
+        self.targets
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown webhook target: {}", name))
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+}
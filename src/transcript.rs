@@ -0,0 +1,138 @@
+use crate::interface::{TranscriptFile, TranscriptRecord};
+use std::fs;
+use std::path::Path;
+
+/// Parses HH:MM:SS, MM:SS or plain seconds into total seconds; unparseable parts count as 0
+pub fn parse_time_to_time(time_str: &str) -> i32 {
+    let parts: Vec<&str> = time_str.split(':').collect();
+
+    match parts.len() {
+        3 => {
+            // HH:MM:SS format
+            let hours = parts[0].parse::<i32>().unwrap_or(0);
+            let minutes = parts[1].parse::<i32>().unwrap_or(0);
+            let time = parts[2].parse::<i32>().unwrap_or(0);
+            hours * 3600 + minutes * 60 + time
+        }
+        2 => {
+            // MM:SS format
+            let minutes = parts[0].parse::<i32>().unwrap_or(0);
+            let time = parts[1].parse::<i32>().unwrap_or(0);
+            minutes * 60 + time
+        }
+        1 => {
+            // Just time
+            parts[0].parse::<i32>().unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
+
+/// Formats a number of seconds as HH:MM:SS
+pub fn format_time(total_secs: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        total_secs / 3600,
+        (total_secs % 3600) / 60,
+        total_secs % 60
+    )
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("json")
+}
+
+/// Loads a transcript from a `.csv` file, or from a `.json` array of records
+pub async fn load_transcript_from_file(path: &Path) -> anyhow::Result<Vec<TranscriptRecord>> {
+    let contents = fs::read_to_string(path)?;
+
+    if is_json(path) {
+        return Ok(serde_json::from_str(&contents)?);
+    }
+
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let mut transcripts = Vec::new();
+
+    for result in reader.deserialize() {
+        let record: TranscriptRecord = result?;
+        transcripts.push(record);
+    }
+
+    Ok(transcripts)
+}
+
+/// Loads every `.csv` transcript in a directory, logging and skipping files that fail to parse
+pub async fn load_all_transcripts(transcript_dir: &Path) -> anyhow::Result<Vec<TranscriptFile>> {
+    if !transcript_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut transcript_files = Vec::new();
+    let entries = fs::read_dir(transcript_dir)?;
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("csv")
+            && let Some(filename) = path.file_name().and_then(|s| s.to_str())
+        {
+            match load_transcript_from_file(&path).await {
+                Ok(records) => {
+                    transcript_files.push(TranscriptFile {
+                        filename: filename.to_string(),
+                        records,
+                    });
+                }
+                Err(e) => {
+                    tracing::error!("Error loading {}: {}", filename, e);
+                }
+            }
+        }
+    }
+
+    Ok(transcript_files)
+}
+
+/// Writes records as a `.csv` transcript, or a `.json` array of records, refusing to overwrite
+pub fn write_transcript_to_file(path: &Path, records: &[TranscriptRecord]) -> anyhow::Result<()> {
+    if path.exists() {
+        return Err(anyhow::anyhow!(
+            "Transcript already exists: {}",
+            path.display()
+        ));
+    }
+
+    if is_json(path) {
+        fs::write(path, serde_json::to_string_pretty(records)?)?;
+        return Ok(());
+    }
+
+    // The role and audio columns are only written when some record has one
+    let with_roles = records.iter().any(|record| record.role.is_some());
+    let with_audio = records.iter().any(|record| record.audio.is_some());
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = vec!["time", "speaker"];
+    if with_roles {
+        header.push("role");
+    }
+    header.push("sentence");
+    if with_audio {
+        header.push("audio");
+    }
+    writer.write_record(header)?;
+    for record in records {
+        let mut row = vec![record.time.as_str(), record.speaker.as_str()];
+        if with_roles {
+            row.push(record.role.as_deref().unwrap_or_default());
+        }
+        row.push(record.sentence.as_str());
+        if with_audio {
+            row.push(record.audio.as_deref().unwrap_or_default());
+        }
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
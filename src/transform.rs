@@ -0,0 +1,44 @@
+use crate::interface::TranscriptRecord;
+use crate::redact::{RedactionOptions, Redactor};
+use crate::speakers::{SpeakerMapper, SpeakerMetadata, SpeakerOverrides, load_sidecar};
+use crate::transcript::load_transcript_from_file;
+use std::path::Path;
+
+/// Transforms applied to transcripts between loading and broadcasting
+///
+/// Speakers are mapped first, then PII is redacted. Names for redaction are taken
+/// from the speaker column as loaded, so renamed speakers are still redacted where
+/// they are mentioned. Use one pipeline per broadcast to keep tokens consistent.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptTransforms {
+    pub speakers: SpeakerMapper,
+    pub redactor: Option<Redactor>,
+}
+
+impl TranscriptTransforms {
+    pub fn new(speakers: SpeakerOverrides, redaction: Option<RedactionOptions>) -> Self {
+        Self {
+            speakers: SpeakerMapper::new(speakers),
+            redactor: redaction.map(Redactor::new),
+        }
+    }
+
+    /// Applies the transforms to a transcript's records, as loaded
+    pub fn apply(&mut self, metadata: &SpeakerMetadata, records: &mut [TranscriptRecord]) {
+        if let Some(redactor) = &mut self.redactor {
+            redactor.learn_names(records, &self.speakers.roles(metadata, records));
+        }
+        self.speakers.apply(metadata, records);
+        if let Some(redactor) = &mut self.redactor {
+            redactor.apply(records);
+        }
+    }
+
+    /// Loads a transcript and applies the transforms, using its speaker sidecar
+    pub async fn load(&mut self, path: &Path) -> anyhow::Result<Vec<TranscriptRecord>> {
+        let mut records = load_transcript_from_file(path).await?;
+        let metadata = load_sidecar(path)?;
+        self.apply(&metadata, &mut records);
+        Ok(records)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::chaos::ChaosDisconnect;
+use crate::clock::SharedClock;
+use crate::interface::TranscriptRecord;
+use crate::playback::{EventStream, Playback, PlaybackStep};
+use crate::script::{ReplyReceiver, reply_text};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_reply_speaker() -> String {
+    "Agent".to_string()
+}
+
+/// Interactive session mode: which records wait for the agent, and what becomes of its replies
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TurnTakingOptions {
+    /// Speakers, by name or role, whose records wait for a reply
+    pub speakers: Vec<String>,
+    /// Seconds to wait for a reply before playback carries on
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Send each reply back as a record of its own
+    #[serde(default)]
+    pub echo_replies: bool,
+    /// Speaker of echoed replies
+    #[serde(default = "default_reply_speaker")]
+    pub reply_speaker: String,
+}
+
+impl TurnTakingOptions {
+    /// Builds options from query parameters; interactive mode is on when a speaker is given
+    pub fn from_params(
+        speakers: Vec<String>,
+        timeout_secs: Option<u64>,
+        echo_replies: bool,
+        reply_speaker: Option<String>,
+    ) -> Option<Self> {
+        if speakers.is_empty() {
+            return None;
+        }
+        Some(Self {
+            speakers,
+            timeout_secs: timeout_secs.unwrap_or_else(default_timeout_secs),
+            echo_replies,
+            reply_speaker: reply_speaker.unwrap_or_else(default_reply_speaker),
+        })
+    }
+
+    /// Whether a record hands the turn to the agent
+    pub fn takes_turn(&self, record: &TranscriptRecord) -> bool {
+        self.speakers.iter().any(|speaker| {
+            speaker.eq_ignore_ascii_case(&record.speaker)
+                || record
+                    .role
+                    .as_deref()
+                    .is_some_and(|role| speaker.eq_ignore_ascii_case(role))
+        })
+    }
+}
+
+/// Whether a message only acknowledges a turn: `ack`, or a JSON object with `"type": "ack"`
+pub fn is_ack(raw: &str) -> bool {
+    let raw = raw.trim();
+    raw.eq_ignore_ascii_case("ack")
+        || serde_json::from_str::<serde_json::Value>(raw)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(|kind| kind == "ack"))
+            .unwrap_or(false)
+}
+
+/// Pauses a playback after each turn-taking record until the agent replies
+///
+/// Waiting shifts the rest of the transcript back, as gaps between records are kept
+/// from the reply on. Replies received before the record is sent are stale and
+/// discarded. An echoed reply keeps the index and time of the record it answers.
+pub struct TurnTaking {
+    playback: Playback,
+    options: TurnTakingOptions,
+    replies: ReplyReceiver,
+    clock: SharedClock,
+    /// Record the agent has yet to answer
+    waiting: Option<PlaybackStep>,
+}
+
+impl TurnTaking {
+    pub fn new(
+        playback: Playback,
+        options: TurnTakingOptions,
+        replies: ReplyReceiver,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            playback,
+            options,
+            replies,
+            clock,
+            waiting: None,
+        }
+    }
+
+    /// Waits for a reply or acknowledgement, returning the reply text to echo
+    async fn await_reply(&mut self, turn: &PlaybackStep) -> Option<String> {
+        let timeout = Duration::from_secs(self.options.timeout_secs);
+        let raw = tokio::select! {
+            // A reply already received wins over the timeout
+            biased;
+            raw = self.replies.recv() => raw,
+            _ = self.clock.sleep(timeout) => None,
+        };
+        let Some(raw) = raw else {
+            tracing::info!(
+                "No reply to record {} within {:?}, carrying on",
+                turn.index,
+                timeout
+            );
+            return None;
+        };
+        if is_ack(&raw) {
+            return None;
+        }
+        let reply = reply_text(&raw);
+        (!reply.trim().is_empty()).then_some(reply)
+    }
+
+    /// Waits until the next record or echoed reply is due, or `None` once playback is done
+    pub async fn next(&mut self) -> Option<Result<PlaybackStep, ChaosDisconnect>> {
+        if let Some(turn) = self.waiting.take()
+            && let Some(reply) = self.await_reply(&turn).await
+            && self.options.echo_replies
+        {
+            return Some(Ok(PlaybackStep {
+                index: turn.index,
+                time: turn.time,
+                record: TranscriptRecord {
+                    time: turn.record.time,
+                    speaker: self.options.reply_speaker.clone(),
+                    role: None,
+                    sentence: reply,
+                    audio: None,
+                },
+            }));
+        }
+
+        let event = self.playback.next().await?;
+        if let Ok(step) = &event
+            && self.options.takes_turn(&step.record)
+        {
+            while self.replies.try_recv().is_ok() {}
+            self.waiting = Some(step.clone());
+        }
+        Some(event)
+    }
+
+    pub fn into_stream(self) -> EventStream {
+        futures_util::stream::unfold(self, |mut turns| async move {
+            turns.next().await.map(|event| (event, turns))
+        })
+        .boxed()
+    }
+}
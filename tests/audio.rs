@@ -0,0 +1,267 @@
+mod common;
+
+use common::record;
+use futures_util::sink;
+use restream::adapter::{AudioMessage, RewindSession, SessionStore, stream_audio_session};
+use restream::audio::{
+    AudioEncoding, AudioEvent, AudioOptions, AudioPlayback, AudioSource, AudioTrack, mulaw,
+};
+use restream::chaos::ChaosOptions;
+use restream::clock::{Clock, VirtualClock};
+use restream::interface::{DeliveryState, SessionContext, SessionKind, TranscriptRecord};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// A record spoken from a WAV file
+fn spoken(sentence: &str, audio: &str) -> TranscriptRecord {
+    TranscriptRecord {
+        audio: Some(audio.to_string()),
+        ..record("00:00:00", "Mike", sentence)
+    }
+}
+
+/// Two seconds of speech, a gap, then under a second
+fn records() -> Vec<TranscriptRecord> {
+    vec![
+        record("00:00:00", "Mike", "one two three four five"),
+        record("00:00:05", "Mike", "six seven"),
+    ]
+}
+
+/// 1 kHz with 100 ms frames, so a frame is 100 samples
+fn options(encoding: AudioEncoding, source: AudioSource) -> AudioOptions {
+    AudioOptions::from_params(
+        Some(encoding),
+        Some(source),
+        Some(1_000),
+        Some(100),
+        Some(50.0),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+#[test]
+fn mulaw_matches_g711() {
+    assert_eq!(mulaw(0), 0xFF);
+    assert_eq!(mulaw(-1), 0x7F);
+    assert_eq!(mulaw(i16::MAX), 0x80);
+    assert_eq!(mulaw(i16::MIN), 0x00);
+}
+
+#[test]
+fn options_default_by_encoding_and_are_validated() {
+    assert_eq!(
+        AudioOptions::from_params(None, Some(AudioSource::Tone), None, None, None).unwrap(),
+        None
+    );
+    let mulaw = AudioOptions::from_params(Some(AudioEncoding::Mulaw), None, None, None, None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(mulaw.sample_rate, 8_000);
+    assert_eq!(mulaw.source, AudioSource::Silence);
+    assert_eq!(mulaw.frame_samples(), 160);
+
+    assert!(
+        AudioOptions::from_params(Some(AudioEncoding::Pcm), None, None, Some(0), None).is_err()
+    );
+    assert!(
+        AudioOptions::from_params(Some(AudioEncoding::Mulaw), None, None, None, Some(5_000.0))
+            .is_err()
+    );
+}
+
+#[test]
+fn track_sounds_each_utterance_and_is_silent_between() {
+    let dir = Path::new("transcript");
+    let silence = AudioTrack::load(
+        &records(),
+        options(AudioEncoding::Pcm, AudioSource::Silence),
+        dir,
+    )
+    .unwrap();
+    assert_eq!(silence.len(), 5_800);
+    assert_eq!(silence.end_secs(0), 2);
+    assert_eq!(silence.end_secs(1), 6);
+    let frame = silence.frame(0, 100);
+    assert_eq!(frame.len(), 200);
+    assert!(frame.iter().all(|&b| b == 0));
+
+    let tone = AudioTrack::load(
+        &records(),
+        options(AudioEncoding::Mulaw, AudioSource::Tone),
+        dir,
+    )
+    .unwrap();
+    assert_eq!(tone.frame(0, 100).len(), 100);
+    assert!(tone.frame(0, 100).iter().any(|&b| b != mulaw(0)));
+    // Between the utterances
+    assert!(tone.frame(3_000, 100).iter().all(|&b| b == mulaw(0)));
+}
+
+#[test]
+fn wav_files_are_mixed_down_and_resampled() {
+    let dir = std::env::temp_dir().join(format!("restream-audio-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 2_000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(dir.join("clip.wav"), spec).unwrap();
+    for _ in 0..1_000 {
+        writer.write_sample(8_000i16).unwrap();
+        writer.write_sample(4_000i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // Half a second of audio, longer than the two words it stands for
+    let records = vec![spoken("hello there", "clip.wav")];
+    let track = AudioTrack::load(
+        &records,
+        options(AudioEncoding::Pcm, AudioSource::Wav),
+        &dir,
+    )
+    .unwrap();
+    assert_eq!(track.len(), 500);
+    let frame = track.frame(0, 100);
+    let sample = i16::from_le_bytes([frame[0], frame[1]]);
+    assert!((sample - 6_000).abs() <= 1, "{}", sample);
+
+    let outside = vec![spoken("hello", "../clip.wav")];
+    assert!(
+        AudioTrack::load(
+            &outside,
+            options(AudioEncoding::Pcm, AudioSource::Wav),
+            &dir
+        )
+        .is_err()
+    );
+    let missing = vec![spoken("hello", "missing.wav")];
+    assert!(
+        AudioTrack::load(
+            &missing,
+            options(AudioEncoding::Pcm, AudioSource::Wav),
+            &dir
+        )
+        .is_err()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn records_are_sent_before_the_frame_they_start_in() {
+    let clock = Arc::new(VirtualClock::new());
+    let track = AudioTrack::load(
+        &records(),
+        options(AudioEncoding::Pcm, AudioSource::Silence),
+        Path::new("transcript"),
+    )
+    .unwrap();
+    let mut playback = AudioPlayback::new(records(), track, clock.clone());
+
+    let mut frames = 0;
+    let mut records_at = Vec::new();
+    while let Some(event) = playback.next().await {
+        match event {
+            AudioEvent::Record(step) => records_at.push((step.index, clock.elapsed(), frames)),
+            AudioEvent::Frame(bytes) => {
+                assert_eq!(bytes.len(), 200);
+                frames += 1;
+            }
+        }
+    }
+    assert_eq!(
+        records_at,
+        [(0, Duration::ZERO, 0), (1, Duration::from_secs(5), 50)]
+    );
+    assert_eq!(frames, 58);
+    // The last frame went out as soon as it was due
+    assert_eq!(clock.elapsed(), Duration::from_millis(5_700));
+}
+
+async fn store_session(sessions: &SessionStore, audio: Option<AudioTrack>) {
+    sessions.lock().await.insert(
+        "session".to_string(),
+        RewindSession {
+            session: SessionContext::new(SessionKind::job_description(), 7),
+            filename: "test.csv".to_string(),
+            records: records(),
+            current_index: 0,
+            chaos: ChaosOptions::default(),
+            script: None,
+            interactive: None,
+            channels: None,
+            hub: None,
+            audio,
+        },
+    );
+}
+
+async fn stream(sessions: &SessionStore) -> (DeliveryState, Vec<AudioMessage>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sender = sink::unfold(
+        received.clone(),
+        |received, message: AudioMessage| async move {
+            received.lock().unwrap().push(message);
+            Ok::<_, std::convert::Infallible>(received)
+        },
+    );
+    let report = stream_audio_session(
+        "session",
+        sender,
+        sessions.clone(),
+        Arc::new(VirtualClock::new()),
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    let received = received.lock().unwrap().clone();
+    (report.state, received)
+}
+
+#[tokio::test]
+async fn audio_sessions_interleave_text_and_binary_frames() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    let track = AudioTrack::load(
+        &records(),
+        options(AudioEncoding::Mulaw, AudioSource::Tone),
+        Path::new("transcript"),
+    )
+    .unwrap();
+    store_session(&sessions, Some(track)).await;
+
+    let (state, received) = stream(&sessions).await;
+    assert_eq!(state, DeliveryState::Completed);
+    let AudioMessage::Text(first) = &received[0] else {
+        panic!("expected the first record, got {:?}", received[0]);
+    };
+    let first: serde_json::Value = serde_json::from_str(first).unwrap();
+    assert_eq!(first["end"], "00:00:02");
+    assert_eq!(first["job_description_enrichment_session"], 7);
+    assert!(matches!(&received[1], AudioMessage::Binary(bytes) if bytes.len() == 100));
+    // Record, 50 frames, record, 8 frames, completion
+    assert_eq!(received.len(), 61);
+    assert!(matches!(&received[51], AudioMessage::Text(text) if text.contains("six seven")));
+    assert_eq!(
+        received.last(),
+        Some(&AudioMessage::Text("SESSION_COMPLETE".to_string()))
+    );
+    assert!(sessions.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn sessions_without_audio_are_refused() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, None).await;
+    let (state, received) = stream(&sessions).await;
+    assert_eq!(state, DeliveryState::Failed);
+    assert_eq!(
+        received,
+        [AudioMessage::Text("AUDIO_NOT_ENABLED".to_string())]
+    );
+}
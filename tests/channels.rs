@@ -0,0 +1,178 @@
+mod common;
+
+use common::{Received, record};
+use futures_util::sink;
+use restream::adapter::channels::{ChannelOptions, channel_message, channel_order};
+use restream::adapter::{RewindSession, SessionStore, join_channel_session};
+use restream::chaos::ChaosOptions;
+use restream::clock::{SharedClock, TokioClock};
+use restream::interface::{DeliveryState, SessionContext, SessionKind, TranscriptRecord};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Darryll talks over the end of Mike's first utterance
+fn records() -> Vec<TranscriptRecord> {
+    vec![
+        record(
+            "00:00:00",
+            "Mike",
+            "We need someone strong in React and Node who can lead the team",
+        ),
+        record("00:00:02", "Darryll", "Right, and the salary?"),
+        record("00:00:06", "Mike", "Around eighty."),
+    ]
+}
+
+async fn store_session(sessions: &SessionStore, key: &str, channels: Option<ChannelOptions>) {
+    sessions.lock().await.insert(
+        key.to_string(),
+        RewindSession {
+            session: SessionContext::new(SessionKind::job_description(), 7),
+            filename: "test.csv".to_string(),
+            records: records(),
+            current_index: 0,
+            chaos: ChaosOptions::default(),
+            script: None,
+            interactive: None,
+            channels,
+            hub: None,
+            audio: None,
+        },
+    );
+}
+
+fn channels() -> Option<ChannelOptions> {
+    Some(ChannelOptions::new(channel_order(&records()), Some(5)))
+}
+
+/// Joins a session as one stream, timestamping what it receives with `clock`
+fn join(
+    sessions: &SessionStore,
+    speaker: Option<&str>,
+    clock: SharedClock,
+) -> tokio::task::JoinHandle<(DeliveryState, Vec<(Duration, String)>)> {
+    let sessions = sessions.clone();
+    let speaker = speaker.map(str::to_string);
+    tokio::spawn(async move {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let sink_clock = clock.clone();
+        let sender = sink::unfold(received.clone(), move |received, text: String| {
+            let at = sink_clock.elapsed();
+            async move {
+                received.lock().unwrap().push((at, text));
+                Ok::<_, std::convert::Infallible>(received)
+            }
+        });
+        let (_replies, reply_receiver) = mpsc::unbounded_channel();
+        let report = join_channel_session(
+            "session",
+            speaker.as_deref(),
+            sender,
+            reply_receiver,
+            sessions,
+            clock,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        let received = received.lock().unwrap().clone();
+        (report.state, received)
+    })
+}
+
+fn message(text: &str) -> serde_json::Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn channels_follow_first_appearance() {
+    assert_eq!(channel_order(&records()), ["Mike", "Darryll"]);
+    let options = channels().unwrap();
+    assert_eq!(options.channel("Darryll"), Some(1));
+    assert_eq!(options.find_speaker("darryll"), Some("Darryll"));
+    assert_eq!(options.find_speaker("Sam"), None);
+
+    let session = SessionContext::new(SessionKind::job_description(), 7);
+    let value =
+        serde_json::to_value(channel_message(&session, Some(0), records()[0].clone())).unwrap();
+    assert_eq!(value["channel"], 0);
+    // Thirteen words at 150 words per minute, rounded up
+    assert_eq!(value["end"], "00:00:06");
+    assert_eq!(value["job_description_enrichment_session"], 7);
+}
+
+#[tokio::test(start_paused = true)]
+async fn each_speaker_stream_gets_its_channel_on_one_timeline() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "session", channels()).await;
+    let clock = TokioClock::shared();
+
+    let mike = join(&sessions, Some("mike"), clock.clone());
+    let darryll = join(&sessions, Some("Darryll"), clock.clone());
+    let (mike_state, mike) = mike.await.unwrap();
+    let (darryll_state, darryll) = darryll.await.unwrap();
+
+    assert_eq!(mike_state, DeliveryState::Completed);
+    assert_eq!(darryll_state, DeliveryState::Completed);
+    let times = |received: &[(Duration, String)]| {
+        received
+            .iter()
+            .map(|(at, _)| at.as_secs())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(times(&mike), [0, 6, 6]);
+    assert_eq!(times(&darryll), [2, 6]);
+    assert_eq!(mike.last().unwrap().1, "SESSION_COMPLETE");
+
+    // Darryll starts before Mike's first utterance ends
+    let first = message(&mike[0].1);
+    let overlapping = message(&darryll[0].1);
+    assert_eq!(first["channel"], 0);
+    assert_eq!(overlapping["channel"], 1);
+    assert!(first["end"].as_str().unwrap() > overlapping["body"]["time"].as_str().unwrap());
+    assert!(sessions.lock().await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn playback_starts_after_the_wait_without_every_speaker() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "session", channels()).await;
+    let clock = TokioClock::shared();
+
+    let (_, darryll) = join(&sessions, Some("Darryll"), clock).await.unwrap();
+    assert_eq!(darryll[0].0, Duration::from_secs(5 + 2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn whole_stream_gets_every_channel() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "session", channels()).await;
+
+    let (state, received) = join(&sessions, None, TokioClock::shared()).await.unwrap();
+    assert_eq!(state, DeliveryState::Completed);
+    let channels: Vec<_> = received[..3]
+        .iter()
+        .map(|(_, text)| message(text)["channel"].clone())
+        .collect();
+    assert_eq!(channels, [0, 1, 0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unknown_speakers_and_plain_sessions_are_refused() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "session", channels()).await;
+    let (state, received) = join(&sessions, Some("Sam"), TokioClock::shared())
+        .await
+        .unwrap();
+    assert_eq!(state, DeliveryState::Failed);
+    assert_eq!(received[0].1, "SPEAKER_NOT_FOUND");
+
+    store_session(&sessions, "session", None).await;
+    let (_, received) = join(&sessions, Some("Mike"), TokioClock::shared())
+        .await
+        .unwrap();
+    assert_eq!(received[0].1, "CHANNELS_NOT_ENABLED");
+}
@@ -0,0 +1,76 @@
+// Shared by several test crates, each of which only uses some of the helpers
+#![allow(dead_code)]
+
+use poem::listener::{Acceptor, Listener, TcpListener};
+use poem::{Request, Route, Server};
+use restream::clock::SharedClock;
+use restream::interface::TranscriptRecord;
+use restream::transcript::load_transcript_from_file;
+use std::path::Path;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub type Received = Arc<Mutex<Vec<(Duration, String)>>>;
+
+pub async fn fixture(filename: &str) -> Vec<TranscriptRecord> {
+    load_transcript_from_file(&Path::new("transcript").join(filename))
+        .await
+        .unwrap()
+}
+
+/// A record without a role or audio
+pub fn record(time: &str, speaker: &str, sentence: &str) -> TranscriptRecord {
+    TranscriptRecord {
+        time: time.to_string(),
+        speaker: speaker.to_string(),
+        role: None,
+        sentence: sentence.to_string(),
+        audio: None,
+    }
+}
+
+/// Starts a local webhook receiver that timestamps each payload with `clock`
+pub async fn spawn_webhook_stub(clock: SharedClock) -> (String, Received) {
+    let received: Received = Arc::new(Mutex::new(Vec::new()));
+    let stub_received = received.clone();
+    let endpoint = poem::endpoint::make(move |mut req: Request| {
+        let received = stub_received.clone();
+        let at = clock.elapsed();
+        async move {
+            let body = req.take_body().into_string().await.unwrap();
+            received.lock().unwrap().push((at, body));
+            poem::web::Json(serde_json::json!({ "ok": true }))
+        }
+    });
+
+    let acceptor = TcpListener::bind("127.0.0.1:0")
+        .into_acceptor()
+        .await
+        .unwrap();
+    let addr = acceptor.local_addr()[0].as_socket_addr().cloned().unwrap();
+    tokio::spawn(Server::new_with_acceptor(acceptor).run(Route::new().at("/hook", endpoint)));
+
+    (format!("http://{}/hook", addr), received)
+}
+
+/// Starts a webhook receiver answering with whatever status `status` currently holds
+pub async fn spawn_status_stub(status: Arc<AtomicU16>) -> String {
+    let endpoint = poem::endpoint::make(move |_req: Request| {
+        let status = status.load(Ordering::SeqCst);
+        async move {
+            poem::Response::builder()
+                .status(poem::http::StatusCode::from_u16(status).unwrap())
+                .body("")
+        }
+    });
+
+    let acceptor = TcpListener::bind("127.0.0.1:0")
+        .into_acceptor()
+        .await
+        .unwrap();
+    let addr = acceptor.local_addr()[0].as_socket_addr().cloned().unwrap();
+    tokio::spawn(Server::new_with_acceptor(acceptor).run(Route::new().at("/hook", endpoint)));
+
+    format!("http://{}/hook", addr)
+}
@@ -0,0 +1,260 @@
+mod common;
+
+use common::{fixture, record, spawn_status_stub};
+use restream::adapter::WebhookBroadcaster;
+use restream::batch::{BatchMode, BatchOptions};
+use restream::chaos::ChaosOptions;
+use restream::clock::{TokioClock, VirtualClock};
+use restream::dead_letter::{DeadLetterHandle, DeadLetterStore, Destination};
+use restream::interface::{
+    Broadcaster, DeliveryReport, DeliveryState, SessionContext, SessionKind,
+};
+use restream::playback::Playback;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "restream-dead-letters-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn broadcaster(url: &str, store: &Arc<DeadLetterStore>, batch: BatchOptions) -> WebhookBroadcaster {
+    WebhookBroadcaster::new(url, reqwest::Client::new())
+        .with_batch(batch)
+        .with_dead_letters(DeadLetterHandle {
+            store: store.clone(),
+            target: Some("n8n".to_string()),
+        })
+}
+
+/// Replays test.csv to `url` and returns the report and the store its failures were filed in
+async fn replay(
+    url: &str,
+    path: &Path,
+    batch: BatchOptions,
+) -> (DeliveryReport, Arc<DeadLetterStore>) {
+    let store = Arc::new(DeadLetterStore::load(path).unwrap());
+    let playback = Playback::new(
+        fixture("test.csv").await,
+        ChaosOptions::default(),
+        Arc::new(VirtualClock::new()),
+    );
+    let report = Arc::new(broadcaster(url, &store, batch))
+        .broadcast(
+            SessionContext::new(SessionKind::job_description(), 7),
+            playback.into_stream(),
+            CancellationToken::new(),
+        )
+        .report()
+        .await;
+    (report, store)
+}
+
+async fn replay_failing(url: &str, path: &Path) -> Arc<DeadLetterStore> {
+    let (report, store) = replay(url, path, BatchOptions::default()).await;
+    assert_eq!(report.state, DeliveryState::Failed);
+    store
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_persisted() {
+    let status = Arc::new(AtomicU16::new(503));
+    let url = spawn_status_stub(status).await;
+    let path = store_path("persisted");
+
+    let records = fixture("test.csv").await.len();
+    let (report, store) = replay(&url, &path, BatchOptions::default()).await;
+
+    // Every record and the completion message are attempted and kept with their payload
+    assert_eq!(report.state, DeliveryState::Failed);
+    assert_eq!(report.delivered, records);
+    let error = report.error.unwrap();
+    assert!(
+        error.contains(&format!("{} webhook message(s)", records + 1)),
+        "{}",
+        error
+    );
+    let letters = store.list().await;
+    assert_eq!(letters.len(), records + 1);
+    let indices: Vec<Option<usize>> = letters.iter().map(|l| l.record_index).collect();
+    let mut expected: Vec<Option<usize>> = (0..records).map(Some).collect();
+    expected.push(None);
+    assert_eq!(indices, expected);
+    let letter = &letters[0];
+    assert_eq!(letter.url, url);
+    assert_eq!(letter.target.as_deref(), Some("n8n"));
+    assert_eq!(letter.status, Some(503));
+    assert_eq!(letter.attempts, 1);
+    assert_eq!(letter.record_index, Some(0));
+    assert_eq!(letter.payload["job_description_enrichment_session"], 7);
+    assert!(letter.error.contains("503"), "{}", letter.error);
+
+    // And survives a restart
+    let reloaded = DeadLetterStore::load(&path).unwrap();
+    assert_eq!(reloaded.list().await, letters);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn concurrent_broadcasts_count_their_own_dead_letters() {
+    let status = Arc::new(AtomicU16::new(503));
+    let url = spawn_status_stub(status).await;
+    let path = store_path("concurrent");
+    let store = Arc::new(DeadLetterStore::load(&path).unwrap());
+    let records = fixture("test.csv").await;
+    let broadcaster = Arc::new(broadcaster(&url, &store, BatchOptions::default()));
+
+    let handles: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|id| {
+            broadcaster.clone().broadcast(
+                SessionContext::new(SessionKind::job_description(), id),
+                Playback::new(
+                    records.clone(),
+                    ChaosOptions::default(),
+                    Arc::new(VirtualClock::new()),
+                )
+                .into_stream(),
+                CancellationToken::new(),
+            )
+        })
+        .collect();
+    for handle in handles {
+        let error = handle.report().await.error.unwrap();
+        assert!(
+            error.contains(&format!("{} webhook message(s)", records.len() + 1)),
+            "{}",
+            error
+        );
+    }
+    assert_eq!(store.list().await.len(), 2 * (records.len() + 1));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn failed_batches_and_their_remainder_are_persisted() {
+    let status = Arc::new(AtomicU16::new(503));
+    let url = spawn_status_stub(status).await;
+    let path = store_path("batches");
+    let records = fixture("test.csv").await.len();
+    let batch = BatchOptions {
+        mode: BatchMode::Records,
+        size: 4,
+        ..Default::default()
+    };
+
+    let (report, store) = replay(&url, &path, batch).await;
+    assert_eq!(report.state, DeliveryState::Failed);
+
+    // The first batch, the records still batched at the end, then the completion message
+    let letters = store.list().await;
+    assert_eq!(letters.len(), 3);
+    assert_eq!(letters[0].payload.as_array().unwrap().len(), 4);
+    assert_eq!(letters[1].record_index, Some(4));
+    assert_eq!(letters[1].payload.as_array().unwrap().len(), records - 4);
+    assert_eq!(letters[2].record_index, None);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn batched_records_are_persisted_when_a_broadcast_stops() {
+    let url = spawn_status_stub(Arc::new(AtomicU16::new(200))).await;
+    let path = store_path("stopped");
+    let store = Arc::new(DeadLetterStore::load(&path).unwrap());
+    let batch = BatchOptions {
+        mode: BatchMode::Records,
+        size: 3,
+        ..Default::default()
+    };
+    let records = vec![
+        record("00:00:00", "Alex", "Hello"),
+        record("00:00:00", "Alex", "Hello"),
+        record("01:00:00", "Alex", "Hello"),
+    ];
+    let playback = Playback::new(records, ChaosOptions::default(), TokioClock::shared());
+    let cancel = CancellationToken::new();
+    let handle = Arc::new(broadcaster(&url, &store, batch)).broadcast(
+        SessionContext::new(SessionKind::job_description(), 7),
+        playback.into_stream(),
+        cancel.clone(),
+    );
+
+    // Two records are waiting for the third when the broadcast is cancelled
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    cancel.cancel();
+    let report = handle.report().await;
+    assert_eq!(report.state, DeliveryState::Cancelled);
+    assert_eq!(report.delivered, 2);
+
+    let letters = store.list().await;
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].status, None);
+    assert_eq!(letters[0].record_index, Some(0));
+    assert_eq!(letters[0].payload.as_array().unwrap().len(), 2);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn redelivery_removes_delivered_letters_and_counts_attempts() {
+    let status = Arc::new(AtomicU16::new(500));
+    let url = spawn_status_stub(status.clone()).await;
+    let path = store_path("redeliver");
+    let store = replay_failing(&url, &path).await;
+    let client = reqwest::Client::new();
+    let id = store.list().await[0].id.clone();
+
+    // Unknown IDs are rejected before anything is sent
+    let error = store
+        .redeliver(&client, &["missing".to_string()], None)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("missing"), "{}", error);
+
+    // Still failing: every letter stays with one more attempt
+    let letters = store.len().await;
+    let results = store.redeliver(&client, &[], None).await.unwrap();
+    assert_eq!(results.len(), letters);
+    assert!(results.iter().all(|result| !result.delivered));
+    assert!(store.list().await.iter().all(|letter| letter.attempts == 2));
+
+    // A redirect that is not followed is not a delivery either
+    status.store(304, Ordering::SeqCst);
+    let results = store
+        .redeliver(&client, std::slice::from_ref(&id), None)
+        .await
+        .unwrap();
+    assert!(!results[0].delivered);
+    assert_eq!(results[0].status, Some(304));
+    let error = results[0].error.as_deref().unwrap();
+    assert!(error.contains("only a 2xx"), "{}", error);
+    assert_eq!(store.list().await[0].attempts, 3);
+
+    // Redirected to a healthy receiver: the letter leaves the store
+    let healthy = spawn_status_stub(Arc::new(AtomicU16::new(200))).await;
+    let destination = Destination {
+        url: healthy.clone(),
+        target: None,
+    };
+    let results = store
+        .redeliver(&client, std::slice::from_ref(&id), Some(&destination))
+        .await
+        .unwrap();
+    assert_eq!(results[0].id, id);
+    assert_eq!(results[0].url, healthy);
+    assert!(results[0].delivered);
+    assert_eq!(store.len().await, letters - 1);
+    assert!(store.list().await.iter().all(|letter| letter.id != id));
+    assert_eq!(
+        DeadLetterStore::load(&path).unwrap().len().await,
+        letters - 1
+    );
+    let _ = std::fs::remove_file(&path);
+}
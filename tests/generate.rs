@@ -0,0 +1,168 @@
+use restream::generate::{
+    GeneratorTemplate, PauseRange, RepeatRange, TemplateSpeaker, TemplateTiming, TemplateTurn,
+    generate_transcripts, numbered_paths, save_transcripts,
+};
+use restream::interface::TranscriptRecord;
+use restream::lint::{LintOptions, lint_records};
+use restream::transcript::{load_transcript_from_file, parse_time_to_time};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+fn json(records: &[TranscriptRecord]) -> serde_json::Value {
+    serde_json::to_value(records).unwrap()
+}
+
+fn turn(speaker: &str, utterances: &[&str]) -> TemplateTurn {
+    TemplateTurn {
+        speaker: Some(speaker.to_string()),
+        utterances: utterances.iter().map(|u| u.to_string()).collect(),
+        turns: Vec::new(),
+        probability: 1.0,
+        repeat: None,
+        redraw: Vec::new(),
+        pause_secs: None,
+    }
+}
+
+fn template() -> GeneratorTemplate {
+    GeneratorTemplate {
+        speakers: vec![
+            TemplateSpeaker {
+                id: "recruiter".to_string(),
+                names: vec!["Alex".to_string()],
+                role: Some("recruiter".to_string()),
+            },
+            TemplateSpeaker {
+                id: "manager".to_string(),
+                names: vec!["Sam".to_string()],
+                role: None,
+            },
+        ],
+        slots: BTreeMap::from([
+            (
+                "skill".to_string(),
+                vec!["Rust".to_string(), "Go".to_string()],
+            ),
+            ("salary".to_string(), vec!["£90k".to_string()]),
+        ]),
+        turns: vec![
+            turn("recruiter", &["Hi {{manager}}, what's the budget?"]),
+            turn("manager", &["{{salary}}, {{recruiter}}."]),
+            TemplateTurn {
+                speaker: None,
+                utterances: Vec::new(),
+                turns: vec![
+                    turn("recruiter", &["Is {{skill}} needed?"]),
+                    turn("manager", &["Yes, {{skill}}."]),
+                ],
+                probability: 1.0,
+                repeat: Some(RepeatRange { min: 3, max: 3 }),
+                redraw: vec!["skill".to_string()],
+                pause_secs: None,
+            },
+        ],
+        timing: TemplateTiming {
+            words_per_minute: 60.0,
+            pause_secs: PauseRange { min: 2.0, max: 2.0 },
+        },
+        seed: None,
+    }
+}
+
+#[test]
+fn fills_slots_speakers_and_timing() {
+    let records = template().generate(1);
+    assert_eq!(records.len(), 8);
+    assert_eq!(records[0].sentence, "Hi Sam, what's the budget?");
+    assert_eq!(records[0].speaker, "Alex");
+    assert_eq!(records[0].role.as_deref(), Some("recruiter"));
+    assert_eq!(records[1].sentence, "£90k, Alex.");
+    assert_eq!(records[1].role, None);
+    // One word per second at 60 wpm, plus a fixed two-second pause
+    let times: Vec<i32> = records
+        .iter()
+        .map(|r| parse_time_to_time(&r.time))
+        .collect();
+    assert_eq!(&times[..3], &[0, 7, 11]);
+
+    // Each block pass redraws the skill, and the answer follows the question
+    for pair in records[2..].chunks(2) {
+        let skill = pair[0]
+            .sentence
+            .trim_start_matches("Is ")
+            .trim_end_matches(" needed?");
+        assert!(["Rust", "Go"].contains(&skill));
+        assert_eq!(pair[1].sentence, format!("Yes, {}.", skill));
+    }
+}
+
+#[test]
+fn seeds_are_reproducible() {
+    let template = template();
+    let (seed, first) = generate_transcripts(&template, 5, Some(40)).unwrap();
+    let (_, again) = generate_transcripts(&template, 5, Some(40)).unwrap();
+    assert_eq!(seed, 40);
+    assert_eq!(
+        first.iter().map(|t| t.seed).collect::<Vec<_>>(),
+        vec![40, 41, 42, 43, 44]
+    );
+    for (a, b) in first.iter().zip(&again) {
+        assert_eq!(json(&a.records), json(&b.records));
+    }
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    let mut unknown_speaker = template();
+    unknown_speaker.turns.push(turn("candidate", &["Hello"]));
+    let mut unknown_slot = template();
+    unknown_slot.turns.push(turn("manager", &["In {{city}}"]));
+    let mut empty_turn = template();
+    empty_turn.turns.push(TemplateTurn {
+        speaker: None,
+        ..turn("manager", &[])
+    });
+    let mut bad_repeat = template();
+    bad_repeat.turns[2].repeat = Some(RepeatRange { min: 4, max: 1 });
+
+    for template in [unknown_speaker, unknown_slot, empty_turn, bad_repeat] {
+        assert!(template.validate().is_err(), "{:?}", template.turns.last());
+    }
+    assert!(generate_transcripts(&template(), 0, None).is_err());
+}
+
+#[test]
+fn bundled_template_generates_clean_varied_calls() {
+    let template = GeneratorTemplate::load(Path::new("templates/intake_call.json")).unwrap();
+    let (_, transcripts) = generate_transcripts(&template, 20, Some(1)).unwrap();
+    for transcript in &transcripts {
+        assert!(lint_records(&transcript.records, &LintOptions::default()).is_empty());
+        assert!(
+            transcript
+                .records
+                .iter()
+                .all(|r| !r.sentence.contains("{{"))
+        );
+    }
+    let openings: std::collections::HashSet<_> = transcripts
+        .iter()
+        .map(|t| t.records[0].sentence.clone())
+        .collect();
+    assert!(openings.len() > 5);
+}
+
+#[tokio::test]
+async fn saves_numbered_transcripts_without_overwriting() {
+    let dir = std::env::temp_dir().join(format!("restream-generate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (_, transcripts) = generate_transcripts(&template(), 2, Some(3)).unwrap();
+
+    let paths = save_transcripts(&dir, "call", &transcripts, false).unwrap();
+    assert_eq!(paths, numbered_paths(&dir, "call", 2, false));
+    assert!(paths[1].ends_with("call_002.csv"));
+    let loaded = load_transcript_from_file(&paths[0]).await.unwrap();
+    assert_eq!(json(&loaded), json(&transcripts[0].records));
+    assert!(save_transcripts(&dir, "call", &transcripts, false).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
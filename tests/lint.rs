@@ -0,0 +1,143 @@
+use restream::lint::{LintOptions, LintReport, Severity, lint_contents, lint_dir};
+use std::path::Path;
+
+fn lint_csv(contents: &str) -> LintReport {
+    lint_contents(None, contents, false, &LintOptions::default())
+}
+
+/// (row, severity, code) of every issue
+fn issues(report: &LintReport) -> Vec<(Option<usize>, Severity, &str)> {
+    report
+        .issues
+        .iter()
+        .map(|issue| (issue.row, issue.severity, issue.code.as_str()))
+        .collect()
+}
+
+#[test]
+fn clean_transcripts_have_no_issues() {
+    let report = lint_csv("time,speaker,sentence\n00:00:00,Alex,Hi\n00:00:03,Sam,Hello\n");
+    assert!(report.is_valid());
+    assert_eq!(report.records, 2);
+    assert!(report.issues.is_empty());
+}
+
+#[test]
+fn timestamps_that_would_play_as_zero_or_backwards_are_errors() {
+    let report = lint_csv(
+        "time,speaker,sentence\n\
+         00:00:05,Alex,Hi\n\
+         five,Sam,Hello\n\
+         00:00:02,Alex,Back in time\n\
+         00:10:00,Sam,Much later\n",
+    );
+
+    assert!(!report.is_valid());
+    assert_eq!(report.errors, 2);
+    assert_eq!(report.warnings, 1);
+    assert_eq!(
+        issues(&report),
+        [
+            (Some(2), Severity::Error, "time_unparseable"),
+            (Some(3), Severity::Error, "time_backwards"),
+            (Some(4), Severity::Warning, "long_gap"),
+        ]
+    );
+}
+
+#[test]
+fn text_problems_are_warnings_with_rows_and_lines() {
+    let report = lint_csv(
+        "time,speaker,sentence\n\
+         00:00:00,Alex,\"Wrapped\n\"\n\
+         00:00:01,Sam,\n\
+         00:00:02,alex,Hi\n\
+         00:00:02,alex,Hi\n\
+         00:00:03,,Who said this\n",
+    );
+
+    assert!(report.is_valid());
+    assert_eq!(
+        issues(&report),
+        [
+            (Some(1), Severity::Warning, "trailing_newline"),
+            (Some(2), Severity::Warning, "empty_sentence"),
+            (Some(3), Severity::Warning, "speaker_variant"),
+            (Some(4), Severity::Warning, "speaker_variant"),
+            (Some(4), Severity::Warning, "duplicate_row"),
+            (Some(5), Severity::Warning, "empty_speaker"),
+        ]
+    );
+    // The quoted newline pushes later records down a line
+    assert_eq!(report.issues[1].line, Some(4));
+    assert_eq!(report.issues[4].message, "Duplicate of row 3");
+}
+
+#[test]
+fn known_speakers_flag_everyone_else() {
+    let options = LintOptions {
+        known_speakers: Some(vec!["Alex".to_string()]),
+        max_gap_secs: 60,
+    };
+    let report = lint_contents(
+        None,
+        "time,speaker,sentence\n00:00:00,Alex,Hi\n00:00:01,Jordan,Hello\n",
+        false,
+        &options,
+    );
+    assert_eq!(
+        issues(&report),
+        [(Some(2), Severity::Warning, "unknown_speaker")]
+    );
+}
+
+#[test]
+fn unparseable_rows_are_reported_and_the_rest_still_checked() {
+    let report = lint_csv(
+        "time,speaker,sentence\n\
+         00:00:05,Alex,Hi\n\
+         00:00:06,Sam\n\
+         00:00:00,Alex,Back in time\n",
+    );
+    assert_eq!(report.records, 2);
+    assert_eq!(
+        issues(&report),
+        [
+            (Some(2), Severity::Error, "unparseable_row"),
+            (Some(3), Severity::Error, "time_backwards"),
+        ]
+    );
+
+    let json = lint_contents(
+        None,
+        r#"[{"time":"00:00:00","speaker":"Alex","sentence":"Hi"},{"time":"00:00:01"}]"#,
+        true,
+        &LintOptions::default(),
+    );
+    assert_eq!(
+        issues(&json),
+        [(Some(2), Severity::Error, "unparseable_row")]
+    );
+
+    let empty = lint_csv("time,speaker,sentence\n");
+    assert_eq!(
+        issues(&empty),
+        [(None, Severity::Error, "empty_transcript")]
+    );
+}
+
+#[test]
+fn bundled_transcripts_are_valid() {
+    let reports = lint_dir(Path::new("transcript"), &LintOptions::default()).unwrap();
+    assert!(!reports.is_empty());
+    for report in &reports {
+        assert!(report.is_valid(), "{:?}", report);
+    }
+
+    // Exported with a newline closing every quoted sentence
+    let intake = reports
+        .iter()
+        .find(|report| report.filename.as_deref() == Some("intake_call.csv"))
+        .unwrap();
+    assert_eq!(intake.warnings, intake.records);
+}
@@ -0,0 +1,350 @@
+mod common;
+
+use common::{Received, fixture, spawn_webhook_stub};
+use futures_util::sink;
+use restream::adapter::composite::{
+    BroadcastEntry, BroadcastStatus, MAX_FINISHED_BROADCASTS, insert_broadcast,
+};
+use restream::adapter::{
+    BroadcastStore, CompositeBroadcaster, RewindSession, SessionStore, SubscriberSink,
+    WebhookBroadcaster, WebhookDelivery, broadcast_session_messages,
+};
+use restream::chaos::ChaosOptions;
+use restream::clock::{Clock, SharedClock, TokioClock, VirtualClock};
+use restream::interface::{
+    BroadcastSink, Broadcaster, DeliveryState, ProgressEvent, SessionContext, SessionKind,
+    TranscriptRecord,
+};
+use restream::playback::Playback;
+use restream::report::{ReportHandle, ReportStore, WebhookExpectations, WebhookReport};
+use restream::transcript::parse_time_to_time;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+async fn store_session(sessions: &SessionStore, key: &str, records: Vec<TranscriptRecord>) {
+    sessions.lock().await.insert(
+        key.to_string(),
+        RewindSession {
+            session: SessionContext::new(SessionKind::job_description(), 7),
+            filename: "test.csv".to_string(),
+            records,
+            current_index: 0,
+            chaos: ChaosOptions::default(),
+            script: None,
+            interactive: None,
+            channels: None,
+            hub: None,
+            audio: None,
+        },
+    );
+}
+
+/// Plays a stored session into an in-memory sink, timestamping each message with `clock`
+async fn play_session(
+    sessions: &SessionStore,
+    key: &str,
+    clock: SharedClock,
+) -> Vec<(Duration, String)> {
+    let received: Received = Arc::new(Mutex::new(Vec::new()));
+    let sink_clock = clock.clone();
+    let sender = sink::unfold(received.clone(), move |received, text: String| {
+        let at = sink_clock.elapsed();
+        async move {
+            received.lock().unwrap().push((at, text));
+            Ok::<_, std::convert::Infallible>(received)
+        }
+    });
+
+    broadcast_session_messages(
+        key,
+        sender,
+        sessions.clone(),
+        clock,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    received.lock().unwrap().clone()
+}
+
+fn expected_offsets(records: &[TranscriptRecord]) -> Vec<Duration> {
+    records
+        .iter()
+        .map(|r| Duration::from_secs(parse_time_to_time(&r.time) as u64))
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn websocket_session_keeps_order_and_gaps_on_paused_clock() {
+    let records = fixture("test.csv").await;
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "session", records.clone()).await;
+
+    let received = play_session(&sessions, "session", TokioClock::shared()).await;
+
+    assert_eq!(received.len(), records.len() + 1);
+    let (offsets, messages): (Vec<_>, Vec<_>) = received[..records.len()].iter().cloned().unzip();
+    assert_eq!(offsets, expected_offsets(&records));
+    for (message, record) in messages.iter().zip(&records) {
+        let message: serde_json::Value = serde_json::from_str(message).unwrap();
+        assert_eq!(message["job_description_enrichment_session"], 7);
+        assert_eq!(message["body"]["speaker"], record.speaker.as_str());
+        assert_eq!(message["body"]["sentence"], record.sentence.as_str());
+    }
+
+    assert_eq!(received.last().unwrap().1, "SESSION_COMPLETE");
+    assert!(sessions.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn websocket_session_replays_full_intake_call_on_virtual_clock() {
+    let records = fixture("intake_call.csv").await;
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "intake", records.clone()).await;
+
+    let clock = Arc::new(VirtualClock::new());
+    let received = play_session(&sessions, "intake", clock.clone()).await;
+
+    let offsets: Vec<_> = received[..records.len()]
+        .iter()
+        .map(|(at, _)| *at)
+        .collect();
+    assert_eq!(offsets, expected_offsets(&records));
+    assert_eq!(clock.elapsed(), Duration::from_secs(16 * 60 + 18));
+    assert_eq!(received.last().unwrap().1, "SESSION_COMPLETE");
+    assert!(sessions.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn websocket_session_not_found() {
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+
+    let received = play_session(&sessions, "missing", Arc::new(VirtualClock::new())).await;
+
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].1, "SESSION_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn websocket_chaos_disconnect_cleans_up_session() {
+    let records = fixture("test.csv").await;
+    let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    store_session(&sessions, "chaos", records).await;
+    sessions.lock().await.get_mut("chaos").unwrap().chaos = ChaosOptions {
+        disconnect_at_record: Some(3),
+        ..Default::default()
+    };
+
+    let received = play_session(&sessions, "chaos", Arc::new(VirtualClock::new())).await;
+
+    assert_eq!(received.len(), 3);
+    assert!(received.iter().all(|(_, text)| text != "SESSION_COMPLETE"));
+    assert!(sessions.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn webhook_broadcast_keeps_order_and_gaps_on_virtual_clock() {
+    let records = fixture("test.csv").await;
+    let clock: SharedClock = Arc::new(VirtualClock::new());
+    let (url, received) = spawn_webhook_stub(clock.clone()).await;
+
+    let reports: ReportStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    reports.lock().await.insert(
+        "report".to_string(),
+        WebhookReport::new(
+            "report".to_string(),
+            "test.csv".to_string(),
+            url.clone(),
+            WebhookExpectations {
+                status_codes: vec![200],
+                ..Default::default()
+            },
+        ),
+    );
+
+    let broadcaster = Arc::new(
+        WebhookBroadcaster::new(url, reqwest::Client::new()).with_report(ReportHandle {
+            store: reports.clone(),
+            report_id: "report".to_string(),
+        }),
+    );
+    let playback = Playback::new(records.clone(), ChaosOptions::default(), clock.clone());
+    let mut handle = broadcaster.broadcast(
+        SessionContext::new(SessionKind::job_description(), 11),
+        playback.into_stream(),
+        CancellationToken::new(),
+    );
+    let mut progress = Vec::new();
+    while let Some(event) = handle.progress.recv().await {
+        progress.push(event);
+    }
+    let delivery = handle.report().await;
+    assert!(delivery.is_completed());
+    assert_eq!(delivery.delivered, records.len());
+    assert_eq!(progress.len(), records.len() + 1);
+    assert_eq!(progress.last(), Some(&ProgressEvent::Completed));
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), records.len() + 1);
+    let offsets: Vec<_> = received[..records.len()]
+        .iter()
+        .map(|(at, _)| *at)
+        .collect();
+    assert_eq!(offsets, expected_offsets(&records));
+    for ((_, body), record) in received.iter().zip(&records) {
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["body"]["sentence"], record.sentence.as_str());
+    }
+
+    let completion: serde_json::Value = serde_json::from_str(&received.last().unwrap().1).unwrap();
+    assert_eq!(completion["status"], "complete");
+
+    let report = reports.lock().await.get("report").cloned().unwrap();
+    assert_eq!(report.status, "completed");
+    assert_eq!(report.passed, Some(true));
+    assert_eq!(report.deliveries.len(), records.len() + 1);
+}
+
+#[tokio::test]
+async fn seeded_chaos_is_reproducible() {
+    let records = fixture("test.csv").await;
+    let chaos = ChaosOptions {
+        seed: Some(42),
+        drop_rate: 0.3,
+        duplicate_rate: 0.3,
+        reorder_window: 3,
+        jitter_ms: 500,
+        ..Default::default()
+    };
+
+    let mut runs = Vec::new();
+    for key in ["first", "second"] {
+        let sessions: SessionStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+        store_session(&sessions, key, records.clone()).await;
+        sessions.lock().await.get_mut(key).unwrap().chaos = chaos.clone();
+        runs.push(play_session(&sessions, key, Arc::new(VirtualClock::new())).await);
+    }
+
+    assert_eq!(runs[0], runs[1]);
+}
+
+#[tokio::test]
+async fn composite_broadcast_isolates_failing_sinks() {
+    let records = fixture("test.csv").await;
+    let (subscribers, mut subscription) = tokio::sync::broadcast::channel(64);
+    let sinks: Vec<Arc<dyn BroadcastSink>> = vec![
+        Arc::new(WebhookDelivery::new(Arc::new(WebhookBroadcaster::new(
+            "http://127.0.0.1:1/unreachable",
+            reqwest::Client::new(),
+        )))),
+        Arc::new(SubscriberSink {
+            label: "observers".to_string(),
+            subscribers,
+        }),
+    ];
+
+    let broadcaster = Arc::new(CompositeBroadcaster {
+        sinks,
+        status: None,
+    });
+    let playback = Playback::new(
+        records.clone(),
+        ChaosOptions::default(),
+        Arc::new(VirtualClock::new()),
+    );
+    let report = broadcaster
+        .broadcast(
+            SessionContext::new(SessionKind::candidate_profile(), 3),
+            playback.into_stream(),
+            CancellationToken::new(),
+        )
+        .report()
+        .await;
+    assert_eq!(report.state, DeliveryState::Failed);
+
+    let mut messages = Vec::new();
+    while let Ok(message) = subscription.try_recv() {
+        messages.push(message);
+    }
+    assert_eq!(messages.len(), records.len() + 1);
+    assert_eq!(messages.last().unwrap(), "SESSION_COMPLETE");
+    let first: serde_json::Value = serde_json::from_str(&messages[0]).unwrap();
+    assert_eq!(first["candidate_profile_enrichment_session"], 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelled_broadcast_stops_before_the_next_record() {
+    let records = fixture("test.csv").await;
+    let (subscribers, mut subscription) = tokio::sync::broadcast::channel(64);
+    let sink = Arc::new(SubscriberSink {
+        label: "observers".to_string(),
+        subscribers,
+    });
+
+    let cancel = CancellationToken::new();
+    let playback = Playback::new(
+        records.clone(),
+        ChaosOptions::default(),
+        TokioClock::shared(),
+    );
+    let mut handle = restream::playback::deliver(
+        sink,
+        SessionContext::new(SessionKind::job_description(), 5),
+        playback.into_stream(),
+        cancel.clone(),
+    );
+
+    assert!(matches!(
+        handle.progress.recv().await,
+        Some(ProgressEvent::Delivered { index: 0, .. })
+    ));
+    cancel.cancel();
+
+    let report = handle.report().await;
+    assert_eq!(report.state, DeliveryState::Cancelled);
+    assert_eq!(report.delivered, 1);
+    assert!(subscription.try_recv().is_ok());
+    assert!(subscription.try_recv().is_err());
+}
+
+fn broadcast_entry(broadcast_id: &str, finished_at: Option<String>) -> BroadcastEntry {
+    BroadcastEntry {
+        status: BroadcastStatus {
+            broadcast_id: broadcast_id.to_string(),
+            filename: "test.csv".to_string(),
+            state: if finished_at.is_some() {
+                "completed"
+            } else {
+                "running"
+            }
+            .to_string(),
+            sinks: Vec::new(),
+            finished_at,
+        },
+        subscribers: None,
+        cancel: CancellationToken::new(),
+    }
+}
+
+#[tokio::test]
+async fn oldest_finished_broadcasts_are_evicted() {
+    let broadcasts: BroadcastStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    insert_broadcast(&broadcasts, broadcast_entry("live", None)).await;
+    for i in 0..MAX_FINISHED_BROADCASTS + 3 {
+        let finished_at = format!("2026-01-01T00:{:02}:{:02}+00:00", i / 60, i % 60);
+        insert_broadcast(
+            &broadcasts,
+            broadcast_entry(&format!("finished-{}", i), Some(finished_at)),
+        )
+        .await;
+    }
+
+    let broadcasts = broadcasts.lock().await;
+    assert_eq!(broadcasts.len(), MAX_FINISHED_BROADCASTS + 1);
+    assert!(broadcasts.contains_key("live"));
+    assert!(!broadcasts.contains_key("finished-2"));
+    assert!(broadcasts.contains_key("finished-3"));
+}
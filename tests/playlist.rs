@@ -0,0 +1,199 @@
+use futures_util::StreamExt;
+use restream::chaos::ChaosOptions;
+use restream::clock::{Clock, VirtualClock};
+use restream::playlist::{Playlist, PlaylistItem, PlaylistOptions};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn slice(start: &str, end: &str) -> PlaylistItem {
+    PlaylistItem {
+        start: Some(start.to_string()),
+        end: Some(end.to_string()),
+        ..PlaylistItem::new("test.csv")
+    }
+}
+
+/// Plays the playlist on a virtual clock, returning (index, time, sentence) per step
+async fn play(options: &PlaylistOptions) -> (Vec<(usize, String, String)>, Duration) {
+    let playlist = Playlist::load(options, Path::new("transcript"))
+        .await
+        .unwrap();
+    let clock = Arc::new(VirtualClock::new());
+    let steps: Vec<_> = playlist
+        .into_stream(ChaosOptions::default(), clock.clone())
+        .map(|step| {
+            let step = step.unwrap();
+            (step.index, step.record.time, step.record.sentence)
+        })
+        .collect()
+        .await;
+    (steps, clock.elapsed())
+}
+
+fn times(steps: &[(usize, String, String)]) -> Vec<&str> {
+    steps.iter().map(|(_, time, _)| time.as_str()).collect()
+}
+
+#[tokio::test]
+async fn transcripts_play_back_to_back_with_gaps() {
+    let options = PlaylistOptions {
+        gap_secs: 5,
+        ..PlaylistOptions::from_filenames(["test.csv", "test.csv"])
+    };
+    let (steps, elapsed) = play(&options).await;
+
+    assert_eq!(steps.len(), 14);
+    assert_eq!(
+        steps.iter().map(|(index, _, _)| *index).collect::<Vec<_>>(),
+        (0..14).collect::<Vec<_>>()
+    );
+    assert_eq!(times(&steps)[6..9], ["00:00:18", "00:00:23", "00:00:26"]);
+    assert_eq!(steps[7].2, "Good morning team");
+    assert_eq!(elapsed, Duration::from_secs(41));
+}
+
+#[tokio::test]
+async fn slices_are_rebased_and_gaps_can_be_overridden() {
+    let options = PlaylistOptions {
+        items: vec![
+            PlaylistItem {
+                gap_secs: Some(1),
+                ..slice("00:00:06", "00:00:12")
+            },
+            slice("15", "18"),
+        ],
+        gap_secs: 30,
+        ..PlaylistOptions::default()
+    };
+    let (steps, elapsed) = play(&options).await;
+
+    assert_eq!(
+        times(&steps),
+        ["00:00:00", "00:00:03", "00:00:06", "00:00:07", "00:00:10"]
+    );
+    assert_eq!(steps[0].2, "Let's go around with updates");
+    assert_eq!(steps[3].2, "Sounds good any blockers?");
+    assert_eq!(elapsed, Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn repeated_passes_continue_the_timeline() {
+    let options = PlaylistOptions {
+        items: vec![slice("00:00:00", "00:00:03")],
+        gap_secs: 2,
+        repeat: 3,
+        ..PlaylistOptions::default()
+    };
+    let (steps, _) = play(&options).await;
+
+    assert_eq!(
+        times(&steps),
+        [
+            "00:00:00", "00:00:03", "00:00:05", "00:00:08", "00:00:10", "00:00:13"
+        ]
+    );
+    assert_eq!(steps.last().unwrap().0, 5);
+}
+
+#[tokio::test]
+async fn looping_playlists_run_until_dropped() {
+    let options = PlaylistOptions {
+        items: vec![slice("00:00:00", "00:00:00")],
+        gap_secs: 1,
+        repeat: 0,
+        ..PlaylistOptions::default()
+    };
+    let playlist = Playlist::load(&options, Path::new("transcript"))
+        .await
+        .unwrap();
+    let steps: Vec<_> = playlist
+        .into_stream(ChaosOptions::default(), Arc::new(VirtualClock::new()))
+        .take(50)
+        .collect()
+        .await;
+
+    assert_eq!(steps.len(), 50);
+    assert_eq!(steps[49].as_ref().unwrap().time, 49);
+}
+
+#[tokio::test]
+async fn seeded_shuffles_are_reproducible() {
+    let options = PlaylistOptions {
+        items: vec![
+            slice("0", "0"),
+            slice("3", "3"),
+            slice("6", "6"),
+            slice("9", "9"),
+            slice("12", "12"),
+        ],
+        repeat: 4,
+        shuffle: true,
+        seed: Some(7),
+        ..PlaylistOptions::default()
+    };
+    let (first, _) = play(&options).await;
+    let (second, _) = play(&options).await;
+    assert_eq!(first, second);
+
+    let passes: Vec<Vec<&str>> = first
+        .chunks(5)
+        .map(|pass| {
+            pass.iter()
+                .map(|(_, _, sentence)| sentence.as_str())
+                .collect()
+        })
+        .collect();
+    for pass in &passes {
+        let mut sorted = pass.clone();
+        sorted.sort();
+        let mut expected = passes[0].clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+    }
+    assert!(passes.iter().any(|pass| pass != &passes[0]));
+}
+
+#[tokio::test]
+async fn invalid_playlists_are_rejected() {
+    let dir = Path::new("transcript");
+    for options in [
+        PlaylistOptions::default(),
+        PlaylistOptions::from_filenames(["missing.csv"]),
+        PlaylistOptions {
+            items: vec![slice("00:00:12", "00:00:06")],
+            ..PlaylistOptions::default()
+        },
+        PlaylistOptions {
+            items: vec![slice("00:00:01", "00:00:02")],
+            ..PlaylistOptions::default()
+        },
+    ] {
+        assert!(
+            Playlist::load(&options, dir).await.is_err(),
+            "{:?} should be invalid",
+            options
+        );
+    }
+}
+
+#[tokio::test]
+async fn items_outside_the_transcript_directory_are_rejected() {
+    for filename in [
+        "../transcript/test.csv",
+        "..\\transcript\\test.csv",
+        ".test.csv",
+        "",
+    ] {
+        let options = PlaylistOptions::from_filenames([filename]);
+        let error = Playlist::load(&options, Path::new("transcript"))
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            error.to_string().starts_with("Invalid filename"),
+            "{}",
+            error
+        );
+    }
+}
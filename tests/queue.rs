@@ -0,0 +1,241 @@
+mod common;
+
+use common::{fixture, record};
+use restream::chaos::ChaosOptions;
+use restream::clock::{TokioClock, VirtualClock};
+use restream::interface::{
+    BroadcastHandle, BroadcastSink, Broadcaster, SessionContext, SessionKind,
+};
+use restream::playback::{EventStream, Playback, PlaybackStep, deliver};
+use restream::queue::{
+    JobState, JobStatus, MAX_FINISHED_JOBS, QueueLimits, WebhookJob, WebhookQueue,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Accepts every record without doing any I/O
+struct NullSink;
+
+#[async_trait::async_trait]
+impl BroadcastSink for NullSink {
+    fn describe(&self) -> String {
+        "null".to_string()
+    }
+
+    async fn send(&self, _session: &SessionContext, _step: &PlaybackStep) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn complete(&self, _session: &SessionContext) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl Broadcaster for NullSink {
+    fn broadcast(
+        self: Arc<Self>,
+        session: SessionContext,
+        events: EventStream,
+        cancel: CancellationToken,
+    ) -> BroadcastHandle {
+        deliver(self, session, events, cancel)
+    }
+}
+
+fn job(target: &str, playback: Playback) -> WebhookJob {
+    WebhookJob {
+        target: target.to_string(),
+        filename: "test.csv".to_string(),
+        report_id: None,
+        broadcaster: Arc::new(NullSink),
+        session: SessionContext::new(SessionKind::job_description(), 1),
+        playback: playback.into(),
+    }
+}
+
+/// A job that keeps running for an hour of real time unless cancelled
+fn long_job(target: &str) -> WebhookJob {
+    let records = vec![
+        record("00:00:00", "Alex", "Hello"),
+        record("01:00:00", "Alex", "Hello"),
+    ];
+    job(
+        target,
+        Playback::new(records, ChaosOptions::default(), TokioClock::shared()),
+    )
+}
+
+async fn wait_for(queue: &WebhookQueue, job: &JobStatus, state: JobState) -> JobStatus {
+    for _ in 0..200 {
+        let status = queue.get(&job.job_id).await.unwrap();
+        if status.state == state {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("job {} never reached {:?}", job.job_id, state);
+}
+
+async fn state_of(queue: &WebhookQueue, job: &JobStatus) -> JobState {
+    queue.get(&job.job_id).await.unwrap().state
+}
+
+#[tokio::test]
+async fn per_target_limit_queues_jobs_until_a_slot_frees() {
+    let queue = WebhookQueue::new(QueueLimits {
+        max_running: 4,
+        max_running_per_target: 1,
+        max_queued: 10,
+    });
+
+    let first = queue.submit(long_job("a")).await.unwrap();
+    let second = queue.submit(long_job("a")).await.unwrap();
+    let third = queue.submit(long_job("a")).await.unwrap();
+    let other = queue.submit(long_job("b")).await.unwrap();
+
+    wait_for(&queue, &first, JobState::Running).await;
+    wait_for(&queue, &other, JobState::Running).await;
+    assert_eq!(state_of(&queue, &second).await, JobState::Queued);
+    assert_eq!(state_of(&queue, &third).await, JobState::Queued);
+
+    // A queued job is cancelled without ever running
+    queue.cancel(&third.job_id).await.unwrap();
+    wait_for(&queue, &third, JobState::Cancelled).await;
+
+    // Cancelling the running job hands its slot to the next one for the target
+    queue.cancel(&first.job_id).await.unwrap();
+    wait_for(&queue, &first, JobState::Cancelled).await;
+    wait_for(&queue, &second, JobState::Running).await;
+
+    let listed: Vec<String> = queue.list().await.into_iter().map(|j| j.job_id).collect();
+    assert_eq!(
+        listed,
+        vec![
+            first.job_id,
+            second.job_id.clone(),
+            third.job_id,
+            other.job_id.clone()
+        ]
+    );
+
+    queue.cancel(&second.job_id).await.unwrap();
+    queue.cancel(&other.job_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn global_limit_caps_running_jobs_across_targets() {
+    let queue = WebhookQueue::new(QueueLimits {
+        max_running: 2,
+        max_running_per_target: 2,
+        max_queued: 10,
+    });
+
+    let jobs = vec![
+        queue.submit(long_job("a")).await.unwrap(),
+        queue.submit(long_job("b")).await.unwrap(),
+        queue.submit(long_job("c")).await.unwrap(),
+    ];
+    wait_for(&queue, &jobs[0], JobState::Running).await;
+    wait_for(&queue, &jobs[1], JobState::Running).await;
+    assert_eq!(state_of(&queue, &jobs[2]).await, JobState::Queued);
+
+    queue.cancel(&jobs[0].job_id).await.unwrap();
+    wait_for(&queue, &jobs[2], JobState::Running).await;
+
+    for job in &jobs {
+        queue.cancel(&job.job_id).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn full_queue_rejects_new_jobs() {
+    let queue = WebhookQueue::new(QueueLimits {
+        max_running: 1,
+        max_running_per_target: 1,
+        max_queued: 1,
+    });
+
+    let running = queue.submit(long_job("a")).await.unwrap();
+    wait_for(&queue, &running, JobState::Running).await;
+    let queued = queue.submit(long_job("a")).await.unwrap();
+
+    let error = queue.submit(long_job("a")).await.unwrap_err();
+    assert!(error.to_string().contains("queue is full"), "{}", error);
+    assert_eq!(queue.list().await.len(), 2);
+
+    queue.cancel(&running.job_id).await.unwrap();
+    queue.cancel(&queued.job_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn finished_jobs_keep_their_final_state() {
+    let records = fixture("test.csv").await;
+    let queue = WebhookQueue::new(QueueLimits::default());
+
+    let playback = Playback::new(
+        records.clone(),
+        ChaosOptions::default(),
+        Arc::new(VirtualClock::new()),
+    );
+    let submitted = queue.submit(job("a", playback)).await.unwrap();
+    assert_eq!(submitted.state, JobState::Queued);
+
+    let finished = wait_for(&queue, &submitted, JobState::Completed).await;
+    assert_eq!(finished.delivered, records.len());
+    assert_eq!(finished.error, None);
+
+    // Cancelling a finished job leaves it as it was
+    let cancelled = queue.cancel(&submitted.job_id).await.unwrap();
+    assert_eq!(cancelled.state, JobState::Completed);
+    assert!(queue.cancel("missing").await.is_none());
+}
+
+#[tokio::test]
+async fn acquired_slots_count_against_the_queue_limits() {
+    let queue = WebhookQueue::new(QueueLimits {
+        max_running: 3,
+        max_running_per_target: 1,
+        max_queued: 10,
+    });
+
+    // Two sinks posting to one target take that target's only slot and two global slots
+    let slots = queue
+        .acquire_slots(&["a".to_string(), "a".to_string()])
+        .await;
+    let blocked = queue.submit(long_job("a")).await.unwrap();
+    let other = queue.submit(long_job("b")).await.unwrap();
+    wait_for(&queue, &other, JobState::Running).await;
+    let over = queue.submit(long_job("c")).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(state_of(&queue, &blocked).await, JobState::Queued);
+    assert_eq!(state_of(&queue, &over).await, JobState::Queued);
+
+    drop(slots);
+    wait_for(&queue, &blocked, JobState::Running).await;
+    wait_for(&queue, &over, JobState::Running).await;
+
+    for job in [&blocked, &other, &over] {
+        queue.cancel(&job.job_id).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn oldest_finished_jobs_are_evicted() {
+    let queue = WebhookQueue::new(QueueLimits::default());
+    let mut submitted = Vec::new();
+    for _ in 0..MAX_FINISHED_JOBS + 3 {
+        let playback = Playback::new(
+            vec![record("00:00:00", "Alex", "Hello")],
+            ChaosOptions::default(),
+            Arc::new(VirtualClock::new()),
+        );
+        let job = queue.submit(job("a", playback)).await.unwrap();
+        wait_for(&queue, &job, JobState::Completed).await;
+        submitted.push(job.job_id);
+    }
+
+    let listed: Vec<String> = queue.list().await.into_iter().map(|j| j.job_id).collect();
+    assert_eq!(listed, submitted[3..]);
+    assert!(queue.get(&submitted[0]).await.is_none());
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::fixture;
+use futures_util::StreamExt;
+use restream::chaos::ChaosOptions;
+use restream::clock::{Clock, VirtualClock};
+use restream::playback::Playback;
+use restream::range::{RecordBound, TranscriptRange};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn range(start: Option<&str>, end: Option<&str>, context: usize) -> TranscriptRange {
+    TranscriptRange::parse(start, end, context).unwrap()
+}
+
+#[test]
+fn bounds_are_timestamps_or_indices() {
+    assert_eq!(RecordBound::parse("08:30").unwrap(), RecordBound::Time(510));
+    assert_eq!(
+        RecordBound::parse("01:00:05").unwrap(),
+        RecordBound::Time(3605)
+    );
+    assert_eq!(RecordBound::parse("12").unwrap(), RecordBound::Index(12));
+    for raw in ["", "-1", "8:x", "1:2:3:4", "abc"] {
+        assert!(
+            RecordBound::parse(raw).is_err(),
+            "{:?} should be invalid",
+            raw
+        );
+    }
+
+    assert!(TranscriptRange::parse(Some("00:10"), Some("00:05"), 0).is_err());
+    assert!(TranscriptRange::parse(Some("4"), Some("2"), 0).is_err());
+    // Mixed kinds are only checked against the transcript
+    assert!(TranscriptRange::parse(Some("4"), Some("00:05"), 0).is_ok());
+}
+
+#[tokio::test]
+async fn time_ranges_are_rebased_to_zero() {
+    let records = fixture("test.csv").await;
+    let slice = range(Some("00:00:07"), Some("00:00:15"), 0)
+        .apply(records)
+        .unwrap();
+
+    assert_eq!(slice.offset, 3);
+    assert_eq!(slice.context, 0);
+    let times: Vec<_> = slice.records.iter().map(|r| r.time.as_str()).collect();
+    assert_eq!(times, ["00:00:00", "00:00:03", "00:00:06"]);
+    assert_eq!(
+        slice.records[0].sentence,
+        "I finished the user authentication feature"
+    );
+}
+
+#[tokio::test]
+async fn context_records_are_sent_instantly() {
+    let records = fixture("test.csv").await;
+    let slice = range(Some("4"), None, 2).apply(records).unwrap();
+
+    assert_eq!(slice.offset, 2);
+    assert_eq!(slice.context, 2);
+    let times: Vec<_> = slice.records.iter().map(|r| r.time.as_str()).collect();
+    assert_eq!(
+        times,
+        ["00:00:00", "00:00:00", "00:00:00", "00:00:03", "00:00:06"]
+    );
+
+    let clock = Arc::new(VirtualClock::new());
+    let offsets: Vec<_> = Playback::new(slice.records, ChaosOptions::default(), clock.clone())
+        .into_stream()
+        .map(|step| step.unwrap().time)
+        .collect()
+        .await;
+    assert_eq!(offsets, [0, 0, 0, 3, 6]);
+    assert_eq!(clock.elapsed(), Duration::from_secs(6));
+}
+
+#[tokio::test]
+async fn context_stops_at_the_first_record() {
+    let records = fixture("test.csv").await;
+    let slice = range(Some("1"), Some("1"), 10).apply(records).unwrap();
+
+    assert_eq!(slice.offset, 0);
+    assert_eq!(slice.context, 1);
+    assert_eq!(slice.records.len(), 2);
+}
+
+#[tokio::test]
+async fn empty_ranges_are_rejected() {
+    let records = fixture("test.csv").await;
+    for (start, end) in [
+        (Some("7"), None),
+        (Some("00:01:00"), None),
+        (Some("00:00:04"), Some("00:00:05")),
+        (Some("5"), Some("00:00:03")),
+    ] {
+        assert!(
+            range(start, end, 0).apply(records.clone()).is_err(),
+            "{:?}..{:?} should be empty",
+            start,
+            end
+        );
+    }
+    assert!(range(None, None, 0).apply(Vec::new()).is_err());
+}
@@ -0,0 +1,110 @@
+use restream::recorder::{CapturedEvent, RecordingSession, validate_filename};
+use std::time::Duration;
+
+fn captured(raw: &str) -> restream::recorder::CapturedRecord {
+    match CapturedEvent::parse(raw).unwrap() {
+        CapturedEvent::Envelope { body } => body,
+        CapturedEvent::Record(record) => record,
+        CapturedEvent::Status { status } => panic!("expected a record, got status {}", status),
+    }
+}
+
+#[test]
+fn envelopes_are_unwrapped() {
+    let event = CapturedEvent::parse(
+        r#"{"job_description_enrichment_session": 7, "candidate_profile_enrichment_session": null,
+            "body": {"time": "00:01:00", "speaker": "Mike", "role": "hiring_manager", "sentence": "Hi"}}"#,
+    )
+    .unwrap();
+    let CapturedEvent::Envelope { body } = event else {
+        panic!("expected an envelope, got {:?}", event);
+    };
+    assert_eq!(body.speaker, "Mike");
+    assert_eq!(body.role.as_deref(), Some("hiring_manager"));
+    assert_eq!(body.sentence, "Hi");
+}
+
+#[test]
+fn bare_records_are_accepted() {
+    let event =
+        CapturedEvent::parse(r#"{"time": "00:00:03", "speaker": "Darryll", "sentence": "Hello"}"#)
+            .unwrap();
+    let CapturedEvent::Record(record) = event else {
+        panic!("expected a record, got {:?}", event);
+    };
+    assert_eq!(record.speaker, "Darryll");
+    assert_eq!(record.role, None);
+    assert!(!CapturedEvent::Record(record).is_complete());
+
+    // A record carrying a `status` field is still a record, not a completion
+    let event = CapturedEvent::parse(
+        r#"{"speaker": "Darryll", "sentence": "Done?", "status": "complete"}"#,
+    )
+    .unwrap();
+    assert!(matches!(event, CapturedEvent::Record(_)));
+}
+
+#[test]
+fn completion_markers_end_the_recording() {
+    assert!(
+        CapturedEvent::parse(r#"{"status": "complete"}"#)
+            .unwrap()
+            .is_complete()
+    );
+    assert!(
+        CapturedEvent::parse(" SESSION_COMPLETE\n")
+            .unwrap()
+            .is_complete()
+    );
+    let other = CapturedEvent::parse(r#"{"status": "paused"}"#).unwrap();
+    assert!(matches!(other, CapturedEvent::Status { .. }));
+    assert!(!other.is_complete());
+}
+
+#[test]
+fn unrecognised_events_are_rejected() {
+    for raw in [
+        "hello",
+        r#"{"speaker": "Mike"}"#,
+        r#"{"body": {"speaker": "Mike"}}"#,
+        r#"{"status": 1}"#,
+    ] {
+        let error = CapturedEvent::parse(raw).unwrap_err();
+        assert!(error.to_string().contains("Unrecognised event"), "{}", raw);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn times_are_rebased_to_the_first_event() {
+    let mut session = RecordingSession::new("capture.csv".to_string());
+
+    // Time before the first event is not part of the recording
+    tokio::time::advance(Duration::from_secs(30)).await;
+    session.capture(captured(
+        r#"{"time": "01:00:00", "speaker": "Mike", "sentence": "First"}"#,
+    ));
+    tokio::time::advance(Duration::from_millis(2_400)).await;
+    session.capture(captured(r#"{"speaker": "Darryll", "sentence": "Second"}"#));
+    tokio::time::advance(Duration::from_millis(1_600)).await;
+    session.capture(captured(r#"{"speaker": "Mike", "sentence": "Third"}"#));
+
+    let times: Vec<&str> = session.records.iter().map(|r| r.time.as_str()).collect();
+    // Offsets are rounded to the nearest second
+    assert_eq!(times, ["00:00:00", "00:00:02", "00:00:04"]);
+    assert_eq!(session.records[1].speaker, "Darryll");
+}
+
+#[test]
+fn recording_filenames_stay_in_the_catalog() {
+    assert!(validate_filename("capture.csv").is_ok());
+    for filename in [
+        "",
+        "../capture.csv",
+        "dir/capture.csv",
+        "dir\\capture.csv",
+        ".hidden.csv",
+        "capture.json",
+    ] {
+        assert!(validate_filename(filename).is_err(), "{}", filename);
+    }
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::{fixture, record};
+use restream::interface::TranscriptRecord;
+use restream::redact::{RedactionMode, RedactionOptions, Redactor};
+use restream::speakers::{SpeakerMetadata, SpeakerOverrides};
+use restream::transform::TranscriptTransforms;
+use std::path::Path;
+
+#[test]
+fn redacts_each_kind_with_consistent_tokens() {
+    let mut records = vec![
+        record(
+            "00:00:00",
+            "Alex",
+            "Mail jo.smith@mail.com or call +44 7700 900123 - ask for Jo at Acme Ltd.",
+        ),
+        record(
+            "00:00:00",
+            "Sam",
+            "Thanks Alex. Jo said £80-100k, and acme ltd agreed. Call +447700900123 again?",
+        ),
+    ];
+    let mut redactor = Redactor::new(RedactionOptions {
+        names: vec!["Jo".to_string()],
+        companies: vec!["Acme Ltd".to_string()],
+        ..Default::default()
+    });
+    redactor.learn_names(&records, &[]);
+    redactor.apply(&mut records);
+
+    assert_eq!(
+        records[0].sentence,
+        "Mail [EMAIL_1] or call [PHONE_1] - ask for [NAME_1] at [COMPANY_1]."
+    );
+    assert_eq!(
+        records[1].sentence,
+        "Thanks [NAME_2]. [NAME_1] said [SALARY_1], and [COMPANY_1] agreed. Call [PHONE_1] again?"
+    );
+    assert_eq!(records[0].speaker, "[NAME_2]");
+    assert_eq!(records[1].speaker, "[NAME_3]");
+}
+
+#[test]
+fn leaves_times_and_counts_alone() {
+    let mut redactor = Redactor::new(RedactionOptions::default());
+    let sentence = "We meet at 10:30 on the 3rd, with 5 years experience and 12 people.";
+    assert_eq!(redactor.redact_text(sentence), sentence);
+}
+
+#[test]
+fn pseudonymize_uses_stand_in_values() {
+    let mut redactor = Redactor::new(RedactionOptions {
+        mode: RedactionMode::Pseudonymize,
+        names: vec!["Jo".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(
+        redactor.redact_text("Jo (jo@corp.io) wants $120k."),
+        "Person 1 (user1@example.com) wants $50k."
+    );
+}
+
+#[test]
+fn disabled_categories_are_kept() {
+    let mut records = vec![record("00:00:00", "Alex", "Alex earns £50k, alex@corp.io")];
+    let mut redactor = Redactor::new(RedactionOptions {
+        emails: false,
+        salaries: false,
+        speaker_names: false,
+        ..Default::default()
+    });
+    redactor.learn_names(&records, &[]);
+    redactor.apply(&mut records);
+    assert_eq!(records[0].sentence, "Alex earns £50k, alex@corp.io");
+    assert_eq!(records[0].speaker, "Alex");
+}
+
+#[test]
+fn speakers_labelled_by_their_role_are_not_names() {
+    let with_role = |speaker: &str, role: &str, sentence: &str| TranscriptRecord {
+        role: Some(role.to_string()),
+        ..record("00:00:00", speaker, sentence)
+    };
+    let mut records = vec![
+        with_role("Interviewer", "interviewer", "Thanks for joining, Sam."),
+        with_role("Candidate", "candidate", "Happy to be here."),
+        record(
+            "00:00:00",
+            "Sam",
+            "The Interviewer asked the Candidate first.",
+        ),
+    ];
+    let mut transforms = TranscriptTransforms::new(
+        SpeakerOverrides::default(),
+        Some(RedactionOptions::default()),
+    );
+    transforms.apply(&SpeakerMetadata::new(), &mut records);
+
+    assert_eq!(records[0].speaker, "Interviewer");
+    assert_eq!(records[1].speaker, "Candidate");
+    assert_eq!(records[0].sentence, "Thanks for joining, [NAME_1].");
+    assert_eq!(
+        records[2].sentence,
+        "The Interviewer asked the Candidate first."
+    );
+}
+
+#[test]
+fn from_params_enables_on_request() {
+    assert_eq!(
+        RedactionOptions::from_params(None, None, vec![], vec![]),
+        None
+    );
+    assert_eq!(
+        RedactionOptions::from_params(Some(true), None, vec![], vec![]),
+        Some(RedactionOptions::default())
+    );
+    let names = vec!["Jo".to_string()];
+    assert_eq!(
+        RedactionOptions::from_params(None, None, names.clone(), vec![]).map(|o| o.names),
+        Some(names.clone())
+    );
+    assert_eq!(
+        RedactionOptions::from_params(Some(false), None, names, vec![]),
+        None
+    );
+}
+
+#[tokio::test]
+async fn fixture_leaks_no_names_companies_or_salaries() {
+    let mut transforms = TranscriptTransforms::new(
+        SpeakerOverrides::default(),
+        Some(RedactionOptions {
+            companies: vec!["BetSquad".to_string()],
+            ..Default::default()
+        }),
+    );
+    let records = transforms
+        .load(Path::new("transcript/intake_call.csv"))
+        .await
+        .unwrap();
+    assert_eq!(records.len(), fixture("intake_call.csv").await.len());
+    for record in &records {
+        for leak in ["Darryll", "Mike", "BetSquad", "£80"] {
+            assert!(!record.sentence.contains(leak), "{}", record.sentence);
+        }
+    }
+    assert!(records[0].sentence.starts_with("Hi [NAME_1], "));
+    assert_eq!(records[0].speaker, "[NAME_2]");
+    // Sidecar roles still apply to redacted speakers
+    assert_eq!(records[0].role.as_deref(), Some("recruiter"));
+}
+
+#[tokio::test]
+async fn renamed_speakers_are_still_redacted_in_sentences() {
+    let mut transforms = TranscriptTransforms::new(
+        SpeakerOverrides::parse(&["Mike=Hiring Manager".to_string()], &[], false).unwrap(),
+        Some(RedactionOptions::default()),
+    );
+    let records = transforms
+        .load(Path::new("transcript/intake_call_test.csv"))
+        .await
+        .unwrap();
+    assert_eq!(records[1].speaker, "Hiring Manager");
+    assert!(records[0].sentence.starts_with("Hi [NAME_1], "));
+}
@@ -0,0 +1,212 @@
+use restream::report::{
+    DeliveryResult, JsonExpectation, MAX_FINISHED_REPORTS, MAX_REPORTED_DELIVERIES, ReportHandle,
+    ReportStore, WebhookExpectations, WebhookReport, insert_report,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn report(report_id: &str) -> WebhookReport {
+    WebhookReport::new(
+        report_id.to_string(),
+        "test.csv".to_string(),
+        "http://localhost/hook".to_string(),
+        WebhookExpectations::default(),
+    )
+}
+
+#[tokio::test]
+async fn oldest_finished_reports_are_evicted() {
+    let reports: ReportStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    insert_report(&reports, report("running")).await;
+    for i in 0..MAX_FINISHED_REPORTS + 5 {
+        let mut finished = report(&format!("finished-{}", i));
+        finished.finish(None);
+        finished.finished_at = Some(format!("2026-01-01T00:{:02}:{:02}+00:00", i / 60, i % 60));
+        insert_report(&reports, finished).await;
+    }
+
+    {
+        let reports = reports.lock().await;
+        assert_eq!(reports.len(), MAX_FINISHED_REPORTS + 1);
+        assert!(reports.contains_key("running"));
+        assert!(!reports.contains_key("finished-4"));
+        assert!(reports.contains_key("finished-5"));
+    }
+
+    // Finishing a report makes room by evicting another
+    ReportHandle {
+        store: reports.clone(),
+        report_id: "running".to_string(),
+    }
+    .finish(None)
+    .await;
+    let reports = reports.lock().await;
+    assert_eq!(reports.len(), MAX_FINISHED_REPORTS);
+    assert!(reports.contains_key("running"));
+    assert!(!reports.contains_key("finished-5"));
+}
+
+#[tokio::test]
+async fn expected_statuses_are_looked_up_in_the_report() {
+    let reports: ReportStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    let mut expecting = report("report");
+    expecting.expectations.status_codes = vec![404, 422];
+    insert_report(&reports, expecting).await;
+    let handle = ReportHandle {
+        store: reports,
+        report_id: "report".to_string(),
+    };
+    assert!(handle.expects_status(422).await);
+    assert!(!handle.expects_status(500).await);
+}
+
+#[test]
+fn expectations_parse_paths_and_values() {
+    let exists = JsonExpectation::parse(" data.id ").unwrap();
+    assert_eq!(exists.path, "data.id");
+    assert_eq!(exists.equals, None);
+
+    // Values are read as JSON, or kept as plain strings
+    assert_eq!(
+        JsonExpectation::parse("ok=true").unwrap().equals,
+        Some(json!(true))
+    );
+    assert_eq!(
+        JsonExpectation::parse("count=3").unwrap().equals,
+        Some(json!(3))
+    );
+    assert_eq!(
+        JsonExpectation::parse("status=accepted").unwrap().equals,
+        Some(json!("accepted"))
+    );
+    assert_eq!(
+        JsonExpectation::parse(r#"status="3""#).unwrap().equals,
+        Some(json!("3"))
+    );
+    // Only the first `=` separates the value
+    assert_eq!(
+        JsonExpectation::parse("query=a=b").unwrap().equals,
+        Some(json!("a=b"))
+    );
+
+    for raw in ["", "  ", "$", "=1"] {
+        assert!(JsonExpectation::parse(raw).is_err(), "{:?}", raw);
+    }
+}
+
+#[test]
+fn expectations_check_paths_in_the_body() {
+    let body = json!({
+        "data": {"items": [{"id": 7}, {"id": 8}], "a/b": "slash", "flag": false},
+        "status": "accepted"
+    });
+    let check = |raw: &str| JsonExpectation::parse(raw).unwrap().check(Some(&body));
+
+    // Dotted paths, `$.` prefixes and JSON pointers, with array indices
+    assert_eq!(check("data.items.1.id=8"), None);
+    assert_eq!(check("$.data.items.0.id"), None);
+    assert_eq!(check("/data/items/0/id=7"), None);
+    assert_eq!(check("data.a/b=slash"), None);
+    // A present `false` still exists
+    assert_eq!(check("data.flag"), None);
+
+    assert_eq!(
+        check("data.missing"),
+        Some("data.missing: path not found".to_string())
+    );
+    assert_eq!(
+        check("data.items.5.id"),
+        Some("data.items.5.id: path not found".to_string())
+    );
+    assert_eq!(
+        check("data.items.first"),
+        Some("data.items.first: path not found".to_string())
+    );
+    assert_eq!(
+        check("status.code"),
+        Some("status.code: path not found".to_string())
+    );
+    // Types must match: the number 7 is not the string "7"
+    assert_eq!(
+        check(r#"data.items.0.id="7""#),
+        Some(r#"data.items.0.id: expected "7", got 7"#.to_string())
+    );
+    assert_eq!(
+        check("status=rejected"),
+        Some(r#"status: expected "rejected", got "accepted""#.to_string())
+    );
+
+    assert_eq!(
+        JsonExpectation::parse("status").unwrap().check(None),
+        Some("status: response body is not JSON".to_string())
+    );
+}
+
+#[test]
+fn deliveries_are_evaluated_against_every_expectation() {
+    let expectations = WebhookExpectations {
+        status_codes: vec![200],
+        json: vec![JsonExpectation::parse("ok=true").unwrap()],
+        max_latency_ms: Some(100),
+    };
+    let delivery = DeliveryResult {
+        record_index: Some(0),
+        status: Some(200),
+        latency_ms: 20,
+        body: Some(r#"{"ok": true}"#.to_string()),
+        ..DeliveryResult::default()
+    };
+    assert!(expectations.evaluate(&delivery).is_empty());
+
+    let failing = DeliveryResult {
+        status: Some(500),
+        latency_ms: 250,
+        body: Some("<html>".to_string()),
+        ..delivery
+    };
+    assert_eq!(
+        expectations.evaluate(&failing),
+        [
+            "status 500 not in expected [200]",
+            "latency 250ms exceeds 100ms",
+            "ok: response body is not JSON",
+        ]
+    );
+
+    let unanswered = DeliveryResult {
+        error: Some("connection refused".to_string()),
+        ..DeliveryResult::default()
+    };
+    assert_eq!(
+        expectations.evaluate(&unanswered),
+        ["no response: connection refused"]
+    );
+}
+
+#[test]
+fn long_reports_keep_the_first_and_latest_deliveries() {
+    let mut report = report("long");
+    report.expectations.status_codes = vec![200];
+    let total = MAX_REPORTED_DELIVERIES + 10;
+    for index in 0..total {
+        report.push(DeliveryResult {
+            record_index: Some(index),
+            status: Some(500),
+            ..Default::default()
+        });
+    }
+
+    assert_eq!(report.deliveries.len(), MAX_REPORTED_DELIVERIES);
+    assert_eq!(report.omitted_deliveries, 10);
+    // Omitted deliveries still count towards the verdict
+    assert_eq!(report.failed_deliveries, total);
+    let half = MAX_REPORTED_DELIVERIES / 2;
+    let indices: Vec<usize> = report
+        .deliveries
+        .iter()
+        .map(|delivery| delivery.record_index.unwrap())
+        .collect();
+    assert_eq!(indices[..half], (0..half).collect::<Vec<_>>());
+    assert_eq!(indices[half..], (half + 10..total).collect::<Vec<_>>());
+}
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Duration, Utc};
+use restream::adapter::SinkConfig;
+use restream::adapter::composite::{CompositeBroadcastRequest, FileSinkConfig};
+use restream::schedule::{
+    CronSchedule, Schedule, ScheduleRequest, ScheduleState, ScheduleStore, spawn_scheduler,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn utc(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn next(expression: &str, after: &str) -> Option<String> {
+    CronSchedule::parse(expression)
+        .unwrap()
+        .next_after(utc(after))
+        .map(|time| time.to_rfc3339())
+}
+
+fn broadcast() -> CompositeBroadcastRequest {
+    CompositeBroadcastRequest {
+        filename: "test.csv".to_string(),
+        job_description_enrichment_session: Some(1),
+        candidate_profile_enrichment_session: None,
+        session_kind: None,
+        session_id: None,
+        sinks: vec![SinkConfig::File(FileSinkConfig {
+            filename: "nightly.jsonl".to_string(),
+        })],
+        chaos: Default::default(),
+        start_delay_secs: 0,
+        playlist: None,
+        speakers: Default::default(),
+        redaction: None,
+    }
+}
+
+fn request(at: Option<&str>, cron: Option<&str>) -> ScheduleRequest {
+    ScheduleRequest {
+        name: Some("nightly".to_string()),
+        at: at.map(str::to_string),
+        cron: cron.map(str::to_string),
+        broadcast: broadcast(),
+    }
+}
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "restream-schedules-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn cron_expressions_find_the_next_matching_minute() {
+    let cases = [
+        (
+            "0 2 * * *",
+            "2026-01-31T03:00:00Z",
+            "2026-02-01T02:00:00+00:00",
+        ),
+        (
+            "*/15 * * * *",
+            "2026-03-01T10:07:30Z",
+            "2026-03-01T10:15:00+00:00",
+        ),
+        (
+            "*/15 * * * *",
+            "2026-03-01T10:15:00Z",
+            "2026-03-01T10:30:00+00:00",
+        ),
+        // Saturday to the following Monday
+        (
+            "0 9 * * 1-5",
+            "2026-10-17T12:00:00Z",
+            "2026-10-19T09:00:00+00:00",
+        ),
+        (
+            "30 8 * * 7",
+            "2026-10-17T12:00:00Z",
+            "2026-10-18T08:30:00+00:00",
+        ),
+        // Both day fields restricted: either one matches
+        (
+            "0 0 13 * 5",
+            "2026-10-10T00:00:00Z",
+            "2026-10-13T00:00:00+00:00",
+        ),
+        (
+            "@monthly",
+            "2026-12-15T00:00:00Z",
+            "2027-01-01T00:00:00+00:00",
+        ),
+        (
+            "0 12 29 2 *",
+            "2026-03-01T00:00:00Z",
+            "2028-02-29T12:00:00+00:00",
+        ),
+    ];
+    for (expression, after, expected) in cases {
+        assert_eq!(
+            next(expression, after).as_deref(),
+            Some(expected),
+            "{} after {}",
+            expression,
+            after
+        );
+    }
+
+    assert_eq!(next("0 0 30 2 *", "2026-01-01T00:00:00Z"), None);
+}
+
+#[test]
+fn invalid_cron_expressions_are_rejected() {
+    for expression in [
+        "61 * * * *",
+        "* * *",
+        "*/0 * * * *",
+        "0 0 0 * *",
+        "5-1 * * * *",
+    ] {
+        assert!(
+            CronSchedule::parse(expression).is_err(),
+            "{} should be invalid",
+            expression
+        );
+    }
+}
+
+#[test]
+fn schedules_need_exactly_one_future_timing() {
+    let now = utc("2026-10-18T12:00:00Z");
+
+    let once = Schedule::new(request(Some("2026-10-19T02:00:00Z"), None), now).unwrap();
+    assert_eq!(once.state, ScheduleState::Active);
+    assert_eq!(once.next_run.as_deref(), Some("2026-10-19T02:00:00+00:00"));
+
+    let nightly = Schedule::new(request(None, Some("0 2 * * *")), now).unwrap();
+    assert_eq!(
+        nightly.next_run.as_deref(),
+        Some("2026-10-19T02:00:00+00:00")
+    );
+
+    for (at, cron) in [
+        (None, None),
+        (Some("2026-10-19T02:00:00Z"), Some("0 2 * * *")),
+        (Some("2026-10-18T11:00:00Z"), None),
+        (Some("tomorrow"), None),
+        (None, Some("0 0 30 2 *")),
+    ] {
+        assert!(
+            Schedule::new(request(at, cron), now).is_err(),
+            "{:?} {:?}",
+            at,
+            cron
+        );
+    }
+}
+
+#[tokio::test]
+async fn due_schedules_advance_and_persist() {
+    let path = store_path("due");
+    let store = ScheduleStore::load(&path).unwrap();
+    let now = utc("2026-10-18T12:00:00Z");
+
+    let once = Schedule::new(request(Some("2026-10-19T02:00:00Z"), None), now).unwrap();
+    let nightly = Schedule::new(request(None, Some("0 2 * * *")), now).unwrap();
+    store.add(once.clone()).await;
+    store.add(nightly.clone()).await;
+
+    assert!(store.take_due(now).await.is_empty());
+
+    let run_at = utc("2026-10-19T02:00:30Z");
+    let due = store.take_due(run_at).await;
+    assert_eq!(due.len(), 2);
+    // Already advanced: running again at the same time fires nothing
+    assert!(store.take_due(run_at).await.is_empty());
+
+    store
+        .record_run(&nightly.schedule_id, &Ok("broadcast-1".to_string()))
+        .await;
+    store
+        .record_run(&once.schedule_id, &Err(anyhow::anyhow!("no sinks")))
+        .await;
+
+    // Reloading from disk keeps the advanced state
+    let reloaded = ScheduleStore::load(&path).unwrap();
+    let once = reloaded.get(&once.schedule_id).await.unwrap();
+    assert_eq!(once.state, ScheduleState::Completed);
+    assert_eq!(once.next_run, None);
+    assert_eq!(once.runs, 1);
+    assert_eq!(once.last_error.as_deref(), Some("no sinks"));
+    let nightly = reloaded.get(&nightly.schedule_id).await.unwrap();
+    assert_eq!(nightly.state, ScheduleState::Active);
+    assert_eq!(
+        nightly.next_run.as_deref(),
+        Some("2026-10-20T02:00:00+00:00")
+    );
+    assert_eq!(nightly.last_broadcast_id.as_deref(), Some("broadcast-1"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn paused_schedules_do_not_fire_and_resume_from_the_next_run() {
+    let path = store_path("pause");
+    let store = ScheduleStore::load(&path).unwrap();
+    let now = utc("2026-10-18T12:00:00Z");
+    let nightly = Schedule::new(request(None, Some("0 2 * * *")), now).unwrap();
+    let id = nightly.schedule_id.clone();
+    store.add(nightly).await;
+
+    let paused = store.set_paused(&id, true, now).await.unwrap().unwrap();
+    assert_eq!(paused.state, ScheduleState::Paused);
+    assert!(store.take_due(utc("2026-10-21T03:00:00Z")).await.is_empty());
+
+    // Runs missed while paused are skipped
+    let resumed = store
+        .set_paused(&id, false, utc("2026-10-21T03:00:00Z"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(resumed.state, ScheduleState::Active);
+    assert_eq!(
+        resumed.next_run.as_deref(),
+        Some("2026-10-22T02:00:00+00:00")
+    );
+
+    assert!(store.set_paused("missing", true, now).await.is_none());
+    assert!(store.delete(&id).await);
+    assert!(!store.delete(&id).await);
+    assert!(ScheduleStore::load(&path).unwrap().is_empty().await);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn scheduler_starts_due_broadcasts() {
+    let path = store_path("scheduler");
+    let store = Arc::new(ScheduleStore::load(&path).unwrap());
+    let launched = Arc::new(AtomicUsize::new(0));
+
+    let counter = launched.clone();
+    let scheduler = spawn_scheduler(store.clone(), move |request| {
+        let counter = counter.clone();
+        async move {
+            assert_eq!(request.filename, "test.csv");
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok("broadcast-1".to_string())
+        }
+    });
+
+    // Added after the scheduler went to sleep, so it must wake up for it
+    let at = (Utc::now() + Duration::milliseconds(300)).to_rfc3339();
+    let once = Schedule::new(request(Some(&at), None), Utc::now()).unwrap();
+    store.add(once.clone()).await;
+
+    for _ in 0..100 {
+        if launched.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    scheduler.abort();
+
+    assert_eq!(launched.load(Ordering::SeqCst), 1);
+    let once = store.get(&once.schedule_id).await.unwrap();
+    assert_eq!(once.state, ScheduleState::Completed);
+    assert_eq!(once.last_broadcast_id.as_deref(), Some("broadcast-1"));
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,141 @@
+use restream::clock::VirtualClock;
+use restream::script::{ReplySender, Script, ScriptPlayback, reply_text};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+fn script() -> Script {
+    serde_json::from_value(serde_json::json!({
+        "start": "intro",
+        "timeout_secs": 30,
+        "nodes": {
+            "intro": {
+                "lines": [
+                    { "speaker": "Mike", "sentence": "Hi, I'm hiring a backend engineer." },
+                    { "speaker": "Mike", "sentence": "What's your fee?", "pause_secs": 2 }
+                ],
+                "branches": [
+                    { "regex": "(?P<fee>\\d+)\\s?%", "next": "fee" },
+                    { "keywords": ["depends", "negotiable"], "next": "vague" }
+                ],
+                "otherwise": "repeat",
+                "on_timeout": "nudge"
+            },
+            "fee": {
+                "lines": [{ "speaker": "Mike", "sentence": "{{fee}}% works for us." }]
+            },
+            "vague": {
+                "lines": [{ "speaker": "Mike", "sentence": "I need a number." }],
+                "next": "fee"
+            },
+            "repeat": {
+                "lines": [{ "speaker": "Mike", "sentence": "Sorry, \"{{reply}}\"?" }]
+            },
+            "nudge": {
+                "lines": [{ "speaker": "Mike", "sentence": "Are you still there?" }]
+            }
+        }
+    }))
+    .unwrap()
+}
+
+fn start() -> (ReplySender, ScriptPlayback) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let playback = ScriptPlayback::new(script(), rx, Arc::new(VirtualClock::new()));
+    (tx, playback)
+}
+
+async fn sentence(playback: &mut ScriptPlayback) -> Option<String> {
+    let step = playback.next().await?.unwrap();
+    Some(step.record.sentence)
+}
+
+#[tokio::test]
+async fn regex_branch_fills_named_groups() {
+    let (tx, mut playback) = start();
+    assert_eq!(
+        sentence(&mut playback).await.unwrap(),
+        "Hi, I'm hiring a backend engineer."
+    );
+    let step = playback.next().await.unwrap().unwrap();
+    assert_eq!(step.time, 2);
+    tx.send(r#"{"reply": "We charge 18 % of base"}"#.to_string())
+        .unwrap();
+    assert_eq!(sentence(&mut playback).await.unwrap(), "18% works for us.");
+    assert_eq!(sentence(&mut playback).await, None);
+}
+
+#[tokio::test]
+async fn keyword_branch_and_next() {
+    let (tx, mut playback) = start();
+    sentence(&mut playback).await;
+    sentence(&mut playback).await;
+    tx.send("It DEPENDS on the role".to_string()).unwrap();
+    assert_eq!(sentence(&mut playback).await.unwrap(), "I need a number.");
+    // `fee` is reached through `next`, before any group was captured
+    assert_eq!(
+        sentence(&mut playback).await.unwrap(),
+        "{{fee}}% works for us."
+    );
+}
+
+#[tokio::test]
+async fn unmatched_reply_goes_to_otherwise() {
+    let (tx, mut playback) = start();
+    sentence(&mut playback).await;
+    sentence(&mut playback).await;
+    tx.send("hello?".to_string()).unwrap();
+    assert_eq!(sentence(&mut playback).await.unwrap(), "Sorry, \"hello?\"?");
+}
+
+#[tokio::test]
+async fn replies_before_the_question_are_stale() {
+    let (tx, mut playback) = start();
+    sentence(&mut playback).await;
+    tx.send("20%".to_string()).unwrap();
+    sentence(&mut playback).await;
+    tx.send("negotiable".to_string()).unwrap();
+    assert_eq!(sentence(&mut playback).await.unwrap(), "I need a number.");
+}
+
+#[tokio::test]
+async fn silence_goes_to_on_timeout() {
+    let (_tx, mut playback) = start();
+    sentence(&mut playback).await;
+    sentence(&mut playback).await;
+    let step = playback.next().await.unwrap().unwrap();
+    assert_eq!(step.record.sentence, "Are you still there?");
+    assert_eq!(step.time, 32);
+    assert_eq!(step.index, 2);
+}
+
+#[test]
+fn validate_rejects_bad_scripts() {
+    let mut unknown = script();
+    unknown.nodes.get_mut("intro").unwrap().on_timeout = Some("missing".to_string());
+    assert!(unknown.validate().is_err());
+
+    let mut regex = script();
+    regex.nodes.get_mut("intro").unwrap().branches[0].regex = Some("(".to_string());
+    assert!(regex.validate().is_err());
+
+    let mut both = script();
+    both.nodes.get_mut("intro").unwrap().next = Some("fee".to_string());
+    assert!(both.validate().is_err());
+
+    assert!(script().validate().is_ok());
+}
+
+#[test]
+fn bundled_script_is_valid() {
+    Script::load(Path::new("scripts/hiring_manager.json")).unwrap();
+}
+
+#[test]
+fn reply_text_reads_common_shapes() {
+    assert_eq!(reply_text("plain words"), "plain words");
+    assert_eq!(reply_text(r#""quoted""#), "quoted");
+    assert_eq!(reply_text(r#"{"message": "hi"}"#), "hi");
+    assert_eq!(reply_text(r#"{"body": {"sentence": "hey"}}"#), "hey");
+    assert_eq!(reply_text(r#"{"status": "ok"}"#), r#"{"status": "ok"}"#);
+}
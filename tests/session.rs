@@ -0,0 +1,127 @@
+mod common;
+
+use common::record;
+use restream::interface::{BroadcastMessage, WebSocketMessage};
+use restream::session::{
+    SessionContext, SessionFields, SessionId, SessionKind, SessionKinds, SessionParams,
+};
+use serde_json::json;
+
+#[test]
+fn legacy_kinds_keep_the_existing_message_shape() {
+    let session = SessionContext::new(SessionKind::candidate_profile(), 3);
+    let message = serde_json::to_value(WebSocketMessage::new(
+        &session,
+        record("00:00:01", "Alex", "Hello"),
+    ))
+    .unwrap();
+
+    assert_eq!(
+        message,
+        json!({
+            "job_description_enrichment_session": null,
+            "candidate_profile_enrichment_session": 3,
+            "body": { "time": "00:00:01", "speaker": "Alex", "sentence": "Hello" }
+        })
+    );
+}
+
+#[test]
+fn configured_kinds_get_their_own_field_and_text_ids() {
+    let kinds = SessionKinds::new(["interview_debrief"]).unwrap();
+    let session = SessionContext::new(
+        kinds.get("interview_debrief").unwrap(),
+        SessionId::parse("deb-42").unwrap(),
+    );
+    let message = serde_json::to_value(BroadcastMessage::new(
+        &session,
+        record("00:00:01", "Alex", "Hello"),
+    ))
+    .unwrap();
+
+    assert_eq!(message["interview_debrief_enrichment_session"], "deb-42");
+    assert!(message["job_description_enrichment_session"].is_null());
+    assert!(message["candidate_profile_enrichment_session"].is_null());
+}
+
+#[test]
+fn only_canonical_integers_become_numeric_ids() {
+    assert_eq!(SessionId::parse("42").unwrap(), SessionId::Number(42));
+    assert_eq!(SessionId::parse("-3").unwrap(), SessionId::Number(-3));
+    for raw in ["007", "+7", "-0", "00"] {
+        assert_eq!(
+            SessionId::parse(raw).unwrap(),
+            SessionId::Text(raw.to_string())
+        );
+    }
+}
+
+#[test]
+fn messages_round_trip() {
+    let session = SessionContext::new(SessionKind::new("reference_check").unwrap(), 9);
+    let raw = serde_json::to_string(&BroadcastMessage::new(
+        &session,
+        record("00:00:01", "Alex", "Hello"),
+    ))
+    .unwrap();
+
+    let parsed: BroadcastMessage = serde_json::from_str(&raw).unwrap();
+    assert_eq!(parsed.session, session);
+    assert_eq!(parsed.body.sentence, "Hello");
+}
+
+#[test]
+fn empty_session_fields_serialize_as_nulls() {
+    assert_eq!(
+        serde_json::to_value(SessionFields::default()).unwrap(),
+        json!({
+            "job_description_enrichment_session": null,
+            "candidate_profile_enrichment_session": null
+        })
+    );
+}
+
+#[test]
+fn resolve_requires_exactly_one_session() {
+    let kinds = SessionKinds::new(["reference_check"]).unwrap();
+
+    let session = SessionParams {
+        job_description_enrichment_session: Some("7".to_string()),
+        ..Default::default()
+    }
+    .resolve(&kinds)
+    .unwrap();
+    assert_eq!(
+        session,
+        SessionContext::new(SessionKind::job_description(), 7)
+    );
+
+    let session = SessionParams {
+        session_kind: Some("reference_check".to_string()),
+        session_id: Some("ref-1".to_string()),
+        ..Default::default()
+    }
+    .resolve(&kinds)
+    .unwrap();
+    assert_eq!(session.id, SessionId::Text("ref-1".to_string()));
+
+    assert!(SessionParams::default().resolve(&kinds).is_err());
+    assert!(
+        SessionParams {
+            job_description_enrichment_session: Some("1".to_string()),
+            candidate_profile_enrichment_session: Some("2".to_string()),
+            ..Default::default()
+        }
+        .resolve(&kinds)
+        .is_err()
+    );
+    assert!(
+        SessionParams {
+            session_kind: Some("unknown".to_string()),
+            session_id: Some("1".to_string()),
+            ..Default::default()
+        }
+        .resolve(&kinds)
+        .is_err()
+    );
+}
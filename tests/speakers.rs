@@ -0,0 +1,162 @@
+mod common;
+
+use common::fixture;
+use restream::interface::{BroadcastMessage, TranscriptRecord};
+use restream::session::{SessionContext, SessionId, SessionKind};
+use restream::speakers::{
+    SpeakerInfo, SpeakerMapper, SpeakerMetadata, SpeakerOverrides, load_with_speakers, sidecar_path,
+};
+use restream::transcript::{load_transcript_from_file, write_transcript_to_file};
+use std::path::Path;
+
+fn speakers(records: &[TranscriptRecord]) -> Vec<(&str, Option<&str>)> {
+    records
+        .iter()
+        .map(|record| (record.speaker.as_str(), record.role.as_deref()))
+        .collect()
+}
+
+fn metadata() -> SpeakerMetadata {
+    SpeakerMetadata::from([
+        (
+            "Alex".to_string(),
+            SpeakerInfo {
+                role: Some("interviewer".to_string()),
+                display_name: Some("Alex P.".to_string()),
+            },
+        ),
+        (
+            "Jordan".to_string(),
+            SpeakerInfo {
+                role: Some("candidate".to_string()),
+                display_name: None,
+            },
+        ),
+    ])
+}
+
+#[test]
+fn sidecars_sit_next_to_the_transcript() {
+    assert_eq!(
+        sidecar_path(Path::new("transcript/intake_call.csv")),
+        Path::new("transcript/intake_call.speakers.json")
+    );
+}
+
+#[tokio::test]
+async fn metadata_sets_roles_and_display_names() {
+    let mut records = fixture("test.csv").await;
+    SpeakerMapper::default().apply(&metadata(), &mut records);
+
+    assert_eq!(
+        speakers(&records[..4]),
+        [
+            ("Alex P.", Some("interviewer")),
+            ("Jordan", Some("candidate")),
+            ("Alex P.", Some("interviewer")),
+            ("Taylor", None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn overrides_win_and_anonymous_numbers_stick_across_transcripts() {
+    let overrides = SpeakerOverrides::parse(
+        &["Jordan=Interviewer".to_string()],
+        &["Taylor=candidate".to_string(), "Alex=observer".to_string()],
+        true,
+    )
+    .unwrap();
+    let mut mapper = SpeakerMapper::new(overrides);
+
+    let mut first = fixture("test.csv").await;
+    mapper.apply(&metadata(), &mut first);
+    assert_eq!(
+        speakers(&first[..4]),
+        [
+            ("Speaker 1", Some("observer")),
+            ("Interviewer", Some("candidate")),
+            ("Speaker 1", Some("observer")),
+            ("Speaker 2", Some("candidate")),
+        ]
+    );
+
+    // Same mapper, so a second transcript keeps the numbering
+    let mut second = fixture("test.csv").await;
+    second.reverse();
+    mapper.apply(&SpeakerMetadata::new(), &mut second);
+    assert_eq!(second[0].speaker, "Speaker 2");
+    assert_eq!(second.last().unwrap().speaker, "Speaker 1");
+}
+
+#[test]
+fn override_pairs_must_have_both_sides() {
+    for pair in ["Alex", "=x", "Alex=", " = "] {
+        assert!(
+            SpeakerOverrides::parse(&[pair.to_string()], &[], false).is_err(),
+            "{:?} should be invalid",
+            pair
+        );
+    }
+    assert!(SpeakerOverrides::default().is_empty());
+}
+
+#[tokio::test]
+async fn bundled_intake_calls_have_roles() {
+    let records = load_with_speakers(
+        Path::new("transcript/intake_call.csv"),
+        &mut SpeakerMapper::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        speakers(&records[..2]),
+        [
+            ("Darryll", Some("recruiter")),
+            ("Mike", Some("hiring_manager"))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn roles_are_in_message_bodies_only_when_known() {
+    let session = SessionContext::new(SessionKind::job_description(), SessionId::Number(1));
+    let mut record = fixture("test.csv").await.remove(0);
+
+    let body = |record: &TranscriptRecord| {
+        serde_json::to_value(BroadcastMessage::new(&session, record.clone())).unwrap()["body"]
+            .clone()
+    };
+    assert!(body(&record).get("role").is_none());
+    record.role = Some("interviewer".to_string());
+    assert_eq!(body(&record)["role"], "interviewer");
+    assert_eq!(body(&record)["speaker"], "Alex");
+}
+
+#[tokio::test]
+async fn csv_role_column_round_trips() {
+    let dir = std::env::temp_dir().join(format!("restream-speakers-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut records = fixture("test.csv").await;
+    SpeakerMapper::default().apply(&metadata(), &mut records);
+    let with_roles = dir.join("roles.csv");
+    let _ = std::fs::remove_file(&with_roles);
+    write_transcript_to_file(&with_roles, &records).unwrap();
+    let header = std::fs::read_to_string(&with_roles).unwrap();
+    assert!(header.starts_with("time,speaker,role,sentence\n"));
+    assert_eq!(
+        speakers(&load_transcript_from_file(&with_roles).await.unwrap()),
+        speakers(&records)
+    );
+
+    let plain = dir.join("plain.csv");
+    let _ = std::fs::remove_file(&plain);
+    write_transcript_to_file(&plain, &fixture("test.csv").await).unwrap();
+    assert!(
+        std::fs::read_to_string(&plain)
+            .unwrap()
+            .starts_with("time,speaker,sentence\n")
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
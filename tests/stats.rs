@@ -0,0 +1,78 @@
+mod common;
+
+use common::{fixture, record};
+use restream::stats::TranscriptStats;
+
+#[test]
+fn talk_time_turns_silence_and_overlaps() {
+    // 5 words take 2s at the nominal rate; 10 words take 4s
+    let records = vec![
+        record("00:00:00", "Alex", "one two three four five"),
+        record("00:00:02", "Alex", "one two three four five"),
+        // 6s gap after 2s of talk: 4s silence
+        record(
+            "00:00:08",
+            "Sam",
+            "one two three four five six seven eight nine ten",
+        ),
+        // Sam is cut off after 1s: an overlap
+        record("00:00:09", "Alex", "one two three four five"),
+    ];
+    let stats = TranscriptStats::compute(&records, 5);
+
+    assert_eq!(stats.records, 4);
+    assert_eq!(stats.words, 25);
+    assert_eq!(stats.turns, 3);
+    assert_eq!(stats.overlaps, 1);
+    assert_eq!(stats.longest_silence_secs, 4.0);
+    assert_eq!(stats.longest_silence_after_row, Some(2));
+    assert_eq!(stats.duration_secs, 11.0);
+    assert_eq!(stats.average_turn_words, 8.33);
+
+    let alex = &stats.speakers[0];
+    assert_eq!(
+        (alex.speaker.as_str(), alex.records, alex.turns, alex.words),
+        ("Alex", 3, 2, 15)
+    );
+    assert_eq!(alex.talk_secs, 6.0);
+    assert_eq!(alex.average_turn_secs, 3.0);
+    let sam = &stats.speakers[1];
+    assert_eq!(sam.talk_secs, 1.0);
+    assert_eq!(sam.talk_share, 0.14);
+
+    let timeline: Vec<_> = stats
+        .timeline
+        .iter()
+        .map(|bucket| (bucket.start_secs, bucket.records, bucket.words))
+        .collect();
+    assert_eq!(timeline, [(0, 2, 10), (5, 2, 15)]);
+}
+
+#[test]
+fn empty_transcripts_have_zero_stats() {
+    let stats = TranscriptStats::compute(&[], 60);
+    assert_eq!(stats.records, 0);
+    assert_eq!(stats.duration_secs, 0.0);
+    assert_eq!(stats.words_per_minute, 0.0);
+    assert!(stats.speakers.is_empty());
+    assert!(stats.timeline.is_empty());
+    assert_eq!(stats.longest_silence_after_row, None);
+}
+
+#[tokio::test]
+async fn fixture_speakers_and_timeline_add_up() {
+    let records = fixture("intake_call.csv").await;
+    let stats = TranscriptStats::compute(&records, 300);
+
+    assert_eq!(stats.records, records.len());
+    assert_eq!(
+        stats.speakers.iter().map(|s| s.records).sum::<usize>(),
+        stats.records
+    );
+    assert_eq!(
+        stats.timeline.iter().map(|b| b.words).sum::<usize>(),
+        stats.words
+    );
+    let share: f64 = stats.speakers.iter().map(|s| s.talk_share).sum();
+    assert!((share - 1.0).abs() < 0.02);
+}
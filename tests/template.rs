@@ -0,0 +1,147 @@
+mod common;
+
+use common::record;
+use restream::playback::PlaybackStep;
+use restream::session::{SessionContext, SessionId, SessionKind};
+use restream::template::{
+    PayloadTemplate, TemplateContext, TemplateScope, WebhookTargets, WebhookTemplates,
+};
+use serde_json::json;
+
+fn step() -> PlaybackStep {
+    PlaybackStep {
+        index: 4,
+        time: 75,
+        record: record("00:01:15", "Jordan", "Sounds good"),
+    }
+}
+
+#[test]
+fn whole_placeholders_keep_their_type_and_inline_ones_interpolate() {
+    let template = PayloadTemplate::parse(
+        &json!({
+            "index": "{{index}}",
+            "offset": "{{ offset }}",
+            "line": "[{{time}}] {{speaker}} said {{sentence}}",
+            "tags": ["{{session_kind}}", 1, null],
+            "session": "{{session_id}}"
+        }),
+        TemplateScope::Record,
+    )
+    .unwrap();
+
+    let session = SessionContext::new(
+        SessionKind::job_description(),
+        SessionId::Text("jd-9".to_string()),
+    );
+    let step = step();
+    let rendered = template.render(&TemplateContext {
+        session: &session,
+        step: Some(&step),
+    });
+
+    assert_eq!(
+        rendered,
+        json!({
+            "index": 4,
+            "offset": 75,
+            "line": "[00:01:15] Jordan said Sounds good",
+            "tags": ["job_description", 1, null],
+            "session": "jd-9"
+        })
+    );
+}
+
+#[test]
+fn role_placeholder_is_null_when_unknown() {
+    let template = PayloadTemplate::parse(
+        &json!({ "role": "{{role}}", "line": "{{speaker}} ({{role}})" }),
+        TemplateScope::Record,
+    )
+    .unwrap();
+    let session = SessionContext::new(SessionKind::job_description(), SessionId::Number(1));
+    let render = |step: &PlaybackStep| {
+        template.render(&TemplateContext {
+            session: &session,
+            step: Some(step),
+        })
+    };
+
+    let mut step = step();
+    assert_eq!(render(&step), json!({ "role": null, "line": "Jordan ()" }));
+    step.record.role = Some("candidate".to_string());
+    assert_eq!(
+        render(&step),
+        json!({ "role": "candidate", "line": "Jordan (candidate)" })
+    );
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    let parse = |value, scope| PayloadTemplate::parse(&value, scope);
+
+    assert!(parse(json!({ "a": "{{nope}}" }), TemplateScope::Record).is_err());
+    assert!(parse(json!({ "a": "{{speaker" }), TemplateScope::Record).is_err());
+    assert!(parse(json!(["{{speaker}}"]), TemplateScope::Record).is_err());
+    assert!(parse(json!({ "a": "{{sentence}}" }), TemplateScope::Completion).is_err());
+    assert!(parse(json!({ "a": "{{session_id}}" }), TemplateScope::Completion).is_ok());
+}
+
+#[test]
+fn default_templates_keep_the_broadcast_message_shape() {
+    let session = SessionContext::new(SessionKind::candidate_profile(), 3);
+
+    assert_eq!(
+        WebhookTemplates::default().payload(&session, &step()),
+        json!({
+            "job_description_enrichment_session": null,
+            "candidate_profile_enrichment_session": 3,
+            "body": { "time": "00:01:15", "speaker": "Jordan", "sentence": "Sounds good" }
+        })
+    );
+    assert_eq!(
+        WebhookTemplates::default().completion(&session),
+        json!({ "status": "complete", "message": "Broadcast completed" })
+    );
+}
+
+#[test]
+fn targets_file_is_validated_on_load() {
+    let dir = std::env::temp_dir().join(format!("restream-targets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let valid = dir.join("valid.json");
+    std::fs::write(
+        &valid,
+        json!({
+            "internal": { "url": "https://example.com/hook", "payload": { "text": "{{sentence}}" } }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let targets = WebhookTargets::load(&valid).unwrap();
+    assert_eq!(
+        targets.get("internal").unwrap().url,
+        "https://example.com/hook"
+    );
+    assert!(targets.get("missing").is_err());
+
+    let invalid = dir.join("invalid.json");
+    std::fs::write(
+        &invalid,
+        json!({
+            "internal": { "url": "https://example.com/hook", "completion": { "text": "{{sentence}}" } }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let error = WebhookTargets::load(&invalid).unwrap_err().to_string();
+    assert!(error.contains("internal"), "{}", error);
+
+    assert!(
+        WebhookTargets::load(&dir.join("absent.json"))
+            .unwrap()
+            .is_empty()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
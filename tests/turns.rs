@@ -0,0 +1,140 @@
+mod common;
+
+use common::record;
+use restream::chaos::ChaosOptions;
+use restream::clock::{Clock, VirtualClock};
+use restream::interface::TranscriptRecord;
+use restream::playback::Playback;
+use restream::script::ReplySender;
+use restream::turns::{TurnTaking, TurnTakingOptions, is_ack};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn records() -> Vec<TranscriptRecord> {
+    vec![
+        TranscriptRecord {
+            role: Some("hiring_manager".to_string()),
+            ..record("00:00:00", "Mike", "We need a developer.")
+        },
+        TranscriptRecord {
+            role: Some("recruiter".to_string()),
+            ..record("00:00:05", "Darryll", "Tell me more.")
+        },
+        TranscriptRecord {
+            role: Some("hiring_manager".to_string()),
+            ..record("00:00:10", "Mike", "React and Node.")
+        },
+    ]
+}
+
+fn start(echo_replies: bool) -> (ReplySender, TurnTaking, Arc<VirtualClock>) {
+    let clock = Arc::new(VirtualClock::new());
+    let (tx, rx) = mpsc::unbounded_channel();
+    let options = TurnTakingOptions::from_params(
+        vec!["hiring_manager".to_string()],
+        Some(20),
+        echo_replies,
+        None,
+    )
+    .unwrap();
+    let playback = Playback::new(records(), ChaosOptions::default(), clock.clone());
+    (
+        tx,
+        TurnTaking::new(playback, options, rx, clock.clone()),
+        clock,
+    )
+}
+
+#[tokio::test]
+async fn replies_are_echoed_and_delay_the_tape() {
+    let (tx, mut turns, clock) = start(true);
+    let first = turns.next().await.unwrap().unwrap();
+    assert_eq!(first.record.speaker, "Mike");
+
+    clock.advance(Duration::from_secs(3));
+    tx.send(r#"{"text": "What stack?"}"#.to_string()).unwrap();
+    let echo = turns.next().await.unwrap().unwrap();
+    assert_eq!(echo.record.speaker, "Agent");
+    assert_eq!(echo.record.sentence, "What stack?");
+    assert_eq!(echo.index, 0);
+
+    // The tape's five second gap runs from the reply
+    let second = turns.next().await.unwrap().unwrap();
+    assert_eq!(second.record.speaker, "Darryll");
+    assert_eq!(clock.elapsed(), Duration::from_secs(8));
+}
+
+#[tokio::test]
+async fn ack_continues_without_echo() {
+    let (tx, mut turns, _clock) = start(true);
+    turns.next().await.unwrap().unwrap();
+    tx.send(r#"{"type": "ack"}"#.to_string()).unwrap();
+    let next = turns.next().await.unwrap().unwrap();
+    assert_eq!(next.record.speaker, "Darryll");
+}
+
+#[tokio::test]
+async fn replies_are_not_echoed_unless_asked() {
+    let (tx, mut turns, _clock) = start(false);
+    turns.next().await.unwrap().unwrap();
+    tx.send("Sounds good".to_string()).unwrap();
+    let next = turns.next().await.unwrap().unwrap();
+    assert_eq!(next.record.speaker, "Darryll");
+}
+
+#[tokio::test]
+async fn silence_times_out() {
+    let (_tx, mut turns, clock) = start(true);
+    turns.next().await.unwrap().unwrap();
+    let next = turns.next().await.unwrap().unwrap();
+    assert_eq!(next.record.speaker, "Darryll");
+    assert_eq!(clock.elapsed(), Duration::from_secs(25));
+}
+
+#[tokio::test]
+async fn early_replies_are_stale() {
+    let (tx, mut turns, _clock) = start(true);
+    turns.next().await.unwrap().unwrap();
+    tx.send("ack".to_string()).unwrap();
+    turns.next().await.unwrap().unwrap();
+
+    // Sent before the third record, so it does not answer it
+    tx.send("Too early".to_string()).unwrap();
+    let third = turns.next().await.unwrap().unwrap();
+    assert_eq!(third.index, 2);
+    tx.send("On time".to_string()).unwrap();
+    let echo = turns.next().await.unwrap().unwrap();
+    assert_eq!(echo.record.sentence, "On time");
+    assert!(turns.next().await.is_none());
+}
+
+#[tokio::test]
+async fn closed_channel_does_not_wait() {
+    let (tx, mut turns, clock) = start(true);
+    drop(tx);
+    let mut speakers = Vec::new();
+    while let Some(step) = turns.next().await {
+        speakers.push(step.unwrap().record.speaker);
+    }
+    assert_eq!(speakers, ["Mike", "Darryll", "Mike"]);
+    assert_eq!(clock.elapsed(), Duration::from_secs(10));
+}
+
+#[test]
+fn options_match_names_or_roles() {
+    assert_eq!(
+        TurnTakingOptions::from_params(vec![], None, true, None),
+        None
+    );
+    let options =
+        TurnTakingOptions::from_params(vec!["darryll".to_string()], None, false, None).unwrap();
+    assert_eq!(options.timeout_secs, 30);
+    assert_eq!(options.reply_speaker, "Agent");
+    assert!(options.takes_turn(&records()[1]));
+    assert!(!options.takes_turn(&records()[0]));
+
+    assert!(is_ack(" ACK "));
+    assert!(!is_ack("acknowledged"));
+    assert!(!is_ack(r#"{"type": "reply"}"#));
+}
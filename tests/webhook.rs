@@ -0,0 +1,341 @@
+mod common;
+
+use common::{fixture, spawn_status_stub, spawn_webhook_stub};
+use restream::adapter::WebhookBroadcaster;
+use restream::batch::{BatchMode, BatchOptions};
+use restream::chaos::ChaosOptions;
+use restream::clock::{SharedClock, VirtualClock};
+use restream::interface::{Broadcaster, SessionContext, SessionKind};
+use restream::playback::Playback;
+use restream::report::{
+    ReportHandle, ReportStore, WebhookExpectations, WebhookReport, insert_report,
+};
+use restream::template::{WebhookTarget, WebhookTargetConfig, WebhookTemplates};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU16;
+use tokio_util::sync::CancellationToken;
+
+/// Replays test.csv to a local receiver and returns the decoded payloads it got
+async fn replay_to_stub(
+    session: SessionContext,
+    templates: WebhookTemplates,
+    batch: BatchOptions,
+) -> Vec<Value> {
+    let records = fixture("test.csv").await;
+    let clock: SharedClock = Arc::new(VirtualClock::new());
+    let (url, received) = spawn_webhook_stub(clock.clone()).await;
+
+    let broadcaster = Arc::new(
+        WebhookBroadcaster::new(url, reqwest::Client::new())
+            .with_templates(templates)
+            .with_batch(batch),
+    );
+    let report = broadcaster
+        .broadcast(
+            session,
+            Playback::new(records, ChaosOptions::default(), clock).into_stream(),
+            CancellationToken::new(),
+        )
+        .report()
+        .await;
+    assert!(report.is_completed(), "{:?}", report);
+
+    let received = received.lock().unwrap().clone();
+    received
+        .iter()
+        .map(|(_, body)| serde_json::from_str(body).unwrap())
+        .collect()
+}
+
+/// Checks the payload contract: both session fields present, only `field` set, then the completion message
+async fn assert_contract(payloads: &[Value], field: &str, other: &str, id: Value) {
+    let records = fixture("test.csv").await;
+    assert_eq!(payloads.len(), records.len() + 1);
+
+    for (payload, record) in payloads.iter().zip(&records) {
+        assert_eq!(
+            payload,
+            &json!({
+                field: id,
+                other: null,
+                "body": {
+                    "time": record.time,
+                    "speaker": record.speaker,
+                    "sentence": record.sentence
+                }
+            })
+        );
+    }
+
+    assert_eq!(
+        payloads.last().unwrap(),
+        &json!({ "status": "complete", "message": "Broadcast completed" })
+    );
+}
+
+#[tokio::test]
+async fn job_description_sessions_are_tagged_as_job_description() {
+    let payloads = replay_to_stub(
+        SessionContext::new(SessionKind::job_description(), 42),
+        WebhookTemplates::default(),
+        BatchOptions::default(),
+    )
+    .await;
+
+    assert_contract(
+        &payloads,
+        "job_description_enrichment_session",
+        "candidate_profile_enrichment_session",
+        json!(42),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn candidate_profile_sessions_are_tagged_as_candidate_profile() {
+    let payloads = replay_to_stub(
+        SessionContext::new(SessionKind::candidate_profile(), 7),
+        WebhookTemplates::default(),
+        BatchOptions::default(),
+    )
+    .await;
+
+    assert_contract(
+        &payloads,
+        "candidate_profile_enrichment_session",
+        "job_description_enrichment_session",
+        json!(7),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn target_templates_shape_record_and_completion_payloads() {
+    let target = WebhookTarget::from_config(
+        "zapier",
+        &WebhookTargetConfig {
+            url: "http://127.0.0.1/unused".to_string(),
+            payload: Some(json!({
+                "text": "{{speaker}}: {{sentence}}",
+                "meta": { "at": "{{offset}}", "row": "{{index}}", "stamp": "{{time}}" },
+                "{{session_kind}}_id": "{{session_id}}"
+            })),
+            completion: Some(json!({ "done": true, "session": "{{session_id}}" })),
+        },
+    )
+    .unwrap();
+
+    let payloads = replay_to_stub(
+        SessionContext::new(SessionKind::candidate_profile(), 7),
+        target.templates,
+        BatchOptions::default(),
+    )
+    .await;
+
+    let records = fixture("test.csv").await;
+    assert_eq!(payloads.len(), records.len() + 1);
+    assert_eq!(
+        payloads[1],
+        json!({
+            "text": format!("{}: {}", records[1].speaker, records[1].sentence),
+            "meta": { "at": 3, "row": 1, "stamp": records[1].time },
+            "candidate_profile_id": 7
+        })
+    );
+    assert_eq!(
+        payloads.last().unwrap(),
+        &json!({ "done": true, "session": 7 })
+    );
+}
+
+/// Replays test.csv in batches and returns the record indices of each array payload
+async fn replay_batched(batch: BatchOptions) -> Vec<Vec<String>> {
+    let payloads = replay_to_stub(
+        SessionContext::new(SessionKind::job_description(), 1),
+        WebhookTemplates::default(),
+        batch,
+    )
+    .await;
+
+    let (completion, batches) = payloads.split_last().unwrap();
+    assert_eq!(completion["status"], "complete");
+    batches
+        .iter()
+        .map(|batch| {
+            batch
+                .as_array()
+                .expect("batched payloads are arrays")
+                .iter()
+                .map(|message| {
+                    assert_eq!(message["job_description_enrichment_session"], 1);
+                    message["body"]["time"].as_str().unwrap().to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn batches_by_record_count() {
+    let records = fixture("test.csv").await;
+    let batches = replay_batched(BatchOptions {
+        mode: BatchMode::Records,
+        size: 4,
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(batches.len(), records.len().div_ceil(4));
+    assert!(batches[..batches.len() - 1].iter().all(|b| b.len() == 4));
+    assert_eq!(batches.concat().len(), records.len());
+}
+
+#[tokio::test]
+async fn batches_by_time_window() {
+    let records = fixture("test.csv").await;
+    let batches = replay_batched(BatchOptions {
+        mode: BatchMode::Window,
+        window_secs: 10,
+        ..Default::default()
+    })
+    .await;
+
+    for batch in &batches {
+        let windows: Vec<i32> = batch
+            .iter()
+            .map(|time| restream::transcript::parse_time_to_time(time) / 10)
+            .collect();
+        assert!(windows.iter().all(|w| *w == windows[0]), "{:?}", batch);
+    }
+    assert_eq!(batches.concat().len(), records.len());
+}
+
+#[tokio::test]
+async fn batches_by_speaker_turn() {
+    let records = fixture("test.csv").await;
+    let batches = replay_batched(BatchOptions {
+        mode: BatchMode::SpeakerTurn,
+        ..Default::default()
+    })
+    .await;
+
+    let turns = records
+        .windows(2)
+        .filter(|pair| pair[0].speaker != pair[1].speaker)
+        .count()
+        + 1;
+    assert_eq!(batches.len(), turns);
+    assert_eq!(batches.concat().len(), records.len());
+}
+
+#[tokio::test]
+async fn full_transcript_is_sent_once_at_the_end() {
+    let records = fixture("test.csv").await;
+    let batches = replay_batched(BatchOptions {
+        mode: BatchMode::Full,
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].len(), records.len());
+}
+
+#[tokio::test]
+async fn concurrent_broadcasts_keep_their_own_batches() {
+    let records = fixture("test.csv").await;
+    let clock: SharedClock = Arc::new(VirtualClock::new());
+    let (url, received) = spawn_webhook_stub(clock.clone()).await;
+    let broadcaster = Arc::new(
+        WebhookBroadcaster::new(url, reqwest::Client::new()).with_batch(BatchOptions {
+            mode: BatchMode::Full,
+            ..Default::default()
+        }),
+    );
+
+    let handles: Vec<_> = [
+        SessionKind::job_description(),
+        SessionKind::candidate_profile(),
+    ]
+    .into_iter()
+    .map(|kind| {
+        broadcaster.clone().broadcast(
+            SessionContext::new(kind, 1),
+            Playback::new(records.clone(), ChaosOptions::default(), clock.clone()).into_stream(),
+            CancellationToken::new(),
+        )
+    })
+    .collect();
+    for handle in handles {
+        let report = handle.report().await;
+        assert!(report.is_completed(), "{:?}", report);
+    }
+
+    let received = received.lock().unwrap().clone();
+    let batches: Vec<Vec<Value>> = received
+        .iter()
+        .filter_map(|(_, body)| match serde_json::from_str(body).unwrap() {
+            Value::Array(batch) => Some(batch),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(batches.len(), 2);
+    for batch in &batches {
+        assert_eq!(batch.len(), records.len());
+        let session = batch[0]["job_description_enrichment_session"].clone();
+        assert!(
+            batch
+                .iter()
+                .all(|payload| payload["job_description_enrichment_session"] == session)
+        );
+    }
+}
+
+#[tokio::test]
+async fn expected_error_statuses_do_not_stop_the_broadcast() {
+    let records = fixture("test.csv").await;
+    let url = spawn_status_stub(Arc::new(AtomicU16::new(422))).await;
+    let reports: ReportStore = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    let expectations = WebhookExpectations {
+        status_codes: vec![422],
+        ..WebhookExpectations::default()
+    };
+    insert_report(
+        &reports,
+        WebhookReport::new(
+            "report".to_string(),
+            "test.csv".to_string(),
+            url.clone(),
+            expectations,
+        ),
+    )
+    .await;
+
+    let broadcaster = Arc::new(
+        WebhookBroadcaster::new(url, reqwest::Client::new()).with_report(ReportHandle {
+            store: reports.clone(),
+            report_id: "report".to_string(),
+        }),
+    );
+    let report = broadcaster
+        .broadcast(
+            SessionContext::new(SessionKind::job_description(), 7),
+            Playback::new(
+                records.clone(),
+                ChaosOptions::default(),
+                Arc::new(VirtualClock::new()),
+            )
+            .into_stream(),
+            CancellationToken::new(),
+        )
+        .report()
+        .await;
+    assert!(report.is_completed(), "{:?}", report);
+
+    let reports = reports.lock().await;
+    let webhook_report = &reports["report"];
+    assert_eq!(webhook_report.deliveries.len(), records.len() + 1);
+    assert_eq!(webhook_report.passed, Some(true));
+}